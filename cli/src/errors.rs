@@ -0,0 +1,133 @@
+use anchor_client::anchor_lang::error::ERROR_CODE_OFFSET;
+use anchor_client::solana_sdk::instruction::InstructionError;
+use anchor_client::solana_sdk::transaction::TransactionError;
+use anyhow::{anyhow, Result};
+use keyring_network::common::error::KeyringError;
+
+// Errors of the Keyring program in the order of their codes. New errors are
+// only ever appended to `KeyringError`, so must be appended here as well.
+const KEYRING_ERRORS: &[KeyringError] = &[
+    KeyringError::ErrCallerDoesNotHaveRole,
+    KeyringError::ErrInvalidKeyRegistrationParams,
+    KeyringError::ErrInvalidCredentials,
+    KeyringError::ErrUnableToPackAuthMessage,
+    KeyringError::ErrInvalidSignatureLength,
+    KeyringError::ErrInvalidRecoveryID,
+    KeyringError::ErrInvalidPubkeyLength,
+    KeyringError::ErrInvalidSignature,
+    KeyringError::ErrKeyAlreadyRegistered,
+    KeyringError::ErrCostParameterZero,
+    KeyringError::ErrInCheckingCredentials,
+    KeyringError::ErrAuthMessageParameterOutOfRange,
+    KeyringError::ErrInvalidChainId,
+    KeyringError::ErrBreachedMaxActiveKeyLimit,
+    KeyringError::ErrKeyNotRegistered,
+    KeyringError::ErrInvalidKeyRotationParams,
+    KeyringError::ErrInvalidPolicyScope,
+    KeyringError::ErrPolicyNotAllowedForKey,
+    KeyringError::ErrInvalidKeyQuota,
+    KeyringError::ErrKeyQuotaExceeded,
+    KeyringError::ErrKeyRateLimitExceeded,
+    KeyringError::ErrInvalidPolicyThreshold,
+    KeyringError::ErrThresholdNotMet,
+    KeyringError::ErrDuplicateAttestationKey,
+    KeyringError::ErrInvalidAttestationAccounts,
+    KeyringError::ErrMalleableSignature,
+    KeyringError::ErrKeyMappingMismatch,
+    KeyringError::ErrSignatureMismatch,
+    KeyringError::ErrKeyRevoked,
+    KeyringError::ErrKeyNotYetValid,
+    KeyringError::ErrKeyExpired,
+    KeyringError::ErrCredentialExpired,
+    KeyringError::ErrEntityBlacklisted,
+    KeyringError::ErrExpiryNotIncreasing,
+    KeyringError::ErrNotUpgradeAuthority,
+    KeyringError::ErrProgramPaused,
+    KeyringError::ErrInvalidFeeVault,
+    KeyringError::ErrCredentialValidityTooLong,
+    KeyringError::ErrCostBelowMinimum,
+    KeyringError::ErrInvalidRoleExpiry,
+    KeyringError::ErrBreachedMaxRoleMemberLimit,
+    KeyringError::ErrLastAdmin,
+    KeyringError::ErrRoleNotDefined,
+    KeyringError::ErrInvalidRoleName,
+    KeyringError::ErrInvalidRoleDefinition,
+    KeyringError::ErrInvalidProposal,
+    KeyringError::ErrProposalNotReady,
+    KeyringError::ErrProposalAccountMismatch,
+    KeyringError::ErrProposalAlreadyApproved,
+    KeyringError::ErrProposalNotApproved,
+    KeyringError::ErrInvalidBlacklistExpiry,
+    KeyringError::ErrInvalidBatchSize,
+    KeyringError::ErrEntityMappingMismatch,
+    KeyringError::ErrLegacyInstructionUnsupported,
+];
+
+pub fn decode_error(code: u32) -> Option<KeyringError> {
+    let index = code.checked_sub(ERROR_CODE_OFFSET)?;
+    KEYRING_ERRORS.get(index as usize).copied()
+}
+
+pub fn format_error(error: KeyringError) -> String {
+    format!("{} ({}): {}", error.name(), u32::from(error), error)
+}
+
+// Accepts the code in decimal or as the `0x` prefixed hex printed in program
+// logs ("custom program error: 0x178e").
+pub fn parse_error_code(input: &str) -> Result<u32> {
+    match input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => input.parse(),
+    }
+    .map_err(|err| anyhow!("invalid error code {:?}: {}", input, err))
+}
+
+// Explains a failed transaction if it was rejected by the Keyring program.
+pub fn describe_transaction_error(error: &TransactionError) -> Option<String> {
+    match error {
+        TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+            decode_error(*code)
+                .map(|error| format!("instruction {} failed with {}", index, format_error(error)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_error() {
+        for (index, error) in KEYRING_ERRORS.iter().enumerate() {
+            assert_eq!(u32::from(*error), ERROR_CODE_OFFSET + index as u32);
+        }
+
+        let error = decode_error(parse_error_code("0x178e").unwrap()).unwrap();
+        assert_eq!(error.name(), "ErrKeyExpired");
+        assert_eq!(
+            format_error(error),
+            "ErrKeyExpired (6030): Signing key has expired"
+        );
+        assert!(decode_error(
+            parse_error_code(&(ERROR_CODE_OFFSET + KEYRING_ERRORS.len() as u32).to_string())
+                .unwrap()
+        )
+        .is_none());
+        assert!(decode_error(0).is_none());
+        assert!(parse_error_code("0xzz").is_err());
+
+        assert_eq!(
+            describe_transaction_error(&TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(u32::from(KeyringError::ErrEntityBlacklisted)),
+            ))
+            .unwrap(),
+            "instruction 1 failed with ErrEntityBlacklisted (6032): Entity is blacklisted for the policy"
+        );
+        assert!(describe_transaction_error(&TransactionError::AccountNotFound).is_none());
+    }
+}
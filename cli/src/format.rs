@@ -0,0 +1,277 @@
+use anchor_client::solana_sdk::keccak;
+use anchor_client::solana_sdk::secp256k1_recover::SECP256K1_PUBLIC_KEY_LENGTH;
+use anyhow::{anyhow, bail, Result};
+use keyring_network::common::types::{
+    policy_blacklist_manager_role, BlacklistReason, PolicyScope, APPROVER_ROLE,
+    BLACKLIST_MANAGER_ROLE, CANCELLER_ROLE, DEFAULT_ADMIN_ROLE, ETH_ADDRESS_LENGTH,
+    KEY_MANAGER_ROLE, OPERATOR_ROLE, PROPOSER_ROLE,
+};
+
+// Signing key as accepted on the command line: either the raw uncompressed
+// secp256k1 public key or the Ethereum address derived from it. Keys may be
+// registered under either form, an address is looked up in the active key
+// registry to find out which one is used.
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeyInput {
+    PublicKey(Vec<u8>),
+    Address([u8; ETH_ADDRESS_LENGTH]),
+}
+
+pub fn parse_hex(input: &str) -> Result<Vec<u8>> {
+    let input = input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+        .unwrap_or(input);
+    hex::decode(input).map_err(|err| anyhow!("invalid hex {:?}: {}", input, err))
+}
+
+pub fn parse_key(input: &str) -> Result<KeyInput> {
+    let bytes = parse_hex(input)?;
+    match bytes.len() {
+        SECP256K1_PUBLIC_KEY_LENGTH => Ok(KeyInput::PublicKey(bytes)),
+        // SEC1 uncompressed encoding
+        65 if bytes[0] == 4 => Ok(KeyInput::PublicKey(bytes[1..].to_vec())),
+        ETH_ADDRESS_LENGTH => Ok(KeyInput::Address(
+            bytes.try_into().expect("Length is checked above; qed"),
+        )),
+        len => bail!(
+            "expected a 64 byte public key, a 65 byte 0x04 prefixed public key or a 20 byte address, got {} bytes",
+            len
+        ),
+    }
+}
+
+pub fn eth_address(key: &[u8]) -> [u8; ETH_ADDRESS_LENGTH] {
+    if let Ok(address) = key.try_into() {
+        return address;
+    }
+    let hashed_key = keccak::hash(key).to_bytes();
+    hashed_key[12..]
+        .try_into()
+        .expect("Keccak hash is 32 bytes long; qed")
+}
+
+pub fn format_eth_address(address: &[u8; ETH_ADDRESS_LENGTH]) -> String {
+    format!("0x{}", hex::encode(address))
+}
+
+pub fn parse_role(input: &str) -> Result<[u8; 32]> {
+    match input {
+        "DEFAULT_ADMIN_ROLE" => Ok(DEFAULT_ADMIN_ROLE),
+        "KEY_MANAGER_ROLE" => Ok(KEY_MANAGER_ROLE),
+        "BLACKLIST_MANAGER_ROLE" => Ok(BLACKLIST_MANAGER_ROLE),
+        "OPERATOR_ROLE" => Ok(OPERATOR_ROLE),
+        "PROPOSER_ROLE" => Ok(PROPOSER_ROLE),
+        "CANCELLER_ROLE" => Ok(CANCELLER_ROLE),
+        "APPROVER_ROLE" => Ok(APPROVER_ROLE),
+        _ if input.starts_with("POLICY_") && input.ends_with("_BLACKLIST_MANAGER_ROLE") => {
+            let policy_id = &input["POLICY_".len()..input.len() - "_BLACKLIST_MANAGER_ROLE".len()];
+            let policy_id = policy_id
+                .parse()
+                .map_err(|_| anyhow!("invalid policy id in role {:?}", input))?;
+            Ok(policy_blacklist_manager_role(policy_id))
+        }
+        _ => parse_hex(input)?.try_into().map_err(|_| {
+            anyhow!(
+                "unknown role {:?}, expected a role name or 32 bytes of hex",
+                input
+            )
+        }),
+    }
+}
+
+pub fn role_name(role: &[u8; 32]) -> String {
+    match *role {
+        DEFAULT_ADMIN_ROLE => "DEFAULT_ADMIN_ROLE".to_string(),
+        KEY_MANAGER_ROLE => "KEY_MANAGER_ROLE".to_string(),
+        BLACKLIST_MANAGER_ROLE => "BLACKLIST_MANAGER_ROLE".to_string(),
+        OPERATOR_ROLE => "OPERATOR_ROLE".to_string(),
+        PROPOSER_ROLE => "PROPOSER_ROLE".to_string(),
+        CANCELLER_ROLE => "CANCELLER_ROLE".to_string(),
+        APPROVER_ROLE => "APPROVER_ROLE".to_string(),
+        _ => format!("0x{}", hex::encode(role)),
+    }
+}
+
+// Chain id is either hex encoded bytes or a decimal number which is encoded as
+// 4 big endian bytes, matching `scripts/initialize.ts`.
+pub fn parse_chain_id(input: &str) -> Result<Vec<u8>> {
+    if input.starts_with("0x") || input.starts_with("0X") {
+        return parse_hex(input);
+    }
+    let chain_id: u32 = input
+        .parse()
+        .map_err(|err| anyhow!("invalid chain id {:?}: {}", input, err))?;
+    Ok(chain_id.to_be_bytes().to_vec())
+}
+
+fn parse_policy_id(input: &str) -> Result<u64> {
+    input
+        .trim()
+        .parse()
+        .map_err(|err| anyhow!("invalid policy id {:?}: {}", input, err))
+}
+
+// Policy scope is either `any`, an inclusive range `<from>-<to>` or a comma
+// separated list of policy ids.
+pub fn parse_policy_scope(input: &str) -> Result<PolicyScope> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("any") {
+        return Ok(PolicyScope::Any);
+    }
+    if let Some((from, to)) = input.split_once('-') {
+        return Ok(PolicyScope::Range {
+            from: parse_policy_id(from)?,
+            to: parse_policy_id(to)?,
+        });
+    }
+    let policy_ids = input
+        .split(',')
+        .map(parse_policy_id)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(PolicyScope::List(policy_ids))
+}
+
+pub fn parse_blacklist_reason(input: &str) -> Result<BlacklistReason> {
+    match input.to_ascii_lowercase().as_str() {
+        "unspecified" => Ok(BlacklistReason::Unspecified),
+        "sanctions" => Ok(BlacklistReason::Sanctions),
+        "fraud-review" => Ok(BlacklistReason::FraudReview),
+        "court-order" => Ok(BlacklistReason::CourtOrder),
+        "other" => Ok(BlacklistReason::Other),
+        _ => bail!(
+            "unknown blacklist reason {:?}, expected unspecified, sanctions, fraud-review, court-order or other",
+            input
+        ),
+    }
+}
+
+pub fn format_blacklist_reason(reason: BlacklistReason) -> &'static str {
+    match reason {
+        BlacklistReason::Unspecified => "unspecified",
+        BlacklistReason::Sanctions => "sanctions",
+        BlacklistReason::FraudReview => "fraud-review",
+        BlacklistReason::CourtOrder => "court-order",
+        BlacklistReason::Other => "other",
+    }
+}
+
+pub fn format_policy_scope(policy_scope: &PolicyScope) -> String {
+    match policy_scope {
+        PolicyScope::Any => "any".to_string(),
+        PolicyScope::Range { from, to } => format!("{}-{}", from, to),
+        PolicyScope::List(policy_ids) => policy_ids
+            .iter()
+            .map(|policy_id| policy_id.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        let key = vec![7u8; 64];
+        assert_eq!(
+            parse_key(&hex::encode(&key)).unwrap(),
+            KeyInput::PublicKey(key.clone())
+        );
+
+        let mut prefixed_key = vec![4u8];
+        prefixed_key.extend_from_slice(&key);
+        assert_eq!(
+            parse_key(&format!("0x{}", hex::encode(&prefixed_key))).unwrap(),
+            KeyInput::PublicKey(key.clone())
+        );
+
+        let address = eth_address(&key);
+        assert_eq!(
+            parse_key(&format_eth_address(&address)).unwrap(),
+            KeyInput::Address(address)
+        );
+
+        assert_eq!(eth_address(&address), address);
+
+        assert!(parse_key("0x1234").is_err());
+        assert!(parse_key(&hex::encode([5u8; 65])).is_err());
+    }
+
+    #[test]
+    fn test_parse_role() {
+        assert_eq!(parse_role("KEY_MANAGER_ROLE").unwrap(), KEY_MANAGER_ROLE);
+        assert_eq!(
+            parse_role(&format!("0x{}", hex::encode(OPERATOR_ROLE))).unwrap(),
+            OPERATOR_ROLE
+        );
+        assert_eq!(role_name(&BLACKLIST_MANAGER_ROLE), "BLACKLIST_MANAGER_ROLE");
+        for name in [
+            "KEY_MANAGER_ROLE",
+            "PROPOSER_ROLE",
+            "CANCELLER_ROLE",
+            "APPROVER_ROLE",
+        ] {
+            let role = parse_role(name).unwrap();
+            assert_eq!(role, keccak::hash(name.as_bytes()).to_bytes());
+            assert_eq!(role_name(&role), name);
+        }
+        assert_eq!(
+            parse_role("POLICY_7_BLACKLIST_MANAGER_ROLE").unwrap(),
+            policy_blacklist_manager_role(7)
+        );
+        assert!(parse_role("POLICY_X_BLACKLIST_MANAGER_ROLE").is_err());
+        assert!(parse_role("ROOT").is_err());
+    }
+
+    #[test]
+    fn test_parse_chain_id() {
+        assert_eq!(
+            parse_chain_id("1915121141").unwrap(),
+            1915121141u32.to_be_bytes().to_vec()
+        );
+        assert_eq!(parse_chain_id("0x0102030405").unwrap(), vec![1, 2, 3, 4, 5]);
+        assert!(parse_chain_id("solana").is_err());
+    }
+
+    #[test]
+    fn test_parse_policy_scope() {
+        assert_eq!(parse_policy_scope("any").unwrap(), PolicyScope::Any);
+        assert_eq!(
+            parse_policy_scope("10-20").unwrap(),
+            PolicyScope::Range { from: 10, to: 20 }
+        );
+        assert_eq!(
+            parse_policy_scope("1, 5,9").unwrap(),
+            PolicyScope::List(vec![1, 5, 9])
+        );
+        assert_eq!(
+            format_policy_scope(&PolicyScope::List(vec![1, 5, 9])),
+            "1,5,9"
+        );
+        assert!(parse_policy_scope("1-").is_err());
+        assert!(parse_policy_scope("").is_err());
+    }
+
+    #[test]
+    fn test_parse_blacklist_reason() {
+        for reason in [
+            BlacklistReason::Unspecified,
+            BlacklistReason::Sanctions,
+            BlacklistReason::FraudReview,
+            BlacklistReason::CourtOrder,
+            BlacklistReason::Other,
+        ] {
+            assert_eq!(
+                parse_blacklist_reason(format_blacklist_reason(reason)).unwrap(),
+                reason
+            );
+        }
+        assert_eq!(
+            parse_blacklist_reason("Sanctions").unwrap(),
+            BlacklistReason::Sanctions
+        );
+        assert!(parse_blacklist_reason("fraud").is_err());
+    }
+}
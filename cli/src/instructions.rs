@@ -0,0 +1,574 @@
+use crate::pda;
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::{Id, InstructionData, ToAccountMetas};
+use anchor_client::solana_sdk::instruction::{AccountMeta, Instruction};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use keyring_network::common::types::{
+    BlacklistEntry, BlacklistReason, KeyQuota, PolicyScope, ProgramConfig, Proposal,
+    ProposalAccount, APPROVER_ROLE, CANCELLER_ROLE, DEFAULT_ADMIN_ROLE, KEY_MANAGER_ROLE,
+    OPERATOR_ROLE, PROPOSER_ROLE,
+};
+use keyring_network::{accounts, instruction};
+
+fn build(
+    program_id: &Pubkey,
+    accounts: impl ToAccountMetas,
+    args: impl InstructionData,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+pub fn initialize(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    chain_id: Vec<u8>,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::Initialize {
+            program_state: pda::program_state(program_id, instance),
+            key_registry: pda::key_registry(program_id, instance),
+            default_admin_role: pda::role(program_id, instance, &DEFAULT_ADMIN_ROLE, signer),
+            default_admin_role_members: pda::role_members(
+                program_id,
+                instance,
+                &DEFAULT_ADMIN_ROLE,
+            ),
+            signer: *signer,
+            program_data: Some(pda::program_data(program_id)),
+            system_program: System::id(),
+        },
+        instruction::InitializeV2 {
+            instance: *instance,
+            chain_id,
+        },
+    )
+}
+
+pub fn update_config(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    chain_id: Vec<u8>,
+    config: ProgramConfig,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UpdateConfig {
+            program_state: pda::program_state(program_id, instance),
+            default_admin_role: pda::role(program_id, instance, &DEFAULT_ADMIN_ROLE, signer),
+            signer: *signer,
+            system_program: System::id(),
+        },
+        instruction::UpdateConfig {
+            instance: *instance,
+            chain_id,
+            config,
+        },
+    )
+}
+
+// `admin_role` is the role administering `role`, see `RoleDefinition`
+pub fn manage_roles(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    admin_role: &[u8; 32],
+    role: [u8; 32],
+    user: Pubkey,
+    has_role: bool,
+    expires_at: Option<i64>,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ManageRole {
+            role_definition: pda::role_definition(program_id, instance, &role),
+            admin_role: pda::role(program_id, instance, admin_role, signer),
+            role: pda::role(program_id, instance, &role, &user),
+            role_members: pda::role_members(program_id, instance, &role),
+            signer: *signer,
+            system_program: System::id(),
+        },
+        instruction::ManageRolesV2 {
+            instance: *instance,
+            role,
+            user,
+            has_role,
+            expires_at,
+        },
+    )
+}
+
+pub fn define_role(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    role: [u8; 32],
+    name: String,
+    admin_role: [u8; 32],
+    description_hash: [u8; 32],
+) -> Instruction {
+    build(
+        program_id,
+        accounts::DefineRole {
+            role_definition: pda::role_definition(program_id, instance, &role),
+            default_admin_role: pda::role(program_id, instance, &DEFAULT_ADMIN_ROLE, signer),
+            signer: *signer,
+            system_program: System::id(),
+        },
+        instruction::DefineRole {
+            instance: *instance,
+            role,
+            name,
+            admin_role,
+            description_hash,
+        },
+    )
+}
+
+pub fn register_key(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    key: Vec<u8>,
+    valid_from: u64,
+    valid_to: u64,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::RegisterKey {
+            key_registry: pda::key_registry(program_id, instance),
+            signer: *signer,
+            key_manager_role: pda::role(program_id, instance, &KEY_MANAGER_ROLE, signer),
+            key_mapping: pda::key_mapping(program_id, instance, &key),
+            system_program: System::id(),
+        },
+        instruction::RegisterKeyV2 {
+            instance: *instance,
+            key,
+            valid_from,
+            valid_to,
+        },
+    )
+}
+
+pub fn revoke_key(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    key: Vec<u8>,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::RevokeKey {
+            key_registry: pda::key_registry(program_id, instance),
+            signer: *signer,
+            key_manager_role: pda::role(program_id, instance, &KEY_MANAGER_ROLE, signer),
+            key_mapping: pda::key_mapping(program_id, instance, &key),
+            system_program: System::id(),
+        },
+        instruction::RevokeKeyV2 {
+            instance: *instance,
+            key,
+        },
+    )
+}
+
+pub fn rotate_key(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    old_key: Vec<u8>,
+    new_key: Vec<u8>,
+    valid_from: u64,
+    valid_to: u64,
+    overlap: u64,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::RotateKey {
+            key_registry: pda::key_registry(program_id, instance),
+            signer: *signer,
+            key_manager_role: pda::role(program_id, instance, &KEY_MANAGER_ROLE, signer),
+            old_key_mapping: pda::key_mapping(program_id, instance, &old_key),
+            new_key_mapping: pda::key_mapping(program_id, instance, &new_key),
+            system_program: System::id(),
+        },
+        instruction::RotateKey {
+            instance: *instance,
+            old_key,
+            new_key,
+            valid_from,
+            valid_to,
+            overlap,
+        },
+    )
+}
+
+pub fn update_key_validity(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    key: Vec<u8>,
+    valid_from: u64,
+    valid_to: u64,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UpdateKeyValidity {
+            signer: *signer,
+            key_manager_role: pda::role(program_id, instance, &KEY_MANAGER_ROLE, signer),
+            key_mapping: pda::key_mapping(program_id, instance, &key),
+            system_program: System::id(),
+        },
+        instruction::UpdateKeyValidity {
+            instance: *instance,
+            key,
+            valid_from,
+            valid_to,
+        },
+    )
+}
+
+pub fn update_key_policy_scope(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    key: Vec<u8>,
+    policy_scope: PolicyScope,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UpdateKeyPolicyScope {
+            signer: *signer,
+            key_manager_role: pda::role(program_id, instance, &KEY_MANAGER_ROLE, signer),
+            key_mapping: pda::key_mapping(program_id, instance, &key),
+            system_program: System::id(),
+        },
+        instruction::UpdateKeyPolicyScope {
+            instance: *instance,
+            key,
+            policy_scope,
+        },
+    )
+}
+
+pub fn update_key_quota(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    key: Vec<u8>,
+    quota: KeyQuota,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UpdateKeyQuota {
+            signer: *signer,
+            key_manager_role: pda::role(program_id, instance, &KEY_MANAGER_ROLE, signer),
+            key_mapping: pda::key_mapping(program_id, instance, &key),
+            system_program: System::id(),
+        },
+        instruction::UpdateKeyQuota {
+            instance: *instance,
+            key,
+            quota,
+        },
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn blacklist_entity(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    manager_role: &[u8; 32],
+    policy_id: u64,
+    trading_address: Pubkey,
+    reason: BlacklistReason,
+    until: Option<u64>,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::BlacklistEntity {
+            signer: *signer,
+            blacklist_manager_role: pda::role(program_id, instance, manager_role, signer),
+            entity_mapping: pda::entity_mapping(program_id, instance, policy_id, &trading_address),
+            system_program: System::id(),
+        },
+        instruction::BlacklistEntityV2 {
+            instance: *instance,
+            policy_id,
+            trading_address,
+            reason,
+            until,
+        },
+    )
+}
+
+pub fn unblacklist_entity(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    manager_role: &[u8; 32],
+    policy_id: u64,
+    trading_address: Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UnblacklistEntity {
+            signer: *signer,
+            blacklist_manager_role: pda::role(program_id, instance, manager_role, signer),
+            entity_mapping: pda::entity_mapping(program_id, instance, policy_id, &trading_address),
+            system_program: System::id(),
+        },
+        instruction::UnblacklistEntityV2 {
+            instance: *instance,
+            policy_id,
+            trading_address,
+        },
+    )
+}
+
+// Entity mappings of the entries, passed as remaining accounts
+fn entity_mappings(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    entries: &[BlacklistEntry],
+) -> Vec<AccountMeta> {
+    entries
+        .iter()
+        .map(|entry| {
+            AccountMeta::new(
+                pda::entity_mapping(
+                    program_id,
+                    instance,
+                    entry.policy_id,
+                    &entry.trading_address,
+                ),
+                false,
+            )
+        })
+        .collect()
+}
+
+pub fn batch_blacklist_entities(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    manager_role: &[u8; 32],
+    entries: Vec<BlacklistEntry>,
+    reason: BlacklistReason,
+    until: Option<u64>,
+) -> Instruction {
+    let mut instruction = build(
+        program_id,
+        accounts::BatchBlacklistEntities {
+            signer: *signer,
+            blacklist_manager_role: pda::role(program_id, instance, manager_role, signer),
+            system_program: System::id(),
+        },
+        instruction::BatchBlacklistEntities {
+            instance: *instance,
+            entries: entries.clone(),
+            reason,
+            until,
+        },
+    );
+    instruction
+        .accounts
+        .extend(entity_mappings(program_id, instance, &entries));
+    instruction
+}
+
+pub fn batch_unblacklist_entities(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    manager_role: &[u8; 32],
+    entries: Vec<BlacklistEntry>,
+) -> Instruction {
+    let mut instruction = build(
+        program_id,
+        accounts::BatchUnblacklistEntities {
+            signer: *signer,
+            blacklist_manager_role: pda::role(program_id, instance, manager_role, signer),
+            system_program: System::id(),
+        },
+        instruction::BatchUnblacklistEntities {
+            instance: *instance,
+            entries: entries.clone(),
+        },
+    );
+    instruction
+        .accounts
+        .extend(entity_mappings(program_id, instance, &entries));
+    instruction
+}
+
+pub fn set_policy_threshold(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    policy_id: u64,
+    threshold: u8,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::SetPolicyThreshold {
+            signer: *signer,
+            default_admin_role: pda::role(program_id, instance, &DEFAULT_ADMIN_ROLE, signer),
+            policy_config: pda::policy_config(program_id, instance, policy_id),
+            program_state: pda::program_state(program_id, instance),
+            system_program: System::id(),
+        },
+        instruction::SetPolicyThreshold {
+            instance: *instance,
+            policy_id,
+            threshold,
+        },
+    )
+}
+
+pub fn collect_fees(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    receiver: Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::CollectFees {
+            receiver_account: receiver,
+            signer: *signer,
+            operator_role: pda::role(program_id, instance, &OPERATOR_ROLE, signer),
+            program_state: pda::program_state(program_id, instance),
+        },
+        instruction::CollectFeesV2 {
+            instance: *instance,
+        },
+    )
+}
+
+// Schedules `proposed`, built with the timelock authority as signer, to be
+// executed once the timelock delay has passed
+pub fn schedule_proposal(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    proposal_id: u64,
+    proposed: Instruction,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ScheduleProposal {
+            proposal: pda::proposal(program_id, instance, proposal_id),
+            program_state: pda::program_state(program_id, instance),
+            proposer_role: pda::role(program_id, instance, &PROPOSER_ROLE, signer),
+            signer: *signer,
+            system_program: System::id(),
+        },
+        instruction::ScheduleProposal {
+            instance: *instance,
+            proposal_id,
+            accounts: proposed
+                .accounts
+                .into_iter()
+                .map(|account| ProposalAccount {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: proposed.data,
+        },
+    )
+}
+
+pub fn cancel_proposal(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    proposal_id: u64,
+    proposer: Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::CancelProposal {
+            proposal: pda::proposal(program_id, instance, proposal_id),
+            proposer,
+            canceller_role: pda::role(program_id, instance, &CANCELLER_ROLE, signer),
+            signer: *signer,
+        },
+        instruction::CancelProposal {
+            instance: *instance,
+            proposal_id,
+        },
+    )
+}
+
+pub fn approve_proposal(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    proposal_id: u64,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ApproveProposal {
+            proposal: pda::proposal(program_id, instance, proposal_id),
+            program_state: pda::program_state(program_id, instance),
+            approver_role: pda::role(program_id, instance, &APPROVER_ROLE, signer),
+            signer: *signer,
+        },
+        instruction::ApproveProposal {
+            instance: *instance,
+            proposal_id,
+        },
+    )
+}
+
+pub fn execute_proposal(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+    proposal_id: u64,
+    proposal: &Proposal,
+) -> Instruction {
+    let mut instruction = build(
+        program_id,
+        accounts::ExecuteProposal {
+            proposal: pda::proposal(program_id, instance, proposal_id),
+            proposer: proposal.proposer,
+            program_state: pda::program_state(program_id, instance),
+            signer: *signer,
+            keyring_program: *program_id,
+        },
+        instruction::ExecuteProposal {
+            instance: *instance,
+            proposal_id,
+        },
+    );
+    // The timelock authority signs by the program, not the transaction
+    instruction
+        .accounts
+        .extend(proposal.accounts.iter().map(|account| AccountMeta {
+            pubkey: account.pubkey,
+            is_signer: false,
+            is_writable: account.is_writable,
+        }));
+    // Approvals only count while the approver holds the approver role
+    instruction
+        .accounts
+        .extend(proposal.approvals.iter().map(|approver| {
+            AccountMeta::new_readonly(
+                pda::role(program_id, instance, &APPROVER_ROLE, approver),
+                false,
+            )
+        }));
+    instruction
+}
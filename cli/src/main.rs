@@ -0,0 +1,1145 @@
+mod errors;
+mod format;
+mod instructions;
+mod pda;
+mod tx;
+
+use anchor_client::anchor_lang::prelude::Clock;
+use anchor_client::anchor_lang::AccountDeserialize;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::hash::Hash;
+use anchor_client::solana_sdk::instruction::AccountMeta;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use anchor_client::solana_sdk::sysvar::clock;
+use anchor_client::Cluster;
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use format::{
+    eth_address, format_blacklist_reason, format_eth_address, format_policy_scope,
+    parse_blacklist_reason, parse_chain_id, parse_hex, parse_key, parse_policy_scope, parse_role,
+    role_name, KeyInput,
+};
+use keyring_network::common::types::{
+    policy_blacklist_manager_role, BlacklistEntry, EntityData, KeyEntry, KeyQuota, KeyRegistry,
+    PolicyConfig, ProgramState, Proposal, Role, RoleDefinition, RoleMembers,
+    BLACKLIST_MANAGER_ROLE, BUILTIN_ROLES, DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE,
+    MAX_BLACKLIST_BATCH_SIZE, PAUSE_CREDENTIALS, PAUSE_FEE_COLLECTION,
+};
+use std::str::FromStr;
+use tx::{DurableNonce, Mode, Submitter};
+
+#[derive(Parser)]
+#[clap(
+    name = "keyring-cli",
+    version,
+    about = "Admin tool for the Keyring program"
+)]
+struct Cli {
+    /// Cluster name (mainnet, devnet, localnet, ...) or RPC url
+    #[clap(long, short = 'u', global = true, default_value = "localnet")]
+    url: String,
+    /// Path to the keypair signing and paying for transactions
+    #[clap(long, short = 'k', global = true)]
+    keypair: Option<String>,
+    /// Account holding the role required by the instruction, e.g. a multisig
+    /// vault. Defaults to the keypair
+    #[clap(long, global = true)]
+    authority: Option<Pubkey>,
+    /// Account paying transaction fees. Defaults to the authority
+    #[clap(long, global = true)]
+    fee_payer: Option<Pubkey>,
+    /// Keyring program id
+    #[clap(long, global = true, default_value_t = keyring_network::ID)]
+    program_id: Pubkey,
+    /// Keyring instance, defaults to the instance with the original account layout
+    #[clap(long, global = true, default_value_t = DEFAULT_INSTANCE)]
+    instance: Pubkey,
+    /// Simulate the transaction instead of sending it
+    #[clap(long, global = true, conflicts_with_all = &["sign-only", "export"])]
+    dry_run: bool,
+    /// Print the transaction signed by the keypair instead of sending it
+    #[clap(long, global = true, conflicts_with = "export")]
+    sign_only: bool,
+    /// Print the unsigned transaction instead of sending it
+    #[clap(long, global = true)]
+    export: bool,
+    /// Recent blockhash (or nonce value when using --nonce) to sign with,
+    /// required to sign without network access
+    #[clap(long, global = true)]
+    blockhash: Option<Hash>,
+    /// Durable nonce account to use instead of a recent blockhash
+    #[clap(long, global = true)]
+    nonce: Option<Pubkey>,
+    /// Take Ethereum addresses as keys registered by their address instead of
+    /// looking up their public key in the key registry
+    #[clap(long, global = true)]
+    address_key: bool,
+    /// Authority of the durable nonce account. Defaults to the authority
+    #[clap(long, global = true, requires = "nonce")]
+    nonce_authority: Option<Pubkey>,
+    /// Schedule the instruction as the timelock proposal with this id instead
+    /// of sending it. It is signed by the timelock authority once executed
+    #[clap(long, global = true)]
+    schedule: Option<u64>,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Initialize program state and grant the admin role to the signer, which
+    /// must be the program upgrade authority
+    Initialize {
+        /// Decimal chain id encoded as 4 big endian bytes, or 0x prefixed hex
+        #[clap(long)]
+        chain_id: String,
+    },
+    /// Change global parameters, options which are not given keep their value
+    UpdateConfig {
+        /// Decimal chain id encoded as 4 big endian bytes, or 0x prefixed hex
+        #[clap(long)]
+        chain_id: Option<String>,
+        /// Only account fees can be collected to
+        #[clap(long, conflicts_with = "clear-fee-vault")]
+        fee_vault: Option<Pubkey>,
+        /// Allow fees to be collected to any account
+        #[clap(long)]
+        clear_fee_vault: bool,
+        /// Pause (true) or resume (false) credential creation
+        #[clap(long)]
+        pause_credentials: Option<bool>,
+        /// Pause (true) or resume (false) fee collection
+        #[clap(long)]
+        pause_fee_collection: Option<bool>,
+        /// Longest credential lifetime in seconds, 0 for no limit
+        #[clap(long)]
+        max_credential_validity: Option<u64>,
+        /// Smallest credential cost in lamports
+        #[clap(long)]
+        min_credential_cost: Option<u64>,
+        /// Seconds before scheduled proposals can be executed
+        #[clap(long)]
+        timelock_delay: Option<u64>,
+        /// Approvals of APPROVER_ROLE members required to execute proposals,
+        /// 0 to not require approvals
+        #[clap(long)]
+        approval_threshold: Option<u8>,
+    },
+    /// Grant a role to a user
+    GrantRole {
+        /// Role name (e.g. KEY_MANAGER_ROLE) or 32 bytes of hex
+        #[clap(long)]
+        role: String,
+        #[clap(long)]
+        user: Pubkey,
+        /// Unix timestamp from which the grant is no longer honored
+        #[clap(long)]
+        expires_at: Option<i64>,
+        /// Role administering the role, read from its definition if not set
+        #[clap(long)]
+        admin_role: Option<String>,
+        /// Permanent default admin which remains when granting the own admin
+        /// role with an expiry, looked up in the role members if not set
+        #[clap(long)]
+        other_admin: Option<Pubkey>,
+    },
+    /// Revoke a role from a user
+    RevokeRole {
+        /// Role name (e.g. KEY_MANAGER_ROLE) or 32 bytes of hex
+        #[clap(long)]
+        role: String,
+        #[clap(long)]
+        user: Pubkey,
+        /// Role administering the role, read from its definition if not set
+        #[clap(long)]
+        admin_role: Option<String>,
+        /// Permanent default admin which remains when revoking the own admin
+        /// role, looked up in the role members if not set
+        #[clap(long)]
+        other_admin: Option<Pubkey>,
+    },
+    /// Define a role so that it can be granted, or update its definition
+    DefineRole {
+        /// Role name (e.g. KEY_MANAGER_ROLE) or 32 bytes of hex
+        #[clap(long)]
+        role: String,
+        /// Human readable name, at most 32 bytes
+        #[clap(long)]
+        name: String,
+        /// Role whose members may grant the role
+        #[clap(long, default_value = "DEFAULT_ADMIN_ROLE")]
+        admin_role: String,
+        /// 32 bytes of hex, the hash of the off-chain description
+        #[clap(long)]
+        description_hash: Option<String>,
+    },
+    /// Register a signing key
+    RegisterKey {
+        /// Hex encoded secp256k1 public key or Ethereum address to register
+        #[clap(long)]
+        key: String,
+        #[clap(long)]
+        valid_from: u64,
+        #[clap(long)]
+        valid_to: u64,
+    },
+    /// Revoke a signing key
+    RevokeKey {
+        /// Hex encoded secp256k1 public key or Ethereum address
+        #[clap(long)]
+        key: String,
+    },
+    /// Atomically replace a signing key
+    RotateKey {
+        /// Hex encoded secp256k1 public key or Ethereum address
+        #[clap(long)]
+        old_key: String,
+        /// Hex encoded secp256k1 public key or Ethereum address to register
+        #[clap(long)]
+        new_key: String,
+        #[clap(long)]
+        valid_from: u64,
+        #[clap(long)]
+        valid_to: u64,
+        /// Seconds for which the old key stays valid after the rotation. The
+        /// old key then stays in the active keys until it is revoked.
+        #[clap(long, default_value_t = 0)]
+        overlap: u64,
+    },
+    /// Change the validity window of a registered key
+    UpdateKeyValidity {
+        /// Hex encoded secp256k1 public key or Ethereum address
+        #[clap(long)]
+        key: String,
+        #[clap(long)]
+        valid_from: u64,
+        #[clap(long)]
+        valid_to: u64,
+    },
+    /// Restrict the policies a registered key may sign credentials for
+    UpdateKeyPolicyScope {
+        /// Hex encoded secp256k1 public key or Ethereum address
+        #[clap(long)]
+        key: String,
+        /// `any`, an inclusive range of policy ids `<from>-<to>` or a comma
+        /// separated list of policy ids
+        #[clap(long)]
+        policy_scope: String,
+    },
+    /// Limit the number of credentials a registered key can sign
+    UpdateKeyQuota {
+        /// Hex encoded secp256k1 public key or Ethereum address
+        #[clap(long)]
+        key: String,
+        /// Maximum number of credentials over the lifetime of the key, 0 for no limit
+        #[clap(long, default_value_t = 0)]
+        max_credentials: u64,
+        /// Length of the rate limit window in seconds, 0 for no rate limit
+        #[clap(long, default_value_t = 0)]
+        window_duration: u64,
+        /// Maximum number of credentials per rate limit window
+        #[clap(long, default_value_t = 0)]
+        max_credentials_per_window: u64,
+    },
+    /// Blacklist a trading address for a policy
+    Blacklist {
+        #[clap(long)]
+        policy_id: u64,
+        /// Repeat to blacklist several trading addresses in one instruction
+        #[clap(long, required = true)]
+        trading_address: Vec<Pubkey>,
+        /// Reason: unspecified, sanctions, fraud-review, court-order or other
+        #[clap(long, default_value = "unspecified")]
+        reason: String,
+        /// Unix timestamp from which the blacklisting is lifted, permanent if
+        /// not set
+        #[clap(long)]
+        until: Option<u64>,
+        /// Role the authority manages the blacklist with, BLACKLIST_MANAGER_ROLE
+        /// or the blacklist manager role of the policy. Looked up if not set
+        #[clap(long)]
+        manager_role: Option<String>,
+    },
+    /// Remove a trading address from the blacklist of a policy
+    Unblacklist {
+        #[clap(long)]
+        policy_id: u64,
+        /// Repeat to unblacklist several trading addresses in one instruction
+        #[clap(long, required = true)]
+        trading_address: Vec<Pubkey>,
+        /// Role the authority manages the blacklist with, BLACKLIST_MANAGER_ROLE
+        /// or the blacklist manager role of the policy. Looked up if not set
+        #[clap(long)]
+        manager_role: Option<String>,
+    },
+    /// Transfer collected fees to a receiver
+    CollectFees {
+        #[clap(long)]
+        receiver: Pubkey,
+    },
+    /// Require credentials of a policy to be signed by several distinct keys
+    SetPolicyThreshold {
+        #[clap(long)]
+        policy_id: u64,
+        #[clap(long)]
+        threshold: u8,
+    },
+    /// Cancel a scheduled timelock proposal
+    CancelProposal {
+        #[clap(long)]
+        proposal_id: u64,
+    },
+    /// Approve a scheduled timelock proposal
+    ApproveProposal {
+        #[clap(long)]
+        proposal_id: u64,
+    },
+    /// Execute a timelock proposal whose delay has passed
+    ExecuteProposal {
+        #[clap(long)]
+        proposal_id: u64,
+    },
+    /// Show the credential of a trading address for a policy
+    Credential {
+        #[clap(long)]
+        policy_id: u64,
+        #[clap(long)]
+        trading_address: Pubkey,
+    },
+    /// Show the signature threshold of a policy
+    Policy {
+        #[clap(long)]
+        policy_id: u64,
+    },
+    /// Show a signing key and its registration
+    Key {
+        /// Hex encoded secp256k1 public key or Ethereum address
+        #[clap(long)]
+        key: String,
+    },
+    /// List active signing keys
+    Keys,
+    /// Show whether a user holds a role
+    Role {
+        /// Role name (e.g. KEY_MANAGER_ROLE) or 32 bytes of hex
+        #[clap(long)]
+        role: String,
+        #[clap(long)]
+        user: Pubkey,
+    },
+    /// Show the definition of a role
+    RoleDefinition {
+        /// Role name (e.g. KEY_MANAGER_ROLE) or 32 bytes of hex
+        #[clap(long)]
+        role: String,
+    },
+    /// List the users a role is granted to
+    RoleMembers {
+        /// Role name (e.g. KEY_MANAGER_ROLE) or 32 bytes of hex
+        #[clap(long)]
+        role: String,
+    },
+    /// Show a scheduled timelock proposal
+    Proposal {
+        #[clap(long)]
+        proposal_id: u64,
+    },
+    /// Show program state
+    State,
+    /// Explain a custom program error code, in decimal or 0x prefixed hex
+    DecodeError { code: String },
+}
+
+fn rpc_url(url: &str) -> String {
+    Cluster::from_str(url)
+        .map(|cluster| cluster.url().to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+fn load_keypair(path: Option<&str>) -> Result<Keypair> {
+    let path = match path {
+        Some(path) => path.to_string(),
+        None => {
+            let home = std::env::var("HOME").map_err(|_| anyhow!("HOME is not set"))?;
+            format!("{}/.config/solana/id.json", home)
+        }
+    };
+    read_keypair_file(&path).map_err(|err| anyhow!("unable to read keypair {}: {}", path, err))
+}
+
+fn fetch<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<Option<T>> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())
+        .map_err(|err| anyhow!("unable to fetch account {}: {}", address, err))?
+        .value;
+    match account {
+        Some(account) => Ok(Some(T::try_deserialize(&mut account.data.as_slice())?)),
+        None => Ok(None),
+    }
+}
+
+fn timestamp(rpc: &RpcClient) -> Result<u64> {
+    let clock = rpc.get_account(&clock::ID)?;
+    let clock: Clock = bincode::deserialize(&clock.data)?;
+    Ok(clock.unix_timestamp.try_into()?)
+}
+
+// Fails when signing without network access, naming the option which avoids
+// looking up the account state
+fn require_network(offline: bool, option: &str) -> Result<()> {
+    if offline {
+        return Err(anyhow!(
+            "{} is required to sign without network access",
+            option
+        ));
+    }
+    Ok(())
+}
+
+// Resolves Ethereum addresses through the active key registry, unless
+// `address_key` is set. An address that does not belong to an active public
+// key is taken as a key registered by its address.
+fn resolve_key(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    input: &str,
+    address_key: bool,
+    offline: bool,
+) -> Result<Vec<u8>> {
+    match parse_key(input)? {
+        KeyInput::PublicKey(key) => Ok(key),
+        KeyInput::Address(address) if address_key => Ok(address.to_vec()),
+        KeyInput::Address(address) => {
+            require_network(offline, "--address-key or the public key")?;
+            let registry: KeyRegistry = fetch(rpc, &pda::key_registry(program_id, instance))?
+                .ok_or_else(|| anyhow!("key registry does not exist"))?;
+            Ok(registry
+                .active_keys
+                .into_iter()
+                .find(|key| eth_address(key) == address)
+                .unwrap_or_else(|| address.to_vec()))
+        }
+    }
+}
+
+// Role administering the role, built-in roles without a definition are
+// administered by the default admin role.
+fn role_admin(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    role: &[u8; 32],
+) -> Result<[u8; 32]> {
+    let definition: Option<RoleDefinition> =
+        fetch(rpc, &pda::role_definition(program_id, instance, role))?;
+    Ok(definition.map_or(DEFAULT_ADMIN_ROLE, |definition| definition.admin_role))
+}
+
+// Role the authority manages the blacklist of the policy with, the blacklist
+// manager role of the policy if the authority only holds that one
+fn blacklist_manager_role(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    authority: &Pubkey,
+    policy_id: u64,
+) -> Result<[u8; 32]> {
+    let now: i64 = timestamp(rpc)?.try_into()?;
+    let policy_role = policy_blacklist_manager_role(policy_id);
+    let holds = |role: &[u8; 32]| -> Result<bool> {
+        let grant: Option<Role> = fetch(rpc, &pda::role(program_id, instance, role, authority))?;
+        Ok(grant.is_some_and(|grant| grant.is_active(now)))
+    };
+    if !holds(&BLACKLIST_MANAGER_ROLE)? && holds(&policy_role)? {
+        return Ok(policy_role);
+    }
+    Ok(BLACKLIST_MANAGER_ROLE)
+}
+
+// Role account of another default admin whose grant is active and does not
+// expire, which an admin revoking itself or letting its grant expire passes so
+// that the program can tell that a permanent admin remains
+fn other_admin(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    user: &Pubkey,
+    remaining_admin: Option<Pubkey>,
+    offline: bool,
+) -> Result<AccountMeta> {
+    if let Some(admin) = remaining_admin {
+        return Ok(AccountMeta::new_readonly(
+            pda::role(program_id, instance, &DEFAULT_ADMIN_ROLE, &admin),
+            false,
+        ));
+    }
+    require_network(offline, "--other-admin")?;
+    let now: i64 = timestamp(rpc)?.try_into()?;
+    let index: Option<RoleMembers> = fetch(
+        rpc,
+        &pda::role_members(program_id, instance, &DEFAULT_ADMIN_ROLE),
+    )?;
+    for member in index.map(|index| index.members).unwrap_or_default() {
+        if member == *user {
+            continue;
+        }
+        let address = pda::role(program_id, instance, &DEFAULT_ADMIN_ROLE, &member);
+        if fetch::<Role>(rpc, &address)?
+            .is_some_and(|grant| grant.is_active(now) && grant.is_permanent())
+        {
+            return Ok(AccountMeta::new_readonly(address, false));
+        }
+    }
+    Err(anyhow!("no other permanent default admin remains"))
+}
+
+fn new_key(input: &str) -> Result<Vec<u8>> {
+    match parse_key(input)? {
+        KeyInput::PublicKey(key) => Ok(key),
+        KeyInput::Address(address) => Ok(address.to_vec()),
+    }
+}
+
+fn blacklist_entries(policy_id: u64, trading_addresses: &[Pubkey]) -> Result<Vec<BlacklistEntry>> {
+    if trading_addresses.len() > MAX_BLACKLIST_BATCH_SIZE as usize {
+        return Err(anyhow!(
+            "at most {} trading addresses can be batched",
+            MAX_BLACKLIST_BATCH_SIZE
+        ));
+    }
+    Ok(trading_addresses
+        .iter()
+        .map(|&trading_address| BlacklistEntry {
+            policy_id,
+            trading_address,
+        })
+        .collect())
+}
+
+fn print_key(key: &[u8]) {
+    println!("Key: 0x{}", hex::encode(key));
+    println!("Address: {}", format_eth_address(&eth_address(key)));
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let rpc = RpcClient::new_with_commitment(rpc_url(&cli.url), CommitmentConfig::confirmed());
+    let program_id = &cli.program_id;
+    let instance = &cli.instance;
+    let mode = if cli.dry_run {
+        Mode::DryRun
+    } else if cli.sign_only {
+        Mode::SignOnly
+    } else if cli.export {
+        Mode::Export
+    } else {
+        Mode::Send
+    };
+    // Signed transactions with a given blockhash need no network access, so
+    // neither may the accounts they refer to be looked up
+    let offline = cli.sign_only && cli.blockhash.is_some();
+    let address_key = cli.address_key;
+
+    let instructions = match cli.command {
+        Command::Credential {
+            policy_id,
+            trading_address,
+        } => {
+            let address = pda::entity_mapping(program_id, instance, policy_id, &trading_address);
+            println!("Entity mapping: {}", address);
+            match fetch::<EntityData>(&rpc, &address)? {
+                Some(entity) => {
+                    let now = timestamp(&rpc)?;
+                    println!("Version: {}", entity.version);
+                    println!("Blacklisted: {}", entity.is_blacklisted(now));
+                    if entity.blacklisted {
+                        println!(
+                            "Blacklist reason: {}",
+                            format_blacklist_reason(entity.blacklisting.reason)
+                        );
+                        if let Some(until) = entity.blacklisting.until {
+                            println!("Blacklisted until: {}", until);
+                        }
+                    }
+                    println!("Expires at: {}", entity.exp);
+                    println!("Valid: {}", !entity.is_blacklisted(now) && entity.exp > now);
+                }
+                None => println!("No credential found"),
+            }
+            return Ok(());
+        }
+        Command::Policy { policy_id } => {
+            let address = pda::policy_config(program_id, instance, policy_id);
+            println!("Policy config: {}", address);
+            match fetch::<PolicyConfig>(&rpc, &address)? {
+                Some(config) => {
+                    println!("Version: {}", config.version);
+                    println!("Threshold: {}", config.threshold);
+                }
+                None => println!("Threshold: 1 (not configured)"),
+            }
+            return Ok(());
+        }
+        Command::Key { key } => {
+            let key = resolve_key(&rpc, program_id, instance, &key, address_key, false)?;
+            let address = pda::key_mapping(program_id, instance, &key);
+            print_key(&key);
+            println!("Key mapping: {}", address);
+            match fetch::<KeyEntry>(&rpc, &address)? {
+                Some(entry) => {
+                    println!("Version: {}", entry.version);
+                    println!("Valid: {}", entry.is_valid);
+                    println!("Valid from: {}", entry.valid_from);
+                    println!("Valid to: {}", entry.valid_to);
+                    println!("Policy scope: {}", format_policy_scope(&entry.policy_scope));
+                    println!(
+                        "Credentials issued: {} (max {})",
+                        entry.usage.credentials_issued, entry.quota.max_credentials
+                    );
+                    if entry.quota.window_duration > 0 {
+                        let now = timestamp(&rpc)?;
+                        let window_credentials_issued =
+                            if entry.usage.window_start == entry.quota.window_start(now) {
+                                entry.usage.window_credentials_issued
+                            } else {
+                                0
+                            };
+                        println!(
+                            "Credentials issued in current {}s window: {} (max {})",
+                            entry.quota.window_duration,
+                            window_credentials_issued,
+                            entry.quota.max_credentials_per_window
+                        );
+                    }
+                }
+                None => println!("Key is not registered"),
+            }
+            return Ok(());
+        }
+        Command::Keys => {
+            let registry: KeyRegistry = fetch(&rpc, &pda::key_registry(program_id, instance))?
+                .ok_or_else(|| anyhow!("key registry does not exist"))?;
+            for key in registry.active_keys {
+                println!(
+                    "0x{} {}",
+                    hex::encode(&key),
+                    format_eth_address(&eth_address(&key))
+                );
+            }
+            return Ok(());
+        }
+        Command::Role { role, user } => {
+            let role = parse_role(&role)?;
+            let address = pda::role(program_id, instance, &role, &user);
+            let grant = fetch::<Role>(&rpc, &address)?;
+            println!("Role: {}", role_name(&role));
+            println!("User: {}", user);
+            match grant {
+                Some(grant) => {
+                    let now = timestamp(&rpc)?;
+                    println!("Has role: {}", grant.is_active(now.try_into()?));
+                    if let Some(expires_at) = grant.expires_at {
+                        println!("Expires at: {}", expires_at);
+                    }
+                }
+                None => println!("Has role: false"),
+            }
+            return Ok(());
+        }
+        Command::RoleDefinition { role } => {
+            let role = parse_role(&role)?;
+            println!("Role: {}", role_name(&role));
+            match fetch::<RoleDefinition>(&rpc, &pda::role_definition(program_id, instance, &role))?
+            {
+                Some(definition) => {
+                    println!("Name: {}", definition.name);
+                    println!("Admin role: {}", role_name(&definition.admin_role));
+                    println!(
+                        "Description hash: 0x{}",
+                        hex::encode(definition.description_hash)
+                    );
+                }
+                None if BUILTIN_ROLES.contains(&role) => {
+                    println!("Built-in role administered by DEFAULT_ADMIN_ROLE")
+                }
+                None => println!("Role is not defined"),
+            }
+            return Ok(());
+        }
+        Command::RoleMembers { role } => {
+            let role = parse_role(&role)?;
+            let index: Option<RoleMembers> =
+                fetch(&rpc, &pda::role_members(program_id, instance, &role))?;
+            let members = index.map(|index| index.members).unwrap_or_default();
+            println!("Role: {}", role_name(&role));
+            println!("Members: {}", members.len());
+            for member in members {
+                println!("{}", member);
+            }
+            return Ok(());
+        }
+        Command::Proposal { proposal_id } => {
+            let address = pda::proposal(program_id, instance, proposal_id);
+            println!("Proposal: {}", address);
+            match fetch::<Proposal>(&rpc, &address)? {
+                Some(proposal) => {
+                    let now = timestamp(&rpc)?;
+                    println!("Proposer: {}", proposal.proposer);
+                    println!("Execute after: {}", proposal.execute_after);
+                    println!("Ready: {}", i64::try_from(now)? >= proposal.execute_after);
+                    println!("Approvals: {}", proposal.approvals.len());
+                    for approver in &proposal.approvals {
+                        println!("Approved by: {}", approver);
+                    }
+                    println!("Data: 0x{}", hex::encode(&proposal.data));
+                    for account in proposal.accounts {
+                        println!(
+                            "Account: {}{}{}",
+                            account.pubkey,
+                            if account.is_signer { " signer" } else { "" },
+                            if account.is_writable { " writable" } else { "" }
+                        );
+                    }
+                }
+                None => println!("Proposal is not scheduled"),
+            }
+            return Ok(());
+        }
+        Command::State => {
+            let state: ProgramState = fetch(&rpc, &pda::program_state(program_id, instance))?
+                .ok_or_else(|| anyhow!("program is not initialized"))?;
+            println!("Version: {}", state.version);
+            println!("Chain id: 0x{}", hex::encode(state.chain_id.chain_id));
+            match state.config.fee_vault {
+                Some(fee_vault) => println!("Fee vault: {}", fee_vault),
+                None => println!("Fee vault: not set"),
+            }
+            println!(
+                "Credentials paused: {}",
+                state.config.is_paused(PAUSE_CREDENTIALS)
+            );
+            println!(
+                "Fee collection paused: {}",
+                state.config.is_paused(PAUSE_FEE_COLLECTION)
+            );
+            println!(
+                "Max credential validity: {}s",
+                state.config.max_credential_validity
+            );
+            println!(
+                "Min credential cost: {} lamports",
+                state.config.min_credential_cost
+            );
+            println!("Timelock delay: {}s", state.config.timelock_delay);
+            println!("Approval threshold: {}", state.config.approval_threshold);
+            println!(
+                "Timelock authority: {}",
+                pda::timelock(program_id, instance)
+            );
+            return Ok(());
+        }
+        Command::DecodeError { code } => {
+            let code = errors::parse_error_code(&code)?;
+            let error = errors::decode_error(code)
+                .ok_or_else(|| anyhow!("{} is not an error of the Keyring program", code))?;
+            println!("{}", errors::format_error(error));
+            return Ok(());
+        }
+        command => {
+            // Exported transactions are not signed, so no keypair is needed
+            // as long as the authority is known.
+            let keypair = match (mode, cli.authority) {
+                (Mode::Export, Some(_)) => None,
+                _ => Some(load_keypair(cli.keypair.as_deref())?),
+            };
+            let signer = cli
+                .authority
+                .or_else(|| keypair.as_ref().map(|keypair| keypair.pubkey()))
+                .expect("Keypair is loaded when authority is not set; qed");
+            // Scheduled instructions are signed by the timelock authority,
+            // which has to hold the roles they require
+            let authority = match cli.schedule {
+                Some(_) => pda::timelock(program_id, instance),
+                None => signer,
+            };
+            let instructions = match command {
+                Command::Initialize { chain_id } => vec![instructions::initialize(
+                    program_id,
+                    instance,
+                    &authority,
+                    parse_chain_id(&chain_id)?,
+                )],
+                Command::UpdateConfig {
+                    chain_id,
+                    fee_vault,
+                    clear_fee_vault,
+                    pause_credentials,
+                    pause_fee_collection,
+                    max_credential_validity,
+                    min_credential_cost,
+                    timelock_delay,
+                    approval_threshold,
+                } => {
+                    let state: ProgramState =
+                        fetch(&rpc, &pda::program_state(program_id, instance))?
+                            .ok_or_else(|| anyhow!("program is not initialized"))?;
+                    let chain_id = match chain_id {
+                        Some(chain_id) => parse_chain_id(&chain_id)?,
+                        None => state.chain_id.chain_id.to_vec(),
+                    };
+                    let mut config = state.config;
+                    if clear_fee_vault {
+                        config.fee_vault = None;
+                    }
+                    if fee_vault.is_some() {
+                        config.fee_vault = fee_vault;
+                    }
+                    for (flag, paused) in [
+                        (PAUSE_CREDENTIALS, pause_credentials),
+                        (PAUSE_FEE_COLLECTION, pause_fee_collection),
+                    ] {
+                        match paused {
+                            Some(true) => config.pause_flags |= flag,
+                            Some(false) => config.pause_flags &= !flag,
+                            None => {}
+                        }
+                    }
+                    if let Some(max_credential_validity) = max_credential_validity {
+                        config.max_credential_validity = max_credential_validity;
+                    }
+                    if let Some(min_credential_cost) = min_credential_cost {
+                        config.min_credential_cost = min_credential_cost;
+                    }
+                    if let Some(timelock_delay) = timelock_delay {
+                        config.timelock_delay = timelock_delay;
+                    }
+                    if let Some(approval_threshold) = approval_threshold {
+                        config.approval_threshold = approval_threshold;
+                    }
+                    vec![instructions::update_config(
+                        program_id, instance, &authority, chain_id, config,
+                    )]
+                }
+                Command::GrantRole {
+                    role,
+                    user,
+                    expires_at,
+                    admin_role,
+                    other_admin: remaining_admin,
+                } => {
+                    let role = parse_role(&role)?;
+                    let admin_role = match admin_role {
+                        Some(admin_role) => parse_role(&admin_role)?,
+                        None => {
+                            require_network(offline, "--admin-role")?;
+                            role_admin(&rpc, program_id, instance, &role)?
+                        }
+                    };
+                    let mut instruction = instructions::manage_roles(
+                        program_id,
+                        instance,
+                        &authority,
+                        &admin_role,
+                        role,
+                        user,
+                        true,
+                        expires_at,
+                    );
+                    if role == DEFAULT_ADMIN_ROLE && user == authority && expires_at.is_some() {
+                        instruction.accounts.push(other_admin(
+                            &rpc,
+                            program_id,
+                            instance,
+                            &user,
+                            remaining_admin,
+                            offline,
+                        )?);
+                    }
+                    vec![instruction]
+                }
+                Command::RevokeRole {
+                    role,
+                    user,
+                    admin_role,
+                    other_admin: remaining_admin,
+                } => {
+                    let role = parse_role(&role)?;
+                    let admin_role = match admin_role {
+                        Some(admin_role) => parse_role(&admin_role)?,
+                        None => {
+                            require_network(offline, "--admin-role")?;
+                            role_admin(&rpc, program_id, instance, &role)?
+                        }
+                    };
+                    let mut instruction = instructions::manage_roles(
+                        program_id,
+                        instance,
+                        &authority,
+                        &admin_role,
+                        role,
+                        user,
+                        false,
+                        None,
+                    );
+                    if role == DEFAULT_ADMIN_ROLE && user == authority {
+                        instruction.accounts.push(other_admin(
+                            &rpc,
+                            program_id,
+                            instance,
+                            &user,
+                            remaining_admin,
+                            offline,
+                        )?);
+                    }
+                    vec![instruction]
+                }
+                Command::DefineRole {
+                    role,
+                    name,
+                    admin_role,
+                    description_hash,
+                } => {
+                    let description_hash = match description_hash {
+                        Some(input) => parse_hex(&input)?
+                            .try_into()
+                            .map_err(|_| anyhow!("description hash must be 32 bytes"))?,
+                        None => [0; 32],
+                    };
+                    vec![instructions::define_role(
+                        program_id,
+                        instance,
+                        &authority,
+                        parse_role(&role)?,
+                        name,
+                        parse_role(&admin_role)?,
+                        description_hash,
+                    )]
+                }
+                Command::RegisterKey {
+                    key,
+                    valid_from,
+                    valid_to,
+                } => vec![instructions::register_key(
+                    program_id,
+                    instance,
+                    &authority,
+                    new_key(&key)?,
+                    valid_from,
+                    valid_to,
+                )],
+                Command::RevokeKey { key } => vec![instructions::revoke_key(
+                    program_id,
+                    instance,
+                    &authority,
+                    resolve_key(&rpc, program_id, instance, &key, address_key, offline)?,
+                )],
+                Command::RotateKey {
+                    old_key,
+                    new_key: key,
+                    valid_from,
+                    valid_to,
+                    overlap,
+                } => vec![instructions::rotate_key(
+                    program_id,
+                    instance,
+                    &authority,
+                    resolve_key(&rpc, program_id, instance, &old_key, address_key, offline)?,
+                    new_key(&key)?,
+                    valid_from,
+                    valid_to,
+                    overlap,
+                )],
+                Command::UpdateKeyValidity {
+                    key,
+                    valid_from,
+                    valid_to,
+                } => vec![instructions::update_key_validity(
+                    program_id,
+                    instance,
+                    &authority,
+                    resolve_key(&rpc, program_id, instance, &key, address_key, offline)?,
+                    valid_from,
+                    valid_to,
+                )],
+                Command::UpdateKeyPolicyScope { key, policy_scope } => {
+                    vec![instructions::update_key_policy_scope(
+                        program_id,
+                        instance,
+                        &authority,
+                        resolve_key(&rpc, program_id, instance, &key, address_key, offline)?,
+                        parse_policy_scope(&policy_scope)?,
+                    )]
+                }
+                Command::UpdateKeyQuota {
+                    key,
+                    max_credentials,
+                    window_duration,
+                    max_credentials_per_window,
+                } => vec![instructions::update_key_quota(
+                    program_id,
+                    instance,
+                    &authority,
+                    resolve_key(&rpc, program_id, instance, &key, address_key, offline)?,
+                    KeyQuota {
+                        max_credentials,
+                        window_duration,
+                        max_credentials_per_window,
+                    },
+                )],
+                Command::Blacklist {
+                    policy_id,
+                    trading_address,
+                    reason,
+                    until,
+                    manager_role,
+                } => {
+                    let reason = parse_blacklist_reason(&reason)?;
+                    let manager_role = match manager_role {
+                        Some(manager_role) => parse_role(&manager_role)?,
+                        None => {
+                            require_network(offline, "--manager-role")?;
+                            blacklist_manager_role(
+                                &rpc, program_id, instance, &authority, policy_id,
+                            )?
+                        }
+                    };
+                    match trading_address[..] {
+                        [trading_address] => vec![instructions::blacklist_entity(
+                            program_id,
+                            instance,
+                            &authority,
+                            &manager_role,
+                            policy_id,
+                            trading_address,
+                            reason,
+                            until,
+                        )],
+                        _ => vec![instructions::batch_blacklist_entities(
+                            program_id,
+                            instance,
+                            &authority,
+                            &manager_role,
+                            blacklist_entries(policy_id, &trading_address)?,
+                            reason,
+                            until,
+                        )],
+                    }
+                }
+                Command::Unblacklist {
+                    policy_id,
+                    trading_address,
+                    manager_role,
+                } => {
+                    let manager_role = match manager_role {
+                        Some(manager_role) => parse_role(&manager_role)?,
+                        None => {
+                            require_network(offline, "--manager-role")?;
+                            blacklist_manager_role(
+                                &rpc, program_id, instance, &authority, policy_id,
+                            )?
+                        }
+                    };
+                    match trading_address[..] {
+                        [trading_address] => vec![instructions::unblacklist_entity(
+                            program_id,
+                            instance,
+                            &authority,
+                            &manager_role,
+                            policy_id,
+                            trading_address,
+                        )],
+                        _ => vec![instructions::batch_unblacklist_entities(
+                            program_id,
+                            instance,
+                            &authority,
+                            &manager_role,
+                            blacklist_entries(policy_id, &trading_address)?,
+                        )],
+                    }
+                }
+                Command::CollectFees { receiver } => {
+                    vec![instructions::collect_fees(
+                        program_id, instance, &authority, receiver,
+                    )]
+                }
+                Command::CancelProposal { proposal_id } => {
+                    let proposal: Proposal =
+                        fetch(&rpc, &pda::proposal(program_id, instance, proposal_id))?
+                            .ok_or_else(|| anyhow!("proposal is not scheduled"))?;
+                    vec![instructions::cancel_proposal(
+                        program_id,
+                        instance,
+                        &authority,
+                        proposal_id,
+                        proposal.proposer,
+                    )]
+                }
+                Command::ApproveProposal { proposal_id } => vec![instructions::approve_proposal(
+                    program_id,
+                    instance,
+                    &authority,
+                    proposal_id,
+                )],
+                Command::ExecuteProposal { proposal_id } => {
+                    let proposal: Proposal =
+                        fetch(&rpc, &pda::proposal(program_id, instance, proposal_id))?
+                            .ok_or_else(|| anyhow!("proposal is not scheduled"))?;
+                    vec![instructions::execute_proposal(
+                        program_id,
+                        instance,
+                        &authority,
+                        proposal_id,
+                        &proposal,
+                    )]
+                }
+                Command::SetPolicyThreshold {
+                    policy_id,
+                    threshold,
+                } => vec![instructions::set_policy_threshold(
+                    program_id, instance, &authority, policy_id, threshold,
+                )],
+                _ => unreachable!("Read only commands are handled above; qed"),
+            };
+            let instructions = match cli.schedule {
+                Some(proposal_id) => instructions
+                    .into_iter()
+                    .zip(proposal_id..)
+                    .map(|(proposed, proposal_id)| {
+                        instructions::schedule_proposal(
+                            program_id,
+                            instance,
+                            &signer,
+                            proposal_id,
+                            proposed,
+                        )
+                    })
+                    .collect(),
+                None => instructions,
+            };
+            (keypair, signer, instructions)
+        }
+    };
+
+    let (keypair, signer, instructions) = instructions;
+    Submitter {
+        rpc: &rpc,
+        keypair: keypair.as_ref(),
+        fee_payer: cli.fee_payer.unwrap_or(signer),
+        mode,
+        blockhash: cli.blockhash,
+        nonce: cli.nonce.map(|account| DurableNonce {
+            account,
+            authority: cli.nonce_authority.unwrap_or(signer),
+        }),
+    }
+    .submit(&instructions)
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("Error: {:#}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,139 @@
+use anchor_client::solana_sdk::bpf_loader_upgradeable;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use keyring_network::common::types::{instance_seed, ToHash};
+
+pub fn program_state(program_id: &Pubkey, instance: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"global_state".as_ref(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn key_registry(program_id: &Pubkey, instance: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"active_keys".as_ref(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn program_data(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+pub fn role(program_id: &Pubkey, instance: &Pubkey, role: &[u8; 32], user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            role.as_ref(),
+            user.to_bytes().as_ref(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn role_members(program_id: &Pubkey, instance: &Pubkey, role: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"role_members".as_ref(),
+            role.as_ref(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn role_definition(program_id: &Pubkey, instance: &Pubkey, role: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"role_definition".as_ref(),
+            role.as_ref(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn key_mapping(program_id: &Pubkey, instance: &Pubkey, key: &Vec<u8>) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"_key_mapping".as_ref(),
+            key.to_hash().as_ref(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn entity_mapping(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    policy_id: u64,
+    trading_address: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"_entity_mapping".as_ref(),
+            &policy_id.to_le_bytes(),
+            &trading_address.to_bytes(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn policy_config(program_id: &Pubkey, instance: &Pubkey, policy_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"_policy_config".as_ref(),
+            &policy_id.to_le_bytes(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn timelock(program_id: &Pubkey, instance: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"timelock".as_ref(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn proposal(program_id: &Pubkey, instance: &Pubkey, proposal_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"proposal".as_ref(),
+            &proposal_id.to_le_bytes(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
@@ -0,0 +1,281 @@
+use crate::errors::describe_transaction_error;
+use anchor_client::solana_client::nonce_utils;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::hash::Hash;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::message::Message;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::system_instruction;
+use anchor_client::solana_sdk::transaction::Transaction;
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Send,
+    // Simulate the transaction against the cluster without submitting it.
+    DryRun,
+    // Print the transaction signed by the local keypair instead of submitting
+    // it. Signatures of other signers can be added later.
+    SignOnly,
+    // Print the unsigned transaction, e.g. for approval through a multisig or
+    // an air-gapped signer.
+    Export,
+}
+
+// Durable nonce used in place of a recent blockhash so that the transaction
+// does not expire while it is waiting for approvals.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DurableNonce {
+    pub account: Pubkey,
+    pub authority: Pubkey,
+}
+
+pub struct Submitter<'a> {
+    pub rpc: &'a RpcClient,
+    pub keypair: Option<&'a Keypair>,
+    pub fee_payer: Pubkey,
+    pub mode: Mode,
+    pub blockhash: Option<Hash>,
+    pub nonce: Option<DurableNonce>,
+}
+
+impl Submitter<'_> {
+    fn blockhash(&self) -> Result<Hash> {
+        if let Some(blockhash) = self.blockhash {
+            return Ok(blockhash);
+        }
+
+        match self.nonce {
+            Some(nonce) => {
+                let account = nonce_utils::get_account_with_commitment(
+                    self.rpc,
+                    &nonce.account,
+                    self.rpc.commitment(),
+                )
+                .map_err(|err| anyhow!("unable to fetch nonce account: {}", err))?;
+                let data = nonce_utils::data_from_account(&account)
+                    .map_err(|err| anyhow!("invalid nonce account: {}", err))?;
+                if data.authority != nonce.authority {
+                    bail!(
+                        "nonce authority is {}, expected {}",
+                        data.authority,
+                        nonce.authority
+                    );
+                }
+                Ok(data.blockhash())
+            }
+            None => self
+                .rpc
+                .get_latest_blockhash()
+                .map_err(|err| anyhow!("unable to fetch latest blockhash: {}", err)),
+        }
+    }
+
+    pub fn transaction(&self, instructions: &[Instruction]) -> Result<Transaction> {
+        let mut all_instructions = vec![];
+        if let Some(nonce) = self.nonce {
+            // Advancing the nonce must be the first instruction of the transaction
+            all_instructions.push(system_instruction::advance_nonce_account(
+                &nonce.account,
+                &nonce.authority,
+            ));
+        }
+        all_instructions.extend_from_slice(instructions);
+
+        let blockhash = self.blockhash()?;
+        let message =
+            Message::new_with_blockhash(&all_instructions, Some(&self.fee_payer), &blockhash);
+        let mut transaction = Transaction::new_unsigned(message);
+
+        if self.mode != Mode::Export {
+            if let Some(keypair) = self.keypair {
+                transaction
+                    .try_partial_sign(&[keypair], blockhash)
+                    .map_err(|err| anyhow!("unable to sign transaction: {}", err))?;
+            }
+        }
+
+        Ok(transaction)
+    }
+
+    pub fn submit(&self, instructions: &[Instruction]) -> Result<()> {
+        let transaction = self.transaction(instructions)?;
+
+        match self.mode {
+            Mode::Send => {
+                let missing_signers = missing_signers(&transaction);
+                if !missing_signers.is_empty() {
+                    bail!(
+                        "transaction is missing signatures from {}, use --sign-only or --export",
+                        missing_signers
+                            .iter()
+                            .map(|signer| signer.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+                let signature = self
+                    .rpc
+                    .send_and_confirm_transaction(&transaction)
+                    .map_err(|err| {
+                        match err
+                            .get_transaction_error()
+                            .as_ref()
+                            .and_then(describe_transaction_error)
+                        {
+                            Some(description) => anyhow!("transaction failed: {}", description),
+                            None => anyhow!("transaction failed: {}", err),
+                        }
+                    })?;
+                println!("Transaction signature: {}", signature);
+            }
+            Mode::DryRun => {
+                let simulation = self
+                    .rpc
+                    .simulate_transaction(&transaction)
+                    .map_err(|err| anyhow!("simulation failed: {}", err))?
+                    .value;
+                for log in simulation.logs.unwrap_or_default() {
+                    println!("{}", log);
+                }
+                if let Some(units) = simulation.units_consumed {
+                    println!("Compute units consumed: {}", units);
+                }
+                match simulation.err {
+                    Some(err) => {
+                        let description =
+                            describe_transaction_error(&err).unwrap_or_else(|| err.to_string());
+                        return Err(anyhow!("simulation returned an error: {}", description));
+                    }
+                    None => println!("Simulation succeeded"),
+                }
+            }
+            Mode::SignOnly | Mode::Export => print_transaction(&transaction)?,
+        }
+
+        Ok(())
+    }
+}
+
+pub fn missing_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    let signers = transaction.message.header.num_required_signatures as usize;
+    transaction.message.account_keys[..signers]
+        .iter()
+        .zip(transaction.signatures.iter())
+        .filter(|(_, signature)| **signature == Default::default())
+        .map(|(signer, _)| *signer)
+        .collect()
+}
+
+fn print_transaction(transaction: &Transaction) -> Result<()> {
+    let signers = transaction.message.header.num_required_signatures as usize;
+    println!("Blockhash: {}", transaction.message.recent_blockhash);
+    println!("Signers:");
+    for (signer, signature) in transaction.message.account_keys[..signers]
+        .iter()
+        .zip(transaction.signatures.iter())
+    {
+        if *signature == Default::default() {
+            println!("  {} (missing)", signer);
+        } else {
+            println!("  {}={}", signer, signature);
+        }
+    }
+    println!(
+        "Message: {}",
+        bs58::encode(transaction.message.serialize()).into_string()
+    );
+    println!(
+        "Transaction: {}",
+        STANDARD.encode(bincode::serialize(transaction)?)
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions;
+    use anchor_client::solana_sdk::packet::PACKET_DATA_SIZE;
+    use anchor_client::solana_sdk::signature::Signer;
+    use keyring_network::common::types::{
+        BlacklistEntry, BlacklistReason, BLACKLIST_MANAGER_ROLE, DEFAULT_INSTANCE,
+        MAX_BLACKLIST_BATCH_SIZE,
+    };
+
+    #[test]
+    fn test_blacklist_batch_fits_transaction() {
+        let signer = Pubkey::new_unique();
+        let transaction_size = |entries: u8| {
+            let instruction = instructions::batch_blacklist_entities(
+                &keyring_network::ID,
+                &DEFAULT_INSTANCE,
+                &signer,
+                &BLACKLIST_MANAGER_ROLE,
+                (0..entries)
+                    .map(|_| BlacklistEntry {
+                        policy_id: u64::MAX,
+                        trading_address: Pubkey::new_unique(),
+                    })
+                    .collect(),
+                BlacklistReason::Sanctions,
+                Some(u64::MAX),
+            );
+            let transaction = Transaction::new_with_payer(&[instruction], Some(&signer));
+            bincode::serialize(&transaction).unwrap().len()
+        };
+        assert!(transaction_size(MAX_BLACKLIST_BATCH_SIZE) <= PACKET_DATA_SIZE);
+        assert!(transaction_size(MAX_BLACKLIST_BATCH_SIZE + 1) > PACKET_DATA_SIZE);
+    }
+
+    #[test]
+    fn test_export_with_durable_nonce() {
+        let rpc = RpcClient::new("http://127.0.0.1:1".to_string());
+        let keypair = Keypair::new();
+        let multisig = Pubkey::new_unique();
+        let nonce = DurableNonce {
+            account: Pubkey::new_unique(),
+            authority: multisig,
+        };
+        let nonce_blockhash = Hash::new_unique();
+        let program_id = keyring_network::ID;
+        let instruction =
+            instructions::collect_fees(&program_id, &DEFAULT_INSTANCE, &multisig, keypair.pubkey());
+
+        let mut submitter = Submitter {
+            rpc: &rpc,
+            keypair: Some(&keypair),
+            fee_payer: keypair.pubkey(),
+            mode: Mode::Export,
+            blockhash: Some(nonce_blockhash),
+            nonce: Some(nonce),
+        };
+
+        // Exported transaction is not signed by anyone
+        let transaction = submitter
+            .transaction(std::slice::from_ref(&instruction))
+            .unwrap();
+        assert_eq!(transaction.message.recent_blockhash, nonce_blockhash);
+        assert_eq!(transaction.message.instructions.len(), 2);
+        assert_eq!(
+            transaction.message.instructions[0],
+            transaction
+                .message
+                .compile_instruction(&system_instruction::advance_nonce_account(
+                    &nonce.account,
+                    &nonce.authority
+                ))
+        );
+        assert_eq!(
+            missing_signers(&transaction),
+            vec![keypair.pubkey(), multisig]
+        );
+
+        // Local keypair paying fees signs, the multisig signature is still missing
+        submitter.mode = Mode::SignOnly;
+        let transaction = submitter.transaction(&[instruction]).unwrap();
+        assert_eq!(missing_signers(&transaction), vec![multisig]);
+    }
+}
@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum IndexerError {
+    #[error("program data is not valid base64: {0}")]
+    InvalidBase64(String),
+    #[error("event data is shorter than the 8 byte discriminator")]
+    MissingDiscriminator,
+    #[error("unknown event discriminator {0:?}")]
+    UnknownDiscriminator([u8; 8]),
+    #[error("unable to deserialize {event}: {reason}")]
+    InvalidEventData { event: &'static str, reason: String },
+    #[error("malformed program log: {0}")]
+    MalformedLog(String),
+}
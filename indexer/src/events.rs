@@ -0,0 +1,150 @@
+use crate::error::IndexerError;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use keyring_network::events::{
+    BlacklistedEntity, ConfigUpdated, CredentialsCreated, FeesCollected, Initialized,
+    KeyPolicyScopeUpdated, KeyQuotaUpdated, KeyRegistered, KeyRevoked, KeyRotated,
+    KeyValidityUpdated, PolicyThresholdUpdated, ProposalApproved, ProposalCancelled,
+    ProposalExecuted, ProposalScheduled, RoleDefined, RoleManaged, ThresholdCredentialsCreated,
+    UnBlackListedEntity, ValidCredentials,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum KeyringEvent {
+    Initialized(Initialized),
+    ConfigUpdated(ConfigUpdated),
+    RoleManaged(RoleManaged),
+    RoleDefined(RoleDefined),
+    KeyRegistered(KeyRegistered),
+    KeyRevoked(KeyRevoked),
+    KeyRotated(KeyRotated),
+    KeyValidityUpdated(KeyValidityUpdated),
+    KeyPolicyScopeUpdated(KeyPolicyScopeUpdated),
+    KeyQuotaUpdated(KeyQuotaUpdated),
+    BlacklistedEntity(BlacklistedEntity),
+    UnBlackListedEntity(UnBlackListedEntity),
+    CredentialsCreated(CredentialsCreated),
+    ThresholdCredentialsCreated(ThresholdCredentialsCreated),
+    PolicyThresholdUpdated(PolicyThresholdUpdated),
+    ValidCredentials(ValidCredentials),
+    FeesCollected(FeesCollected),
+    ProposalScheduled(ProposalScheduled),
+    ProposalCancelled(ProposalCancelled),
+    ProposalApproved(ProposalApproved),
+    ProposalExecuted(ProposalExecuted),
+}
+
+impl KeyringEvent {
+    // Instance of the program the event was emitted for
+    pub fn instance(&self) -> Pubkey {
+        match self {
+            KeyringEvent::Initialized(event) => event.instance,
+            KeyringEvent::ConfigUpdated(event) => event.instance,
+            KeyringEvent::RoleManaged(event) => event.instance,
+            KeyringEvent::RoleDefined(event) => event.instance,
+            KeyringEvent::KeyRegistered(event) => event.instance,
+            KeyringEvent::KeyRevoked(event) => event.instance,
+            KeyringEvent::KeyRotated(event) => event.instance,
+            KeyringEvent::KeyValidityUpdated(event) => event.instance,
+            KeyringEvent::KeyPolicyScopeUpdated(event) => event.instance,
+            KeyringEvent::KeyQuotaUpdated(event) => event.instance,
+            KeyringEvent::BlacklistedEntity(event) => event.instance,
+            KeyringEvent::UnBlackListedEntity(event) => event.instance,
+            KeyringEvent::CredentialsCreated(event) => event.instance,
+            KeyringEvent::ThresholdCredentialsCreated(event) => event.instance,
+            KeyringEvent::PolicyThresholdUpdated(event) => event.instance,
+            KeyringEvent::ValidCredentials(event) => event.instance,
+            KeyringEvent::FeesCollected(event) => event.instance,
+            KeyringEvent::ProposalScheduled(event) => event.instance,
+            KeyringEvent::ProposalCancelled(event) => event.instance,
+            KeyringEvent::ProposalApproved(event) => event.instance,
+            KeyringEvent::ProposalExecuted(event) => event.instance,
+        }
+    }
+}
+
+fn deserialize<T: AnchorDeserialize>(name: &'static str, data: &[u8]) -> Result<T, IndexerError> {
+    // Bytes left over after the event mean it was not written by this version
+    // of the program, so they are rejected rather than ignored
+    T::try_from_slice(data).map_err(|err| IndexerError::InvalidEventData {
+        event: name,
+        reason: err.to_string(),
+    })
+}
+
+// Decodes raw event bytes as written by `emit!`: an 8 byte discriminator
+// followed by the borsh serialized event.
+pub fn decode_event(data: &[u8]) -> Result<KeyringEvent, IndexerError> {
+    if data.len() < 8 {
+        return Err(IndexerError::MissingDiscriminator);
+    }
+    let (discriminator, payload) = data.split_at(8);
+    let discriminator: [u8; 8] = discriminator
+        .try_into()
+        .expect("We already checked that the length is at least 8 above; qed");
+
+    let event = match discriminator {
+        Initialized::DISCRIMINATOR => {
+            KeyringEvent::Initialized(deserialize("Initialized", payload)?)
+        }
+        ConfigUpdated::DISCRIMINATOR => {
+            KeyringEvent::ConfigUpdated(deserialize("ConfigUpdated", payload)?)
+        }
+        RoleManaged::DISCRIMINATOR => {
+            KeyringEvent::RoleManaged(deserialize("RoleManaged", payload)?)
+        }
+        RoleDefined::DISCRIMINATOR => {
+            KeyringEvent::RoleDefined(deserialize("RoleDefined", payload)?)
+        }
+        KeyRegistered::DISCRIMINATOR => {
+            KeyringEvent::KeyRegistered(deserialize("KeyRegistered", payload)?)
+        }
+        KeyRevoked::DISCRIMINATOR => KeyringEvent::KeyRevoked(deserialize("KeyRevoked", payload)?),
+        KeyRotated::DISCRIMINATOR => KeyringEvent::KeyRotated(deserialize("KeyRotated", payload)?),
+        KeyValidityUpdated::DISCRIMINATOR => {
+            KeyringEvent::KeyValidityUpdated(deserialize("KeyValidityUpdated", payload)?)
+        }
+        KeyPolicyScopeUpdated::DISCRIMINATOR => {
+            KeyringEvent::KeyPolicyScopeUpdated(deserialize("KeyPolicyScopeUpdated", payload)?)
+        }
+        KeyQuotaUpdated::DISCRIMINATOR => {
+            KeyringEvent::KeyQuotaUpdated(deserialize("KeyQuotaUpdated", payload)?)
+        }
+        BlacklistedEntity::DISCRIMINATOR => {
+            KeyringEvent::BlacklistedEntity(deserialize("BlacklistedEntity", payload)?)
+        }
+        UnBlackListedEntity::DISCRIMINATOR => {
+            KeyringEvent::UnBlackListedEntity(deserialize("UnBlackListedEntity", payload)?)
+        }
+        CredentialsCreated::DISCRIMINATOR => {
+            KeyringEvent::CredentialsCreated(deserialize("CredentialsCreated", payload)?)
+        }
+        ThresholdCredentialsCreated::DISCRIMINATOR => KeyringEvent::ThresholdCredentialsCreated(
+            deserialize("ThresholdCredentialsCreated", payload)?,
+        ),
+        PolicyThresholdUpdated::DISCRIMINATOR => {
+            KeyringEvent::PolicyThresholdUpdated(deserialize("PolicyThresholdUpdated", payload)?)
+        }
+        ValidCredentials::DISCRIMINATOR => {
+            KeyringEvent::ValidCredentials(deserialize("ValidCredentials", payload)?)
+        }
+        FeesCollected::DISCRIMINATOR => {
+            KeyringEvent::FeesCollected(deserialize("FeesCollected", payload)?)
+        }
+        ProposalScheduled::DISCRIMINATOR => {
+            KeyringEvent::ProposalScheduled(deserialize("ProposalScheduled", payload)?)
+        }
+        ProposalCancelled::DISCRIMINATOR => {
+            KeyringEvent::ProposalCancelled(deserialize("ProposalCancelled", payload)?)
+        }
+        ProposalApproved::DISCRIMINATOR => {
+            KeyringEvent::ProposalApproved(deserialize("ProposalApproved", payload)?)
+        }
+        ProposalExecuted::DISCRIMINATOR => {
+            KeyringEvent::ProposalExecuted(deserialize("ProposalExecuted", payload)?)
+        }
+        unknown => return Err(IndexerError::UnknownDiscriminator(unknown)),
+    };
+
+    Ok(event)
+}
@@ -0,0 +1,4 @@
+pub mod error;
+pub mod events;
+pub mod logs;
+pub mod state;
@@ -0,0 +1,58 @@
+use crate::error::IndexerError;
+use crate::events::{decode_event, KeyringEvent};
+use anchor_lang::prelude::Pubkey;
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+const PROGRAM_LOG_PREFIX: &str = "Program log: ";
+
+// Extracts the events emitted by `program_id` from the log messages of a
+// single transaction.
+//
+// Logs are attributed to programs by tracking the invocation stack, so
+// `Program data:` lines written by other programs (including programs invoked
+// by Keyring or programs invoking Keyring via CPI) are ignored.
+pub fn parse_logs<S: AsRef<str>>(
+    program_id: &Pubkey,
+    logs: &[S],
+) -> Result<Vec<KeyringEvent>, IndexerError> {
+    let program_id = program_id.to_string();
+    let mut invocation_stack: Vec<String> = vec![];
+    let mut events = vec![];
+
+    for log in logs {
+        let log = log.as_ref();
+
+        if let Some(data) = log.strip_prefix(PROGRAM_DATA_PREFIX) {
+            if invocation_stack.last() == Some(&program_id) {
+                let data = STANDARD
+                    .decode(data.trim())
+                    .map_err(|err| IndexerError::InvalidBase64(err.to_string()))?;
+                events.push(decode_event(&data)?);
+            }
+            continue;
+        }
+
+        if log.starts_with(PROGRAM_LOG_PREFIX) {
+            continue;
+        }
+
+        let mut words = log.split_whitespace();
+        if words.next() != Some("Program") {
+            continue;
+        }
+        let (Some(program), Some(action)) = (words.next(), words.next()) else {
+            continue;
+        };
+
+        match action {
+            "invoke" => invocation_stack.push(program.to_string()),
+            "success" | "failed:" if invocation_stack.pop().as_deref() != Some(program) => {
+                return Err(IndexerError::MalformedLog(log.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(events)
+}
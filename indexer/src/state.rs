@@ -0,0 +1,339 @@
+use crate::error::IndexerError;
+use crate::events::KeyringEvent;
+use crate::logs::parse_logs;
+use anchor_lang::prelude::Pubkey;
+use keyring_network::common::types::{
+    Blacklisting, ChainId, KeyQuota, PolicyScope, ProgramConfig, ProposalAccount,
+    DEFAULT_ADMIN_ROLE,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecordedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    // Events of failed transactions are rolled back with the transaction and
+    // must not be applied.
+    #[serde(default)]
+    pub failed: bool,
+    pub logs: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyRecord {
+    pub is_valid: bool,
+    pub valid_from: u64,
+    pub valid_to: u64,
+    pub policy_scope: PolicyScope,
+    pub quota: KeyQuota,
+    // Lifetime count since the key was last registered
+    pub credentials_issued: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoleDefinitionRecord {
+    pub name: String,
+    pub admin_role: [u8; 32],
+    pub description_hash: [u8; 32],
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProposalRecord {
+    pub proposer: Pubkey,
+    pub accounts: Vec<ProposalAccount>,
+    pub data: Vec<u8>,
+    pub execute_after: i64,
+    pub approvals: Vec<Pubkey>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EntityRecord {
+    pub blacklisted: bool,
+    pub exp: u64,
+    pub blacklisting: Blacklisting,
+}
+
+impl EntityRecord {
+    // Whether the entity is blacklisted at `timestamp`, temporary
+    // blacklistings being lifted once they expire
+    pub fn is_blacklisted_at(&self, timestamp: u64) -> bool {
+        self.blacklisted && timestamp < self.blacklisting.until.unwrap_or(u64::MAX)
+    }
+}
+
+// In-memory view of the program state rebuilt purely from emitted events.
+// Events of other instances than `instance` are ignored.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyringState {
+    pub instance: Pubkey,
+    pub chain_id: Option<ChainId>,
+    pub config: ProgramConfig,
+    // Members of each role with the expiry of their grant
+    pub roles: BTreeMap<[u8; 32], BTreeMap<Pubkey, Option<i64>>>,
+    // Only roles defined with `define_role` are present
+    pub role_definitions: BTreeMap<[u8; 32], RoleDefinitionRecord>,
+    pub keys: BTreeMap<Vec<u8>, KeyRecord>,
+    pub entities: BTreeMap<(u64, Pubkey), EntityRecord>,
+    // Only policies with a configured threshold are present
+    pub policy_thresholds: BTreeMap<u64, u8>,
+    // Proposals which were neither cancelled nor executed yet
+    pub proposals: BTreeMap<u64, ProposalRecord>,
+    pub fees_collected: u64,
+    pub last_slot: u64,
+}
+
+impl KeyringState {
+    pub fn new(instance: Pubkey) -> Self {
+        KeyringState {
+            instance,
+            ..Default::default()
+        }
+    }
+
+    // Whether the role was granted to the user, regardless of its expiry
+    pub fn has_role(&self, role: &[u8; 32], user: &Pubkey) -> bool {
+        self.roles
+            .get(role)
+            .is_some_and(|members| members.contains_key(user))
+    }
+
+    // Whether the grant of the role is honored at the unix timestamp
+    pub fn has_role_at(&self, role: &[u8; 32], user: &Pubkey, timestamp: i64) -> bool {
+        self.roles
+            .get(role)
+            .and_then(|members| members.get(user))
+            .is_some_and(|expires_at| timestamp < expires_at.unwrap_or(i64::MAX))
+    }
+
+    pub fn role_expiry(&self, role: &[u8; 32], user: &Pubkey) -> Option<i64> {
+        self.roles
+            .get(role)
+            .and_then(|members| members.get(user).copied().flatten())
+    }
+
+    pub fn role_members(&self, role: &[u8; 32]) -> Vec<Pubkey> {
+        self.roles
+            .get(role)
+            .map(|members| members.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn active_keys(&self) -> Vec<Vec<u8>> {
+        self.keys
+            .iter()
+            .filter(|(_, record)| record.is_valid)
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    pub fn entity(&self, policy_id: u64, trading_address: &Pubkey) -> Option<&EntityRecord> {
+        self.entities.get(&(policy_id, *trading_address))
+    }
+
+    // Number of signatures required for credentials of the policy
+    pub fn policy_threshold(&self, policy_id: u64) -> u8 {
+        self.policy_thresholds.get(&policy_id).copied().unwrap_or(1)
+    }
+
+    fn set_role(&mut self, role: [u8; 32], user: Pubkey, has_role: bool, expires_at: Option<i64>) {
+        let members = self.roles.entry(role).or_default();
+        if has_role {
+            members.insert(user, expires_at);
+        } else {
+            members.remove(&user);
+        }
+    }
+
+    fn key_entry(&mut self, key: &[u8]) -> &mut KeyRecord {
+        self.keys.entry(key.to_vec()).or_default()
+    }
+
+    pub fn apply(&mut self, event: &KeyringEvent) {
+        if event.instance() != self.instance {
+            return;
+        }
+        match event {
+            KeyringEvent::Initialized(event) => {
+                self.chain_id = Some(event.chain_id.clone());
+                self.set_role(DEFAULT_ADMIN_ROLE, event.admin, true, None);
+            }
+            KeyringEvent::ConfigUpdated(event) => {
+                self.chain_id = Some(event.chain_id.clone());
+                self.config = event.config.clone();
+            }
+            KeyringEvent::RoleManaged(event) => {
+                self.set_role(event.role, event.user, event.has_role, event.expires_at);
+            }
+            KeyringEvent::RoleDefined(event) => {
+                self.role_definitions.insert(
+                    event.role,
+                    RoleDefinitionRecord {
+                        name: event.name.clone(),
+                        admin_role: event.admin_role,
+                        description_hash: event.description_hash,
+                    },
+                );
+            }
+            KeyringEvent::KeyRegistered(event) => {
+                self.keys.insert(
+                    event.key.clone(),
+                    KeyRecord {
+                        is_valid: true,
+                        valid_from: event.valid_from,
+                        valid_to: event.valid_to,
+                        policy_scope: PolicyScope::Any,
+                        quota: KeyQuota::default(),
+                        credentials_issued: 0,
+                    },
+                );
+            }
+            KeyringEvent::KeyRevoked(event) => {
+                let key = self.key_entry(&event.key);
+                key.is_valid = false;
+                key.valid_from = event.valid_from;
+                key.valid_to = event.valid_to;
+            }
+            KeyringEvent::KeyRotated(event) => {
+                let (policy_scope, quota) = match self.keys.get_mut(&event.old_key) {
+                    Some(old_key) => {
+                        old_key.is_valid = !event.old_key_revoked;
+                        old_key.valid_to = event.old_key_valid_to;
+                        (old_key.policy_scope.clone(), old_key.quota.clone())
+                    }
+                    None => Default::default(),
+                };
+                self.keys.insert(
+                    event.new_key.clone(),
+                    KeyRecord {
+                        is_valid: true,
+                        valid_from: event.valid_from,
+                        valid_to: event.valid_to,
+                        policy_scope,
+                        quota,
+                        credentials_issued: 0,
+                    },
+                );
+            }
+            KeyringEvent::KeyValidityUpdated(event) => {
+                let key = self.key_entry(&event.key);
+                key.is_valid = true;
+                key.valid_from = event.valid_from;
+                key.valid_to = event.valid_to;
+            }
+            KeyringEvent::KeyPolicyScopeUpdated(event) => {
+                self.key_entry(&event.key).policy_scope = event.policy_scope.clone();
+            }
+            KeyringEvent::KeyQuotaUpdated(event) => {
+                self.key_entry(&event.key).quota = event.quota.clone();
+            }
+            KeyringEvent::BlacklistedEntity(event) => {
+                let entity = self
+                    .entities
+                    .entry((event.policy_id, event.trading_address))
+                    .or_default();
+                let blacklisting = Blacklisting {
+                    reason: event.reason,
+                    until: event.until,
+                };
+                if event.previously_blacklisted {
+                    entity.blacklisting = blacklisting;
+                } else {
+                    *entity = EntityRecord {
+                        blacklisted: true,
+                        exp: 0,
+                        blacklisting,
+                    };
+                }
+            }
+            KeyringEvent::UnBlackListedEntity(event) => {
+                let entity = self
+                    .entities
+                    .entry((event.policy_id, event.trading_address))
+                    .or_default();
+                if entity.blacklisted {
+                    *entity = EntityRecord::default();
+                }
+            }
+            KeyringEvent::CredentialsCreated(event) => {
+                let entity = self
+                    .entities
+                    .entry((event.policy_id, event.trading_address))
+                    .or_default();
+                // Credentials clear expired blacklistings
+                *entity = EntityRecord {
+                    exp: event.valid_until,
+                    ..Default::default()
+                };
+                self.key_entry(&event.key).credentials_issued += 1;
+            }
+            KeyringEvent::ThresholdCredentialsCreated(event) => {
+                let entity = self
+                    .entities
+                    .entry((event.policy_id, event.trading_address))
+                    .or_default();
+                // Credentials clear expired blacklistings
+                *entity = EntityRecord {
+                    exp: event.valid_until,
+                    ..Default::default()
+                };
+                for key in &event.keys {
+                    self.key_entry(key).credentials_issued += 1;
+                }
+            }
+            KeyringEvent::PolicyThresholdUpdated(event) => {
+                self.policy_thresholds
+                    .insert(event.policy_id, event.threshold);
+            }
+            KeyringEvent::ValidCredentials(_) => {}
+            KeyringEvent::FeesCollected(event) => {
+                self.fees_collected = self.fees_collected.saturating_add(event.amount);
+            }
+            KeyringEvent::ProposalScheduled(event) => {
+                self.proposals.insert(
+                    event.proposal_id,
+                    ProposalRecord {
+                        proposer: event.proposer,
+                        accounts: event.accounts.clone(),
+                        data: event.data.clone(),
+                        execute_after: event.execute_after,
+                        approvals: vec![],
+                    },
+                );
+            }
+            KeyringEvent::ProposalCancelled(event) => {
+                self.proposals.remove(&event.proposal_id);
+            }
+            KeyringEvent::ProposalApproved(event) => {
+                if let Some(proposal) = self.proposals.get_mut(&event.proposal_id) {
+                    proposal.approvals.push(event.signer);
+                }
+            }
+            KeyringEvent::ProposalExecuted(event) => {
+                self.proposals.remove(&event.proposal_id);
+            }
+        }
+    }
+
+    // Applies transactions in the order given, which is expected to be the
+    // order in which they were executed on chain.
+    pub fn replay<'a, I>(
+        &mut self,
+        program_id: &Pubkey,
+        transactions: I,
+    ) -> Result<(), IndexerError>
+    where
+        I: IntoIterator<Item = &'a RecordedTransaction>,
+    {
+        for transaction in transactions {
+            if transaction.failed {
+                continue;
+            }
+            for event in parse_logs(program_id, &transaction.logs)? {
+                self.apply(&event);
+            }
+            self.last_slot = self.last_slot.max(transaction.slot);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use crate::common::error::KeyringError;
+use crate::common::types::{
+    instance_seed, ProgramState, Proposal, Role, APPROVER_ROLE, MAX_PROPOSAL_APPROVALS,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::Accounts;
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct ProposalApproved {
+    pub instance: Pubkey,
+    pub proposal_id: u64,
+    pub signer: Pubkey,
+    // Number of approvals including this one and the number required
+    pub approvals: u8,
+    pub approval_threshold: u8,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, proposal_id: u64)]
+pub struct ApproveProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"proposal".as_ref(), &proposal_id.to_le_bytes(), instance_seed(&instance)],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        seeds = [b"keyring_program".as_ref(), b"global_state".as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        seeds = [APPROVER_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub approver_role: Account<'info, Role>,
+    pub signer: Signer<'info>,
+}
+
+pub fn do_approve_proposal(
+    ctx: Context<ApproveProposal>,
+    instance: Pubkey,
+    proposal_id: u64,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx.accounts.approver_role.is_active(clock.unix_timestamp) {
+        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
+    }
+
+    let signer = ctx.accounts.signer.key();
+    let approvals = &mut ctx.accounts.proposal.approvals;
+    if approvals.contains(&signer) {
+        return Err(error!(KeyringError::ErrProposalAlreadyApproved));
+    }
+    // Each approver is a role member, so the limit is only reached by
+    // approvers whose role was revoked after approving
+    if approvals.len() + 1 > MAX_PROPOSAL_APPROVALS as usize {
+        return Err(error!(KeyringError::ErrBreachedMaxRoleMemberLimit));
+    }
+    approvals.push(signer);
+
+    emit!(ProposalApproved {
+        instance,
+        proposal_id,
+        signer,
+        approvals: approvals.len() as u8,
+        approval_threshold: ctx.accounts.program_state.config.approval_threshold,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use crate::blacklist_entity::{
+    blacklist, check_blacklist_manager, resize_entity_mapping, BlacklistedEntity,
+};
+use crate::common::error::KeyringError;
+use crate::common::types::{
+    instance_seed, BlacklistEntry, BlacklistReason, Blacklisting, EntityData,
+    MAX_BLACKLIST_BATCH_SIZE,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Accounts};
+
+// Entity mappings of the entries are passed as writable remaining accounts, in
+// the same order as the entries.
+#[derive(Accounts)]
+#[instruction(instance: Pubkey)]
+pub struct BatchBlacklistEntities<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// CHECK: Role account of the signer for `BLACKLIST_MANAGER_ROLE` or the
+    /// blacklist manager role of the policy of the entries, checked by
+    /// `check_blacklist_manager`.
+    pub blacklist_manager_role: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn do_batch_blacklist_entities<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchBlacklistEntities<'info>>,
+    instance: Pubkey,
+    entries: Vec<BlacklistEntry>,
+    reason: BlacklistReason,
+    until: Option<u64>,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    check_blacklist_manager(
+        &ctx.accounts.blacklist_manager_role,
+        &ctx.accounts.signer.key(),
+        batch_policy_id(&entries),
+        &instance,
+        clock.unix_timestamp,
+    )?;
+
+    let current_timestamp: u64 = clock.unix_timestamp.try_into().unwrap();
+    if until.is_some_and(|until| until <= current_timestamp) {
+        return Err(error!(KeyringError::ErrInvalidBlacklistExpiry));
+    }
+    check_batch(&entries, ctx.remaining_accounts)?;
+
+    for (entry, entity_info) in entries.iter().zip(ctx.remaining_accounts.iter()) {
+        let mut entity_mapping = load_entity_mapping(
+            entity_info,
+            &instance,
+            entry,
+            &ctx.accounts.signer,
+            &ctx.accounts.system_program,
+        )?;
+        let (previously_blacklisted, previous_exp) = blacklist(
+            &mut entity_mapping,
+            Blacklisting { reason, until },
+            current_timestamp,
+        );
+        entity_mapping.exit(&crate::ID)?;
+
+        emit!(BlacklistedEntity {
+            instance,
+            policy_id: entry.policy_id,
+            trading_address: entry.trading_address,
+            signer: ctx.accounts.signer.key(),
+            previously_blacklisted,
+            previous_exp,
+            reason,
+            until,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+    }
+
+    Ok(())
+}
+
+// Policy of the entries if they all belong to the same policy
+pub(crate) fn batch_policy_id(entries: &[BlacklistEntry]) -> Option<u64> {
+    let policy_id = entries.first()?.policy_id;
+    entries
+        .iter()
+        .all(|entry| entry.policy_id == policy_id)
+        .then_some(policy_id)
+}
+
+pub(crate) fn check_batch(
+    entries: &[BlacklistEntry],
+    entity_mappings: &[AccountInfo],
+) -> Result<()> {
+    if entries.is_empty() || entries.len() > MAX_BLACKLIST_BATCH_SIZE as usize {
+        return Err(error!(KeyringError::ErrInvalidBatchSize));
+    }
+    if entity_mappings.len() != entries.len() {
+        return Err(error!(KeyringError::ErrEntityMappingMismatch));
+    }
+
+    Ok(())
+}
+
+// Entity mapping of the entry, created if it does not exist yet and grown if
+// it is a legacy account.
+pub(crate) fn load_entity_mapping<'info>(
+    entity_info: &'info AccountInfo<'info>,
+    instance: &Pubkey,
+    entry: &BlacklistEntry,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<Account<'info, EntityData>> {
+    let policy_id = entry.policy_id.to_le_bytes();
+    let trading_address = entry.trading_address.to_bytes();
+    let seeds = [
+        b"keyring_program".as_ref(),
+        b"_entity_mapping".as_ref(),
+        &policy_id,
+        &trading_address,
+        instance_seed(instance),
+    ];
+    let (address, bump) = Pubkey::find_program_address(&seeds, &crate::ID);
+    if entity_info.key() != address || !entity_info.is_writable {
+        return Err(error!(KeyringError::ErrEntityMappingMismatch));
+    }
+
+    if entity_info.owner != &crate::ID {
+        let space = 8 + EntityData::MAX_SIZE;
+        let rent = Rent::get()?.minimum_balance(space);
+        let bump = [bump];
+        let signer_seeds = [seeds[0], seeds[1], seeds[2], seeds[3], seeds[4], &bump];
+        // Accounts funded before their creation cannot be created directly
+        if entity_info.lamports() == 0 {
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    system_program.to_account_info(),
+                    system_program::CreateAccount {
+                        from: payer.to_account_info(),
+                        to: entity_info.clone(),
+                    },
+                    &[&signer_seeds],
+                ),
+                rent,
+                space as u64,
+                &crate::ID,
+            )?;
+        } else {
+            let shortfall = rent.saturating_sub(entity_info.lamports());
+            if shortfall != 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: payer.to_account_info(),
+                            to: entity_info.clone(),
+                        },
+                    ),
+                    shortfall,
+                )?;
+            }
+            system_program::allocate(
+                CpiContext::new_with_signer(
+                    system_program.to_account_info(),
+                    system_program::Allocate {
+                        account_to_allocate: entity_info.clone(),
+                    },
+                    &[&signer_seeds],
+                ),
+                space as u64,
+            )?;
+            system_program::assign(
+                CpiContext::new_with_signer(
+                    system_program.to_account_info(),
+                    system_program::Assign {
+                        account_to_assign: entity_info.clone(),
+                    },
+                    &[&signer_seeds],
+                ),
+                &crate::ID,
+            )?;
+        }
+        // The zeroed data of a new account reads as an entity without
+        // credential, the discriminator is written on exit
+        return Account::try_from_unchecked(entity_info);
+    }
+
+    let entity_mapping = Account::try_from(entity_info)?;
+    resize_entity_mapping(&entity_mapping, payer, system_program)?;
+    Ok(entity_mapping)
+}
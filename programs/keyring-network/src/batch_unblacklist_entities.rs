@@ -0,0 +1,65 @@
+use crate::batch_blacklist_entities::{batch_policy_id, check_batch, load_entity_mapping};
+use crate::blacklist_entity::check_blacklist_manager;
+use crate::common::types::BlacklistEntry;
+use crate::unblacklist_entity::{unblacklist, UnBlackListedEntity};
+use anchor_lang::prelude::*;
+use anchor_lang::Accounts;
+
+// Entity mappings of the entries are passed as writable remaining accounts, in
+// the same order as the entries.
+#[derive(Accounts)]
+#[instruction(instance: Pubkey)]
+pub struct BatchUnblacklistEntities<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// CHECK: Role account of the signer for `BLACKLIST_MANAGER_ROLE` or the
+    /// blacklist manager role of the policy of the entries, checked by
+    /// `check_blacklist_manager`.
+    pub blacklist_manager_role: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn do_batch_unblacklist_entities<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchUnblacklistEntities<'info>>,
+    instance: Pubkey,
+    entries: Vec<BlacklistEntry>,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    check_blacklist_manager(
+        &ctx.accounts.blacklist_manager_role,
+        &ctx.accounts.signer.key(),
+        batch_policy_id(&entries),
+        &instance,
+        clock.unix_timestamp,
+    )?;
+    check_batch(&entries, ctx.remaining_accounts)?;
+
+    for (entry, entity_info) in entries.iter().zip(ctx.remaining_accounts.iter()) {
+        let mut entity_mapping = load_entity_mapping(
+            entity_info,
+            &instance,
+            entry,
+            &ctx.accounts.signer,
+            &ctx.accounts.system_program,
+        )?;
+        let (previously_blacklisted, previous_exp, previous_reason) = unblacklist(
+            &mut entity_mapping,
+            clock.unix_timestamp.try_into().unwrap(),
+        );
+        entity_mapping.exit(&crate::ID)?;
+
+        emit!(UnBlackListedEntity {
+            instance,
+            policy_id: entry.policy_id,
+            trading_address: entry.trading_address,
+            signer: ctx.accounts.signer.key(),
+            previously_blacklisted,
+            previous_exp,
+            previous_reason,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+    }
+
+    Ok(())
+}
@@ -1,30 +1,63 @@
 use crate::common::error::KeyringError;
-use crate::common::types::{EntityData, Role, BLACKLIST_MANAGER_ROLE, CURRENT_VERSION};
+use crate::common::types::{
+    instance_seed, policy_blacklist_manager_role, BlacklistReason, Blacklisting, EntityData,
+    BLACKLIST_MANAGER_ROLE, CURRENT_VERSION, DEFAULT_INSTANCE,
+};
+use crate::manage_role::holds_role;
 use anchor_lang::prelude::*;
-use anchor_lang::Accounts;
+use anchor_lang::{system_program, Accounts};
 
 #[event]
+#[derive(Debug, PartialEq)]
 pub struct BlacklistedEntity {
-    policy_id: u64,
-    trading_address: Pubkey,
+    pub instance: Pubkey,
+    pub policy_id: u64,
+    pub trading_address: Pubkey,
+    pub signer: Pubkey,
+    pub previously_blacklisted: bool,
+    pub previous_exp: u64,
+    pub reason: BlacklistReason,
+    pub until: Option<u64>,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[derive(Accounts)]
-#[instruction(policy_id: u64, trading_address: Pubkey)]
+#[instruction(instance: Pubkey, policy_id: u64, trading_address: Pubkey)]
 pub struct BlacklistEntity<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
+    /// CHECK: Role account of the signer for `BLACKLIST_MANAGER_ROLE` or the
+    /// blacklist manager role of the policy, checked by
+    /// `check_blacklist_manager`.
+    pub blacklist_manager_role: AccountInfo<'info>,
     #[account(
-        seeds = [BLACKLIST_MANAGER_ROLE.as_ref(), signer.key().to_bytes().as_ref()],
-        bump
+        init_if_needed,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"_entity_mapping".as_ref(), &policy_id.to_le_bytes(), &trading_address.to_bytes(), instance_seed(&instance)],
+        bump,
+        space = EntityData::space(entity_mapping)
     )]
-    pub blacklist_manager_role: Account<'info, Role>,
+    pub entity_mapping: Account<'info, EntityData>,
+    pub system_program: Program<'info, System>,
+}
+
+// Accounts of the original `blacklist_entity`, for the default instance
+#[derive(Accounts)]
+#[instruction(policy_id: u64, trading_address: Pubkey)]
+pub struct BlacklistEntityLegacy<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// CHECK: Role account of the signer for `BLACKLIST_MANAGER_ROLE` or the
+    /// blacklist manager role of the policy, checked by
+    /// `check_blacklist_manager`.
+    pub blacklist_manager_role: AccountInfo<'info>,
     #[account(
         init_if_needed,
         payer = signer,
         seeds = [b"keyring_program".as_ref(), b"_entity_mapping".as_ref(), &policy_id.to_le_bytes(), &trading_address.to_bytes()],
         bump,
-        space = 8 + EntityData::MAX_SIZE
+        space = EntityData::space(entity_mapping)
     )]
     pub entity_mapping: Account<'info, EntityData>,
     pub system_program: Program<'info, System>,
@@ -32,28 +65,158 @@ pub struct BlacklistEntity<'info> {
 
 pub fn do_blacklist_entity(
     ctx: Context<BlacklistEntity>,
+    instance: Pubkey,
     policy_id: u64,
     trading_address: Pubkey,
+    reason: BlacklistReason,
+    until: Option<u64>,
 ) -> Result<()> {
-    if !ctx.accounts.blacklist_manager_role.has_role {
-        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
-    }
+    let clock: Clock = Clock::get()?;
+    check_blacklist_manager(
+        &ctx.accounts.blacklist_manager_role,
+        &ctx.accounts.signer.key(),
+        Some(policy_id),
+        &instance,
+        clock.unix_timestamp,
+    )?;
 
-    if ctx.accounts.entity_mapping.blacklisted {
-        ctx.accounts.entity_mapping.version = CURRENT_VERSION;
-        return Ok(());
+    let current_timestamp: u64 = clock.unix_timestamp.try_into().unwrap();
+    if until.is_some_and(|until| until <= current_timestamp) {
+        return Err(error!(KeyringError::ErrInvalidBlacklistExpiry));
     }
 
-    *ctx.accounts.entity_mapping = EntityData {
-        version: CURRENT_VERSION,
-        blacklisted: true,
-        exp: 0,
-    };
+    resize_entity_mapping(
+        &ctx.accounts.entity_mapping,
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+
+    let (previously_blacklisted, previous_exp) = blacklist(
+        &mut ctx.accounts.entity_mapping,
+        Blacklisting { reason, until },
+        current_timestamp,
+    );
 
     emit!(BlacklistedEntity {
+        instance,
         policy_id,
-        trading_address
+        trading_address,
+        signer: ctx.accounts.signer.key(),
+        previously_blacklisted,
+        previous_exp,
+        reason,
+        until,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
     });
 
     Ok(())
 }
+
+pub fn do_blacklist_entity_legacy<'info>(
+    ctx: Context<'_, '_, '_, 'info, BlacklistEntityLegacy<'info>>,
+    policy_id: u64,
+    trading_address: Pubkey,
+) -> Result<()> {
+    let legacy = ctx.accounts;
+    let mut accounts = BlacklistEntity {
+        signer: legacy.signer.clone(),
+        blacklist_manager_role: legacy.blacklist_manager_role.clone(),
+        entity_mapping: legacy.entity_mapping.clone(),
+        system_program: legacy.system_program.clone(),
+    };
+    do_blacklist_entity(
+        Context::new(
+            ctx.program_id,
+            &mut accounts,
+            ctx.remaining_accounts,
+            BlacklistEntityBumps::default(),
+        ),
+        DEFAULT_INSTANCE,
+        policy_id,
+        trading_address,
+        BlacklistReason::Unspecified,
+        None,
+    )?;
+    // Written back when the legacy accounts exit
+    legacy.entity_mapping = accounts.entity_mapping;
+
+    Ok(())
+}
+
+// Members of `BLACKLIST_MANAGER_ROLE` manage the blacklist of every policy,
+// members of the blacklist manager role of a policy only that of the policy.
+// `policy_id` is the policy of every entity changed, if they share one.
+pub(crate) fn check_blacklist_manager(
+    role_account: &AccountInfo,
+    signer: &Pubkey,
+    policy_id: Option<u64>,
+    instance: &Pubkey,
+    now: i64,
+) -> Result<()> {
+    if holds_role(role_account, &BLACKLIST_MANAGER_ROLE, signer, instance, now)? {
+        return Ok(());
+    }
+    if let Some(policy_id) = policy_id {
+        let policy_role = policy_blacklist_manager_role(policy_id);
+        if holds_role(role_account, &policy_role, signer, instance, now)? {
+            return Ok(());
+        }
+    }
+    Err(error!(KeyringError::ErrCallerDoesNotHaveRole))
+}
+
+// Blacklists the entity, returning whether it was blacklisted before and its
+// previous expiry. Blacklisting an entity again only replaces the terms.
+pub(crate) fn blacklist(
+    entity_mapping: &mut EntityData,
+    blacklisting: Blacklisting,
+    current_timestamp: u64,
+) -> (bool, u64) {
+    let previously_blacklisted = entity_mapping.is_blacklisted(current_timestamp);
+    let previous_exp = entity_mapping.exp;
+
+    if previously_blacklisted {
+        entity_mapping.version = CURRENT_VERSION;
+        entity_mapping.blacklisting = blacklisting;
+    } else {
+        *entity_mapping = EntityData {
+            version: CURRENT_VERSION,
+            blacklisted: true,
+            exp: 0,
+            blacklisting,
+        };
+    }
+
+    (previously_blacklisted, previous_exp)
+}
+
+// Grows entity accounts created before blacklistings had terms to the
+// current size, so that they can be written.
+pub(crate) fn resize_entity_mapping<'info>(
+    entity_mapping: &Account<'info, EntityData>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let entity_info = entity_mapping.to_account_info();
+    if entity_info.data_len() >= 8 + EntityData::MAX_SIZE {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?
+        .minimum_balance(8 + EntityData::MAX_SIZE)
+        .saturating_sub(entity_info.lamports());
+    if rent != 0 {
+        let cpi_context = CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: entity_info.clone(),
+            },
+        );
+        system_program::transfer(cpi_context, rent)?;
+    }
+    entity_info.realloc(8 + EntityData::MAX_SIZE, false)?;
+
+    Ok(())
+}
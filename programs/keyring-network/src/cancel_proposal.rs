@@ -0,0 +1,56 @@
+use crate::common::error::KeyringError;
+use crate::common::types::{instance_seed, Proposal, Role, CANCELLER_ROLE};
+use anchor_lang::prelude::*;
+use anchor_lang::Accounts;
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct ProposalCancelled {
+    pub instance: Pubkey,
+    pub proposal_id: u64,
+    pub signer: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, proposal_id: u64)]
+pub struct CancelProposal<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"keyring_program".as_ref(), b"proposal".as_ref(), &proposal_id.to_le_bytes(), instance_seed(&instance)],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: Receives the rent of the proposal back.
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: AccountInfo<'info>,
+    #[account(
+        seeds = [CANCELLER_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub canceller_role: Account<'info, Role>,
+    pub signer: Signer<'info>,
+}
+
+pub fn do_cancel_proposal(
+    ctx: Context<CancelProposal>,
+    instance: Pubkey,
+    proposal_id: u64,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx.accounts.canceller_role.is_active(clock.unix_timestamp) {
+        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
+    }
+
+    emit!(ProposalCancelled {
+        instance,
+        proposal_id,
+        signer: ctx.accounts.signer.key(),
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
@@ -1,17 +1,38 @@
 use crate::common::error::KeyringError;
+use crate::common::types::instance_seed;
 use crate::common::types::EntityData;
+use crate::common::types::DEFAULT_INSTANCE;
 use anchor_lang::prelude::*;
 use anchor_lang::Accounts;
 
 #[event]
+#[derive(Debug, PartialEq)]
 pub struct ValidCredentials {
-    policy_id: u64,
-    trading_address: Pubkey,
+    pub instance: Pubkey,
+    pub policy_id: u64,
+    pub trading_address: Pubkey,
+    pub signer: Pubkey,
+    pub exp: u64,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[derive(Accounts)]
-#[instruction(policy_id: u64, trading_address: Pubkey)]
+#[instruction(instance: Pubkey, policy_id: u64, trading_address: Pubkey)]
 pub struct CheckCredential<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"keyring_program".as_ref(), b"_entity_mapping".as_ref(), &policy_id.to_le_bytes(), &trading_address.to_bytes(), instance_seed(&instance)],
+        bump,
+    )]
+    pub entity_mapping: Account<'info, EntityData>,
+}
+
+// Accounts of the original `check_credential`, for the default instance
+#[derive(Accounts)]
+#[instruction(policy_id: u64, trading_address: Pubkey)]
+pub struct CheckCredentialLegacy<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
     #[account(
@@ -23,21 +44,52 @@ pub struct CheckCredential<'info> {
 
 pub fn do_check_credential(
     ctx: Context<CheckCredential>,
+    instance: Pubkey,
     policy_id: u64,
     trading_address: Pubkey,
 ) -> Result<()> {
     let clock: Clock = Clock::get()?;
     let current_timestamp = clock.unix_timestamp.try_into().unwrap();
 
-    if !ctx.accounts.entity_mapping.blacklisted
+    if !ctx
+        .accounts
+        .entity_mapping
+        .is_blacklisted(current_timestamp)
         && ctx.accounts.entity_mapping.exp > current_timestamp
     {
         emit!(ValidCredentials {
+            instance,
             policy_id,
-            trading_address
+            trading_address,
+            signer: ctx.accounts.signer.key(),
+            exp: ctx.accounts.entity_mapping.exp,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
         });
         Ok(())
     } else {
         Err(error!(KeyringError::ErrInCheckingCredentials))
     }
 }
+
+pub fn do_check_credential_legacy<'info>(
+    ctx: Context<'_, '_, '_, 'info, CheckCredentialLegacy<'info>>,
+    policy_id: u64,
+    trading_address: Pubkey,
+) -> Result<()> {
+    let mut accounts = CheckCredential {
+        signer: ctx.accounts.signer.clone(),
+        entity_mapping: ctx.accounts.entity_mapping.clone(),
+    };
+    do_check_credential(
+        Context::new(
+            ctx.program_id,
+            &mut accounts,
+            ctx.remaining_accounts,
+            CheckCredentialBumps::default(),
+        ),
+        DEFAULT_INSTANCE,
+        policy_id,
+        trading_address,
+    )
+}
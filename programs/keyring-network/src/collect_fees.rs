@@ -1,18 +1,47 @@
 use crate::common::error::KeyringError;
 use crate::common::types::ProgramState;
 use crate::common::types::Role;
-use crate::common::types::OPERATOR_ROLE;
+use crate::common::types::{instance_seed, DEFAULT_INSTANCE, OPERATOR_ROLE, PAUSE_FEE_COLLECTION};
 use anchor_lang::prelude::*;
 use anchor_lang::Accounts;
 
 #[event]
+#[derive(Debug, PartialEq)]
 pub struct FeesCollected {
-    amount: u64,
-    receiver: Pubkey,
+    pub instance: Pubkey,
+    pub amount: u64,
+    pub receiver: Pubkey,
+    pub signer: Pubkey,
+    pub remaining_balance: u64,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[derive(Accounts)]
+#[instruction(instance: Pubkey)]
 pub struct CollectFees<'info> {
+    /// CHECK: We are using AccountInfo here as receiver account is only
+    /// used to receive the lamports and does not play any other role.
+    #[account(mut)]
+    pub receiver_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [OPERATOR_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub operator_role: Account<'info, Role>,
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"global_state".as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+// Accounts of the original `collect_fees`, for the default instance
+#[derive(Accounts)]
+pub struct CollectFeesLegacy<'info> {
     /// CHECK: We are using AccountInfo here as receiver account is only
     /// used to receive the lamports and does not play any other role.
     #[account(mut)]
@@ -32,11 +61,23 @@ pub struct CollectFees<'info> {
     pub program_state: Account<'info, ProgramState>,
 }
 
-pub fn do_collect_fees(ctx: Context<CollectFees>) -> Result<()> {
-    if !ctx.accounts.operator_role.has_role {
+pub fn do_collect_fees(ctx: Context<CollectFees>, instance: Pubkey) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx.accounts.operator_role.is_active(clock.unix_timestamp) {
         return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
     }
 
+    let config = &ctx.accounts.program_state.config;
+    if config.is_paused(PAUSE_FEE_COLLECTION) {
+        return Err(error!(KeyringError::ErrProgramPaused));
+    }
+    if config
+        .fee_vault
+        .is_some_and(|fee_vault| fee_vault != ctx.accounts.receiver_account.key())
+    {
+        return Err(error!(KeyringError::ErrInvalidFeeVault));
+    }
+
     let rent_sysvar = Rent::get()?;
     // We added 8 bytes for discriminator
     let min_amount_for_rent_exempt = rent_sysvar.minimum_balance(8 + ProgramState::MAX_SIZE);
@@ -54,9 +95,35 @@ pub fn do_collect_fees(ctx: Context<CollectFees>) -> Result<()> {
     }
 
     emit!(FeesCollected {
+        instance,
         amount: amount_to_transfer,
-        receiver: ctx.accounts.receiver_account.key().clone()
+        receiver: ctx.accounts.receiver_account.key(),
+        signer: ctx.accounts.signer.key(),
+        remaining_balance: ctx.accounts.program_state.get_lamports(),
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
     });
 
     Ok(())
 }
+
+pub fn do_collect_fees_legacy<'info>(
+    ctx: Context<'_, '_, '_, 'info, CollectFeesLegacy<'info>>,
+) -> Result<()> {
+    let legacy = ctx.accounts;
+    let mut accounts = CollectFees {
+        receiver_account: legacy.receiver_account.clone(),
+        signer: legacy.signer.clone(),
+        operator_role: legacy.operator_role.clone(),
+        program_state: legacy.program_state.clone(),
+    };
+    do_collect_fees(
+        Context::new(
+            ctx.program_id,
+            &mut accounts,
+            ctx.remaining_accounts,
+            CollectFeesBumps::default(),
+        ),
+        DEFAULT_INSTANCE,
+    )
+}
@@ -30,4 +30,84 @@ pub enum KeyringError {
     ErrInvalidChainId,
     #[msg("Number of keys breached max active key limit")]
     ErrBreachedMaxActiveKeyLimit,
+    #[msg("Key is not registered")]
+    ErrKeyNotRegistered,
+    #[msg("Invalid parameters passed in key rotation")]
+    ErrInvalidKeyRotationParams,
+    #[msg("Invalid policy scope")]
+    ErrInvalidPolicyScope,
+    #[msg("Key is not allowed to sign credentials for this policy")]
+    ErrPolicyNotAllowedForKey,
+    #[msg("Invalid key quota")]
+    ErrInvalidKeyQuota,
+    #[msg("Key has reached its credential quota")]
+    ErrKeyQuotaExceeded,
+    #[msg("Key has reached its credential rate limit")]
+    ErrKeyRateLimitExceeded,
+    #[msg("Invalid policy threshold")]
+    ErrInvalidPolicyThreshold,
+    #[msg("Not enough attestations for the policy threshold")]
+    ErrThresholdNotMet,
+    #[msg("Attestation key is used more than once")]
+    ErrDuplicateAttestationKey,
+    #[msg("Attestations do not match the key mapping accounts")]
+    ErrInvalidAttestationAccounts,
+    #[msg("Signature s value is in the upper half of the curve order")]
+    ErrMalleableSignature,
+    #[msg("Key mapping does not belong to the signer of the credential")]
+    ErrKeyMappingMismatch,
+    #[msg("Signature was not made by the signing key")]
+    ErrSignatureMismatch,
+    #[msg("Signing key is revoked")]
+    ErrKeyRevoked,
+    #[msg("Signing key is not valid yet")]
+    ErrKeyNotYetValid,
+    #[msg("Signing key has expired")]
+    ErrKeyExpired,
+    #[msg("Credential expiry is in the past")]
+    ErrCredentialExpired,
+    #[msg("Entity is blacklisted for the policy")]
+    ErrEntityBlacklisted,
+    #[msg("Credential expiry must be later than the current one")]
+    ErrExpiryNotIncreasing,
+    #[msg("Only the program upgrade authority can initialize the program")]
+    ErrNotUpgradeAuthority,
+    #[msg("Instruction is paused")]
+    ErrProgramPaused,
+    #[msg("Fees can only be collected to the fee vault")]
+    ErrInvalidFeeVault,
+    #[msg("Credential validity exceeds the configured maximum")]
+    ErrCredentialValidityTooLong,
+    #[msg("Credential cost is below the configured minimum")]
+    ErrCostBelowMinimum,
+    #[msg("Role expiry is in the past")]
+    ErrInvalidRoleExpiry,
+    #[msg("Number of role members breached max role member limit")]
+    ErrBreachedMaxRoleMemberLimit,
+    #[msg("Last permanent admin cannot be revoked or given an expiry")]
+    ErrLastAdmin,
+    #[msg("Role is not defined")]
+    ErrRoleNotDefined,
+    #[msg("Role name must not be empty or longer than 32 bytes")]
+    ErrInvalidRoleName,
+    #[msg("Default admin role cannot be redefined")]
+    ErrInvalidRoleDefinition,
+    #[msg("Proposal has too many accounts, too much data or a signer other than the timelock")]
+    ErrInvalidProposal,
+    #[msg("Proposal cannot be executed before its delay has passed")]
+    ErrProposalNotReady,
+    #[msg("Accounts do not match the accounts of the proposal")]
+    ErrProposalAccountMismatch,
+    #[msg("Proposal was already approved by the signer")]
+    ErrProposalAlreadyApproved,
+    #[msg("Proposal does not have enough approvals")]
+    ErrProposalNotApproved,
+    #[msg("Blacklisting must expire in the future")]
+    ErrInvalidBlacklistExpiry,
+    #[msg("Batch must have between 1 and 12 entries")]
+    ErrInvalidBatchSize,
+    #[msg("Entity mapping does not match the policy and trading address of the entry")]
+    ErrEntityMappingMismatch,
+    #[msg("Legacy instruction cannot be used in this state, use its _v2 version")]
+    ErrLegacyInstructionUnsupported,
 }
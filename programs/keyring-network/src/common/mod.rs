@@ -1,3 +1,4 @@
 pub mod error;
+pub mod typed_data;
 pub mod types;
 pub mod verify_auth_message;
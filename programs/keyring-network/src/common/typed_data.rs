@@ -0,0 +1,90 @@
+use crate::common::types::ChainId;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::keccak::Hash;
+
+pub const EIP712_PREFIX: &[u8] = b"\x19\x01";
+pub const EIP712_DOMAIN_NAME: &[u8] = b"Keyring Network";
+pub const EIP712_DOMAIN_VERSION: &[u8] = b"1";
+// The verifying program id does not fit into `address verifyingContract`, it
+// is passed in the domain salt instead, see `domain_salt`.
+pub const EIP712_DOMAIN_TYPE: &[u8] =
+    b"EIP712Domain(string name,string version,uint256 chainId,bytes32 salt)";
+pub const CREDENTIAL_TYPE: &[u8] =
+    b"Credential(bytes32 tradingAddress,uint256 policyId,uint256 validUntil,uint256 cost,bytes backdoor)";
+
+// ABI encoding of an unsigned integer as a 32 byte word
+pub fn encode_uint(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+// Salt of the domain, committing to the verifying program, the full chain id
+// and the instance, so that signatures cannot be replayed on another instance
+// sharing the chain id and keys.
+pub fn domain_salt(chain_id: &ChainId, program_id: &Pubkey, instance: &Pubkey) -> Hash {
+    keccak::hashv(&[program_id.as_ref(), &chain_id.chain_id, instance.as_ref()])
+}
+
+// Domain separator binding signatures to the chain, the verifying program and
+// the instance. `chainId` is the leading `uint32` of the chain id like in the
+// packed formats, as chain ids of up to `CHAIN_ID_MAX_SIZE` bytes do not fit
+// into a `uint256`. The full chain id is part of the salt.
+pub fn domain_separator(chain_id: &ChainId, program_id: &Pubkey, instance: &Pubkey) -> Hash {
+    let mut encoded_chain_id = [0u8; 32];
+    encoded_chain_id[28..].copy_from_slice(&chain_id.chain_id[0..4]);
+
+    keccak::hashv(&[
+        keccak::hash(EIP712_DOMAIN_TYPE).as_ref(),
+        keccak::hash(EIP712_DOMAIN_NAME).as_ref(),
+        keccak::hash(EIP712_DOMAIN_VERSION).as_ref(),
+        &encoded_chain_id,
+        domain_salt(chain_id, program_id, instance).as_ref(),
+    ])
+}
+
+// `hashStruct` of the typed `Credential`. The trading address is the full
+// Solana pubkey, not the truncated hash used by the packed format.
+pub fn hash_credential(
+    trading_address: &Pubkey,
+    policy_id: u64,
+    valid_until: u64,
+    cost: u64,
+    backdoor: &[u8],
+) -> Hash {
+    keccak::hashv(&[
+        keccak::hash(CREDENTIAL_TYPE).as_ref(),
+        trading_address.as_ref(),
+        &encode_uint(policy_id),
+        &encode_uint(valid_until),
+        &encode_uint(cost),
+        keccak::hash(backdoor).as_ref(),
+    ])
+}
+
+// Final EIP-712 digest `keccak256("\x19\x01" || domainSeparator || hashStruct)`
+pub fn hash_typed_data(domain_separator: &Hash, struct_hash: &Hash) -> Hash {
+    keccak::hashv(&[
+        EIP712_PREFIX,
+        domain_separator.as_ref(),
+        struct_hash.as_ref(),
+    ])
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_typed_data_payload(
+    trading_address: &Pubkey,
+    policy_id: u64,
+    chain_id: &ChainId,
+    valid_until: u64,
+    cost: u64,
+    backdoor: &[u8],
+    program_id: &Pubkey,
+    instance: &Pubkey,
+) -> Hash {
+    hash_typed_data(
+        &domain_separator(chain_id, program_id, instance),
+        &hash_credential(trading_address, policy_id, valid_until, cost, backdoor),
+    )
+}
@@ -1,11 +1,13 @@
 use anchor_lang::account;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
-    keccak::{hash, Hash},
+    keccak::{hash, hashv, Hash},
     secp256k1_recover::SECP256K1_PUBLIC_KEY_LENGTH,
 };
 
 pub const CURRENT_VERSION: u8 = 1;
+// Instance whose accounts use the seeds from before instances were added
+pub const DEFAULT_INSTANCE: Pubkey = Pubkey::new_from_array([0; 32]);
 pub const DEFAULT_ADMIN_ROLE: [u8; 32] = [0; 32];
 pub const KEY_MANAGER_ROLE: [u8; 32] = [
     27, 30, 232, 100, 197, 54, 57, 215, 70, 43, 119, 63, 124, 139, 76, 234, 20, 166, 174, 54, 21,
@@ -19,15 +21,91 @@ pub const OPERATOR_ROLE: [u8; 32] = [
     151, 102, 112, 112, 197, 78, 241, 130, 176, 245, 133, 139, 3, 75, 234, 193, 182, 243, 8, 154,
     162, 211, 24, 139, 177, 232, 146, 159, 79, 169, 185, 41,
 ];
+pub const PROPOSER_ROLE: [u8; 32] = [
+    176, 154, 165, 174, 179, 112, 44, 253, 80, 182, 182, 43, 196, 83, 38, 4, 147, 143, 33, 36, 138,
+    39, 161, 213, 202, 115, 96, 130, 182, 129, 156, 193,
+];
+pub const CANCELLER_ROLE: [u8; 32] = [
+    253, 100, 60, 114, 113, 12, 99, 192, 24, 2, 89, 171, 166, 178, 208, 84, 81, 227, 89, 26, 36,
+    229, 139, 98, 35, 147, 120, 8, 87, 38, 247, 131,
+];
+pub const APPROVER_ROLE: [u8; 32] = [
+    64, 138, 54, 21, 31, 132, 23, 9, 17, 106, 78, 138, 202, 78, 2, 2, 135, 79, 127, 84, 104, 125,
+    203, 134, 59, 30, 164, 103, 45, 201, 216, 207,
+];
+// Roles which are defined without a `RoleDefinition`, administered by
+// `DEFAULT_ADMIN_ROLE` unless a definition says otherwise
+pub const BUILTIN_ROLES: [[u8; 32]; 7] = [
+    DEFAULT_ADMIN_ROLE,
+    KEY_MANAGER_ROLE,
+    BLACKLIST_MANAGER_ROLE,
+    OPERATOR_ROLE,
+    PROPOSER_ROLE,
+    CANCELLER_ROLE,
+    APPROVER_ROLE,
+];
+
+// Role of the blacklist managers of a single policy, who may blacklist and
+// unblacklist entities of that policy only. Like other custom roles it has to
+// be defined with `define_role` before it can be granted.
+pub fn policy_blacklist_manager_role(policy_id: u64) -> [u8; 32] {
+    hashv(&[BLACKLIST_MANAGER_ROLE.as_ref(), &policy_id.to_le_bytes()]).to_bytes()
+}
+
+// Seed appended to the seeds of every account of an instance, so that several
+// independent instances with their own admins, keys and chain id can share the
+// program. The default instance has an empty seed which keeps its addresses.
+pub fn instance_seed(instance: &Pubkey) -> &[u8] {
+    if *instance == DEFAULT_INSTANCE {
+        &[]
+    } else {
+        instance.as_ref()
+    }
+}
 
 #[account]
 pub struct ProgramState {
     pub version: u8,
     pub chain_id: ChainId,
+    pub config: ProgramConfig,
+    // Number of policies whose credentials need more than one signature. The
+    // legacy `create_credential` cannot see policy configs and is refused
+    // while there are any.
+    pub threshold_policies: u32,
 }
 
 impl ProgramState {
-    pub const MAX_SIZE: usize = 32 + 41 + 1;
+    pub const MAX_SIZE: usize = 32 + 41 + 1 + ProgramConfig::MAX_SIZE + 4;
+}
+
+// Flags of `ProgramConfig::pause_flags`
+pub const PAUSE_CREDENTIALS: u8 = 1 << 0;
+pub const PAUSE_FEE_COLLECTION: u8 = 1 << 1;
+
+// Global parameters set by the admin with `update_config`. The default config
+// is stored as zeroes, so states created before it existed read as default.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct ProgramConfig {
+    // Only receiver of collected fees. Fees can be sent anywhere while unset.
+    pub fee_vault: Option<Pubkey>,
+    pub pause_flags: u8,
+    // Longest credential lifetime in seconds. Zero disables the limit.
+    pub max_credential_validity: u64,
+    // Smallest credential cost in lamports
+    pub min_credential_cost: u64,
+    // Seconds between scheduling a proposal and when it can be executed
+    pub timelock_delay: u64,
+    // Number of distinct members of `APPROVER_ROLE` which have to approve a
+    // proposal before it can be executed. Zero disables approvals.
+    pub approval_threshold: u8,
+}
+
+impl ProgramConfig {
+    pub const MAX_SIZE: usize = 1 + 32 + 1 + 8 + 8 + 8 + 1;
+
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.pause_flags & flag != 0
+    }
 }
 
 #[account]
@@ -36,13 +114,177 @@ pub struct KeyEntry {
     pub is_valid: bool,
     pub valid_from: u64,
     pub valid_to: u64,
+    pub policy_scope: PolicyScope,
+    pub quota: KeyQuota,
+    pub usage: KeyUsage,
 }
 
 impl KeyEntry {
-    pub const MAX_SIZE: usize = 1 + 8 + 8 + 1;
+    pub const MAX_SIZE: usize =
+        1 + 8 + 8 + 1 + PolicyScope::MAX_SIZE + KeyQuota::MAX_SIZE + KeyUsage::MAX_SIZE;
+    // Size of key mappings registered before keys had a policy scope, quota
+    // and usage
+    pub const LEGACY_SIZE: usize = 1 + 8 + 8 + 1;
+
+    // Space of the key mapping, which keeps its size if it is a legacy account
+    // until it is resized by `resize_key_mapping`
+    pub fn space(key_mapping: &AccountInfo) -> usize {
+        if key_mapping.data_len() == 8 + Self::LEGACY_SIZE {
+            8 + Self::LEGACY_SIZE
+        } else {
+            8 + Self::MAX_SIZE
+        }
+    }
+}
+
+// Key mappings registered before keys had a policy scope, quota and usage end
+// right before them, so that the missing fields read as default.
+fn read_u64_or_eof<R: std::io::Read>(reader: &mut R) -> std::io::Result<Option<u64>> {
+    let mut bytes = [0u8; 8];
+    let read = reader.read(&mut bytes)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    reader.read_exact(&mut bytes[read..])?;
+    Ok(Some(u64::from_le_bytes(bytes)))
+}
+
+// Limits on the number of credentials a key can sign. Zero disables a limit.
+#[derive(AnchorSerialize, Clone, Debug, Default, PartialEq)]
+pub struct KeyQuota {
+    pub max_credentials: u64,
+    // Length of the rate limit window in seconds. Windows are aligned to
+    // multiples of the length.
+    pub window_duration: u64,
+    pub max_credentials_per_window: u64,
+}
+
+impl AnchorDeserialize for KeyQuota {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let Some(max_credentials) = read_u64_or_eof(reader)? else {
+            return Ok(Self::default());
+        };
+        Ok(Self {
+            max_credentials,
+            window_duration: u64::deserialize_reader(reader)?,
+            max_credentials_per_window: u64::deserialize_reader(reader)?,
+        })
+    }
+}
+
+impl KeyQuota {
+    pub const MAX_SIZE: usize = 8 + 8 + 8;
+
+    pub fn is_valid(&self) -> bool {
+        (self.window_duration == 0) == (self.max_credentials_per_window == 0)
+    }
+
+    pub fn window_start(&self, timestamp: u64) -> u64 {
+        if self.window_duration == 0 {
+            return 0;
+        }
+        timestamp - timestamp % self.window_duration
+    }
+}
+
+#[derive(AnchorSerialize, Clone, Debug, Default, PartialEq)]
+pub struct KeyUsage {
+    pub credentials_issued: u64,
+    pub window_start: u64,
+    pub window_credentials_issued: u64,
+}
+
+impl AnchorDeserialize for KeyUsage {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let Some(credentials_issued) = read_u64_or_eof(reader)? else {
+            return Ok(Self::default());
+        };
+        Ok(Self {
+            credentials_issued,
+            window_start: u64::deserialize_reader(reader)?,
+            window_credentials_issued: u64::deserialize_reader(reader)?,
+        })
+    }
+}
+
+impl KeyUsage {
+    pub const MAX_SIZE: usize = 8 + 8 + 8;
+}
+
+pub const MAX_SCOPED_POLICIES: usize = 16;
+
+// Policies a key is allowed to sign credentials for.
+#[derive(AnchorSerialize, Clone, Debug, Default, PartialEq)]
+pub enum PolicyScope {
+    #[default]
+    Any,
+    // Inclusive range of policy ids
+    Range {
+        from: u64,
+        to: u64,
+    },
+    List(Vec<u64>),
+}
+
+impl AnchorDeserialize for PolicyScope {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let mut variant = [0u8; 1];
+        if reader.read(&mut variant)? == 0 {
+            return Ok(Self::default());
+        }
+        match variant[0] {
+            0 => Ok(PolicyScope::Any),
+            1 => Ok(PolicyScope::Range {
+                from: u64::deserialize_reader(reader)?,
+                to: u64::deserialize_reader(reader)?,
+            }),
+            2 => Ok(PolicyScope::List(Vec::<u64>::deserialize_reader(reader)?)),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Invalid policy scope",
+            )),
+        }
+    }
+}
+
+impl PolicyScope {
+    pub const MAX_SIZE: usize = 1 + 4 + 8 * MAX_SCOPED_POLICIES;
+
+    pub fn is_valid(&self) -> bool {
+        match self {
+            PolicyScope::Any => true,
+            PolicyScope::Range { from, to } => from <= to,
+            PolicyScope::List(policy_ids) => {
+                !policy_ids.is_empty() && policy_ids.len() <= MAX_SCOPED_POLICIES
+            }
+        }
+    }
+
+    pub fn allows(&self, policy_id: u64) -> bool {
+        match self {
+            PolicyScope::Any => true,
+            PolicyScope::Range { from, to } => (*from..=*to).contains(&policy_id),
+            PolicyScope::List(policy_ids) => policy_ids.contains(&policy_id),
+        }
+    }
+}
+
+// Encoding of the credential payload signed by attestation keys
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum AttestationFormat {
+    // `abi.encodePacked` payload signed as an Ethereum signed message
+    #[default]
+    Packed,
+    // EIP-712 typed `Credential`, see `common::typed_data`
+    Eip712,
+    // Packed payload with 64-bit policy id and expiry, see
+    // `pack_extended_auth_message`
+    PackedExtended,
 }
 
 pub const MAX_ACTIVE_KEYS: u8 = 10;
+// Length of an Ethereum address, accepted in place of a full public key
+pub const ETH_ADDRESS_LENGTH: usize = 20;
 
 #[account]
 #[derive(Debug, PartialEq)]
@@ -64,26 +306,197 @@ impl ToHash for Vec<u8> {
     }
 }
 
+// Why an entity is blacklisted, recorded for compliance
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum BlacklistReason {
+    #[default]
+    Unspecified,
+    Sanctions,
+    FraudReview,
+    CourtOrder,
+    Other,
+}
+
+// Terms of the blacklisting of an entity
+#[derive(AnchorSerialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Blacklisting {
+    pub reason: BlacklistReason,
+    // Unix timestamp from which the blacklisting is lifted, `None` if it is
+    // permanent
+    pub until: Option<u64>,
+}
+
+impl Blacklisting {
+    pub const MAX_SIZE: usize = 1 + 9;
+}
+
+// Entity accounts created before blacklistings had terms end right before
+// them, so that missing terms read as default.
+impl AnchorDeserialize for Blacklisting {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let mut reason = [0u8; 1];
+        if reader.read(&mut reason)? == 0 {
+            return Ok(Self::default());
+        }
+        Ok(Self {
+            reason: BlacklistReason::deserialize(&mut &reason[..])?,
+            until: Option::<u64>::deserialize_reader(reader)?,
+        })
+    }
+}
+
+// Entries of a batch blacklist instruction, bounded by the transaction size.
+// Each entry adds 73 bytes (its 40 bytes of data and the entity account with
+// its index), so a transaction signed and paid by the blacklist manager fits
+// 12 entries in the 1232 bytes of a packet (1167 bytes, 13 entries take 1240).
+pub const MAX_BLACKLIST_BATCH_SIZE: u8 = 12;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct BlacklistEntry {
+    pub policy_id: u64,
+    pub trading_address: Pubkey,
+}
+
 #[account]
 #[derive(Debug, PartialEq)]
 pub struct EntityData {
     pub version: u8,
     pub blacklisted: bool,
     pub exp: u64,
+    pub blacklisting: Blacklisting,
 }
 
 impl EntityData {
-    pub const MAX_SIZE: usize = 1 + 8 + 1;
+    pub const MAX_SIZE: usize = 1 + 8 + 1 + Blacklisting::MAX_SIZE;
+    // Size of entity accounts created before blacklistings had terms
+    pub const LEGACY_SIZE: usize = 1 + 8 + 1;
+
+    // Space of the entity account, which keeps its size if it is a legacy
+    // account until it is resized by `resize_entity_mapping`
+    pub fn space(entity_mapping: &AccountInfo) -> usize {
+        if entity_mapping.data_len() == 8 + Self::LEGACY_SIZE {
+            8 + Self::LEGACY_SIZE
+        } else {
+            8 + Self::MAX_SIZE
+        }
+    }
+
+    // Whether the entity is blacklisted at `now`, temporary blacklistings
+    // being lifted once they expire
+    pub fn is_blacklisted(&self, now: u64) -> bool {
+        self.blacklisted && now < self.blacklisting.until.unwrap_or(u64::MAX)
+    }
+}
+
+pub const MAX_ATTESTATION_KEYS: u8 = 5;
+
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct PolicyConfig {
+    pub version: u8,
+    // Number of distinct registered keys which have to sign a credential for
+    // the policy. Policies without a config require a single signature.
+    pub threshold: u8,
+}
+
+impl PolicyConfig {
+    pub const MAX_SIZE: usize = 1 + 1;
 }
 
 #[account]
 #[derive(Debug, PartialEq)]
 pub struct Role {
     pub has_role: bool,
+    // Unix timestamp from which the grant is no longer honored. Role accounts
+    // created before grants could expire read as `None` from their padding.
+    pub expires_at: Option<i64>,
 }
 
 impl Role {
-    pub const MAX_SIZE: usize = 8;
+    pub const MAX_SIZE: usize = 1 + 9;
+
+    pub fn is_active(&self, now: i64) -> bool {
+        self.has_role && now < self.expires_at.unwrap_or(i64::MAX)
+    }
+
+    pub fn is_permanent(&self) -> bool {
+        self.has_role && self.expires_at.is_none()
+    }
+}
+
+pub const MAX_ROLE_NAME_LENGTH: usize = 32;
+
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct RoleDefinition {
+    pub name: String,
+    // Role whose members may grant the role
+    pub admin_role: [u8; 32],
+    // Hash of the off-chain description of the role
+    pub description_hash: [u8; 32],
+}
+
+impl RoleDefinition {
+    pub const MAX_SIZE: usize = 4 + MAX_ROLE_NAME_LENGTH + 32 + 32;
+}
+
+// Every role is indexed, so a role can be granted to at most this many users
+// at once. Further grants fail with `ErrBreachedMaxRoleMemberLimit` until a
+// member is revoked or renounces the role.
+pub const MAX_ROLE_MEMBERS: u8 = 32;
+
+// Index of the users a role is granted to, regardless of the expiry of their
+// grant. Grants made before the index existed are only indexed once the role
+// is granted to the user again.
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct RoleMembers {
+    pub members: Vec<Pubkey>,
+}
+
+impl RoleMembers {
+    pub const MAX_SIZE: usize = 4 + 32 * MAX_ROLE_MEMBERS as usize;
+}
+
+pub const MAX_PROPOSAL_ACCOUNTS: u8 = 8;
+pub const MAX_PROPOSAL_DATA_LENGTH: usize = 256;
+// Approvals come from distinct members of `APPROVER_ROLE`
+pub const MAX_PROPOSAL_APPROVALS: u8 = MAX_ROLE_MEMBERS;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ProposalAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl ProposalAccount {
+    pub const MAX_SIZE: usize = 32 + 1 + 1;
+}
+
+// Instruction of this program scheduled with `schedule_proposal`. It is
+// signed by the timelock authority when executed, which therefore has to hold
+// the roles the instruction requires.
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct Proposal {
+    pub proposer: Pubkey,
+    pub accounts: Vec<ProposalAccount>,
+    pub data: Vec<u8>,
+    pub execute_after: i64,
+    // Members of `APPROVER_ROLE` who approved the proposal
+    pub approvals: Vec<Pubkey>,
+}
+
+impl Proposal {
+    pub const MAX_SIZE: usize = 32
+        + 4
+        + ProposalAccount::MAX_SIZE * MAX_PROPOSAL_ACCOUNTS as usize
+        + 4
+        + MAX_PROPOSAL_DATA_LENGTH
+        + 8
+        + 4
+        + 32 * MAX_PROPOSAL_APPROVALS as usize;
 }
 
 pub const CHAIN_ID_MAX_SIZE: usize = 41;
@@ -130,8 +543,11 @@ impl ChainId {
 #[cfg(test)]
 mod tests {
     use crate::common::types::{
-        ChainId, ChainIdConversionError, CHAIN_ID_MAX_SIZE, CHAIN_ID_MIN_SIZE,
+        BlacklistReason, Blacklisting, ChainId, ChainIdConversionError, EntityData, KeyEntry,
+        KeyQuota, KeyUsage, PolicyScope, ProgramConfig, ProgramState, Role, CHAIN_ID_MAX_SIZE,
+        CHAIN_ID_MIN_SIZE, MAX_SCOPED_POLICIES, PAUSE_CREDENTIALS, PAUSE_FEE_COLLECTION,
     };
+    use anchor_lang::{AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator};
 
     #[test]
     fn test_chain_id() {
@@ -151,4 +567,163 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_policy_scope() {
+        assert!(PolicyScope::Any.is_valid());
+        assert!(PolicyScope::Any.allows(u64::MAX));
+
+        let range = PolicyScope::Range { from: 10, to: 20 };
+        assert!(range.is_valid());
+        assert!(range.allows(10));
+        assert!(range.allows(20));
+        assert!(!range.allows(9));
+        assert!(!range.allows(21));
+        assert!(!PolicyScope::Range { from: 20, to: 10 }.is_valid());
+
+        let list = PolicyScope::List(vec![1, 5]);
+        assert!(list.is_valid());
+        assert!(list.allows(5));
+        assert!(!list.allows(2));
+        assert!(!PolicyScope::List(vec![]).is_valid());
+        assert!(!PolicyScope::List(vec![1; MAX_SCOPED_POLICIES + 1]).is_valid());
+    }
+
+    #[test]
+    fn test_legacy_key_entry() {
+        // Legacy key mappings end right after the validity
+        let mut legacy = vec![1, 1];
+        legacy.extend_from_slice(&100u64.to_le_bytes());
+        legacy.extend_from_slice(&200u64.to_le_bytes());
+        assert_eq!(legacy.len(), KeyEntry::LEGACY_SIZE);
+        let key_entry = KeyEntry::deserialize(&mut &legacy[..]).unwrap();
+        assert!(key_entry.is_valid);
+        assert_eq!(key_entry.valid_from, 100);
+        assert_eq!(key_entry.valid_to, 200);
+        assert_eq!(key_entry.policy_scope, PolicyScope::Any);
+        assert_eq!(key_entry.quota, KeyQuota::default());
+        assert_eq!(key_entry.usage, KeyUsage::default());
+
+        let key_entry = KeyEntry {
+            policy_scope: PolicyScope::List(vec![1, 5]),
+            quota: KeyQuota {
+                max_credentials: 10,
+                window_duration: 100,
+                max_credentials_per_window: 5,
+            },
+            usage: KeyUsage {
+                credentials_issued: 3,
+                window_start: 1200,
+                window_credentials_issued: 2,
+            },
+            ..key_entry
+        };
+        let data = key_entry.try_to_vec().unwrap();
+        let deserialized = KeyEntry::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(deserialized.policy_scope, key_entry.policy_scope);
+        assert_eq!(deserialized.quota, key_entry.quota);
+        assert_eq!(deserialized.usage, key_entry.usage);
+
+        // Truncated fields are still rejected
+        assert!(KeyEntry::deserialize(&mut &data[..KeyEntry::LEGACY_SIZE + 4]).is_err());
+    }
+
+    #[test]
+    fn test_key_quota() {
+        assert!(KeyQuota::default().is_valid());
+        assert_eq!(KeyQuota::default().window_start(1234), 0);
+
+        let quota = KeyQuota {
+            max_credentials: 0,
+            window_duration: 100,
+            max_credentials_per_window: 5,
+        };
+        assert!(quota.is_valid());
+        assert_eq!(quota.window_start(1234), 1200);
+        assert_eq!(quota.window_start(1200), 1200);
+
+        assert!(!KeyQuota {
+            max_credentials: 10,
+            window_duration: 100,
+            max_credentials_per_window: 0,
+        }
+        .is_valid());
+        assert!(!KeyQuota {
+            max_credentials: 10,
+            window_duration: 0,
+            max_credentials_per_window: 5,
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn test_program_config() {
+        // Program state as allocated before the config existed
+        let mut data = ProgramState::DISCRIMINATOR.to_vec();
+        data.push(1);
+        data.extend_from_slice(
+            &ChainId::new(vec![1, 2, 3, 4, 5])
+                .unwrap()
+                .try_to_vec()
+                .unwrap(),
+        );
+        data.resize(8 + 32 + 41 + 1, 0);
+        let state = ProgramState::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(state.config, ProgramConfig::default());
+        assert_eq!(state.threshold_policies, 0);
+
+        let config = ProgramConfig {
+            pause_flags: PAUSE_FEE_COLLECTION,
+            ..Default::default()
+        };
+        assert!(config.is_paused(PAUSE_FEE_COLLECTION));
+        assert!(!config.is_paused(PAUSE_CREDENTIALS));
+    }
+
+    #[test]
+    fn test_role_expiry() {
+        // Legacy role accounts only hold `has_role` followed by zero padding
+        let mut legacy: &[u8] = &[1, 0, 0, 0, 0, 0, 0, 0];
+        let role = Role::deserialize(&mut legacy).unwrap();
+        assert_eq!(role.expires_at, None);
+        assert!(role.is_active(4_000_000_000));
+
+        let role = Role {
+            has_role: true,
+            expires_at: Some(100),
+        };
+        assert!(role.is_active(99));
+        assert!(!role.is_active(100));
+        assert!(role.try_to_vec().unwrap().len() <= Role::MAX_SIZE);
+
+        let role = Role {
+            has_role: false,
+            expires_at: None,
+        };
+        assert!(!role.is_active(0));
+    }
+
+    #[test]
+    fn test_entity_blacklisting() {
+        // Legacy entity accounts end right after the expiry
+        let mut legacy: &[u8] = &[1, 1, 0, 0, 0, 0, 0, 0, 0, 0];
+        let entity = EntityData::deserialize(&mut legacy).unwrap();
+        assert_eq!(entity.blacklisting, Blacklisting::default());
+        assert!(entity.is_blacklisted(4_000_000_000));
+
+        let entity = EntityData {
+            version: 1,
+            blacklisted: true,
+            exp: 0,
+            blacklisting: Blacklisting {
+                reason: BlacklistReason::CourtOrder,
+                until: Some(100),
+            },
+        };
+        let data = entity.try_to_vec().unwrap();
+        assert_eq!(data.len(), EntityData::MAX_SIZE);
+        assert_eq!(EntityData::deserialize(&mut &data[..]).unwrap(), entity);
+        assert!(entity.is_blacklisted(99));
+        assert!(!entity.is_blacklisted(100));
+    }
 }
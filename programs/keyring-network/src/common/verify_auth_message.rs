@@ -1,41 +1,68 @@
 use crate::common::error::KeyringError;
-use crate::common::types::ChainId;
+use crate::common::typed_data::create_typed_data_payload;
+use crate::common::types::{AttestationFormat, ChainId, DEFAULT_INSTANCE, ETH_ADDRESS_LENGTH};
+use anchor_lang::prelude::Pubkey;
 use anchor_lang::solana_program::keccak;
 use anchor_lang::solana_program::keccak::Hash;
 use anchor_lang::solana_program::secp256k1_recover::{
-    secp256k1_recover, Secp256k1Pubkey, SECP256K1_PUBLIC_KEY_LENGTH, SECP256K1_SIGNATURE_LENGTH,
+    secp256k1_recover, SECP256K1_PUBLIC_KEY_LENGTH, SECP256K1_SIGNATURE_LENGTH,
 };
 use anchor_lang::{error, Result};
 
 pub const ETH_SIGNED_MESSAGE_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n32";
+// Version byte of the extended packed message, the original layout has 0 in
+// its place.
+pub const EXTENDED_AUTH_MESSAGE_VERSION: u8 = 1;
 
-pub fn split_signature(signature_data: Vec<u8>) -> Result<(Vec<u8>, u8)> {
-    if signature_data.len() != SECP256K1_SIGNATURE_LENGTH + 1 {
-        return Err(error!(KeyringError::ErrInvalidSignatureLength));
-    }
+// Half of the secp256k1 curve order, signatures with a greater s value are
+// malleable (see EIP-2).
+pub const SECP256K1_HALF_CURVE_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
 
-    let mut recovery_id = *signature_data
-        .last()
-        .expect("We already checked that the length is 65 above; qed");
+// Splits a signature into the 64 byte `r || s` and the recovery id. Accepted
+// encodings are
+// - 65 bytes `r || s || v` with `v` in 27..=30 as returned by ethereum rpc or
+//   the raw recovery id in 0..=3,
+// - 64 bytes EIP-2098 compact `r || yParity << 255 | s`.
+pub fn split_signature(signature_data: Vec<u8>) -> Result<(Vec<u8>, u8)> {
+    let (signature, recovery_id) = match signature_data.len() {
+        len if len == SECP256K1_SIGNATURE_LENGTH + 1 => {
+            let recovery_id = match signature_data[SECP256K1_SIGNATURE_LENGTH] {
+                v @ 27..=30 => v - 27,
+                v @ 0..=3 => v,
+                _ => return Err(error!(KeyringError::ErrInvalidRecoveryID)),
+            };
+            (
+                signature_data[..SECP256K1_SIGNATURE_LENGTH].to_vec(),
+                recovery_id,
+            )
+        }
+        SECP256K1_SIGNATURE_LENGTH => {
+            let mut signature = signature_data;
+            let recovery_id = signature[32] >> 7;
+            signature[32] &= 0x7f;
+            (signature, recovery_id)
+        }
+        _ => return Err(error!(KeyringError::ErrInvalidSignatureLength)),
+    };
 
-    // We expect recovery id similar to ethereum rpc
-    if recovery_id >= 27 && recovery_id < 27 + 4 {
-        recovery_id = recovery_id - 27;
-    } else {
-        return Err(error!(KeyringError::ErrInvalidRecoveryID));
+    if signature[32..] > SECP256K1_HALF_CURVE_ORDER[..] {
+        return Err(error!(KeyringError::ErrMalleableSignature));
     }
 
-    let signature = signature_data[0..64].to_vec();
-
     Ok((signature, recovery_id))
 }
 
-fn parse_publickey(key: Vec<u8>) -> Result<Secp256k1Pubkey> {
-    if key.len() != SECP256K1_PUBLIC_KEY_LENGTH {
-        return Err(error!(KeyringError::ErrInvalidPubkeyLength));
+// Ethereum address of a signing key. Keys are registered either as a 64 byte
+// uncompressed public key or directly as a 20 byte address.
+pub fn signer_address(key: &[u8]) -> Result<[u8; ETH_ADDRESS_LENGTH]> {
+    match key.len() {
+        ETH_ADDRESS_LENGTH => Ok(key.try_into().unwrap()),
+        SECP256K1_PUBLIC_KEY_LENGTH => Ok(keccak::hash(key).to_bytes()[12..].try_into().unwrap()),
+        _ => Err(error!(KeyringError::ErrInvalidPubkeyLength)),
     }
-
-    Ok(Secp256k1Pubkey::new(key.as_slice()))
 }
 
 // Verify auth message
@@ -50,7 +77,6 @@ pub fn verify_auth_message(
     backdoor: Vec<u8>,
 ) -> Result<bool> {
     // Pack auth message
-    let provided_signer = parse_publickey(key)?;
     let message_hash = create_signature_payload(
         trading_address,
         policy_id,
@@ -59,11 +85,107 @@ pub fn verify_auth_message(
         cost,
         backdoor,
     )?;
+
+    verify_signature(&key, &message_hash, signature_data)
+}
+
+// Verify that the signature over an already hashed payload was made by `key`.
+// A key registered as an address is compared against the address of the
+// recovered public key.
+pub fn verify_signature(key: &[u8], message_hash: &Hash, signature_data: Vec<u8>) -> Result<bool> {
+    if key.len() != SECP256K1_PUBLIC_KEY_LENGTH && key.len() != ETH_ADDRESS_LENGTH {
+        return Err(error!(KeyringError::ErrInvalidPubkeyLength));
+    }
+
+    let recovered_pubkey = recover_signer(message_hash, signature_data)?;
+
+    if key.len() == ETH_ADDRESS_LENGTH {
+        return Ok(signer_address(&recovered_pubkey)?.as_slice() == key);
+    }
+
+    Ok(recovered_pubkey.as_slice() == key)
+}
+
+// Recovers the uncompressed public key which signed an already hashed payload
+pub fn recover_signer(
+    message_hash: &Hash,
+    signature_data: Vec<u8>,
+) -> Result<[u8; SECP256K1_PUBLIC_KEY_LENGTH]> {
     let (signature, recovery_id) = split_signature(signature_data)?;
     let recovered_pubkey = secp256k1_recover(message_hash.as_ref(), recovery_id, &signature)
         .map_err(|_| error!(KeyringError::ErrInvalidSignature))?;
 
-    Ok(recovered_pubkey.eq(&provided_signer))
+    Ok(recovered_pubkey.to_bytes())
+}
+
+// Hash signed by attestation keys in the given format, shared by the
+// credential instructions and off-chain signers.
+#[allow(clippy::too_many_arguments)]
+pub fn create_attestation_payload(
+    format: AttestationFormat,
+    instance: &Pubkey,
+    trading_address: &Pubkey,
+    policy_id: u64,
+    chain_id: ChainId,
+    valid_until: u64,
+    cost: u64,
+    backdoor: Vec<u8>,
+) -> Result<Hash> {
+    match format {
+        AttestationFormat::Packed => Ok(hash_packed_message(
+            &pack_auth_message(
+                truncate_trading_address(trading_address),
+                policy_id,
+                chain_id,
+                valid_until,
+                cost,
+                backdoor,
+            )?,
+            instance,
+        )),
+        AttestationFormat::Eip712 => Ok(create_typed_data_payload(
+            trading_address,
+            policy_id,
+            &chain_id,
+            valid_until,
+            cost,
+            &backdoor,
+            &crate::ID,
+            instance,
+        )),
+        AttestationFormat::PackedExtended => Ok(hash_packed_message(
+            &pack_extended_auth_message(
+                truncate_trading_address(trading_address),
+                policy_id,
+                chain_id,
+                valid_until,
+                cost,
+                backdoor,
+            ),
+            instance,
+        )),
+    }
+}
+
+// Hash of a packed auth message as signed by attestation keys. Messages of
+// instances other than the default one also commit to the instance, so that a
+// credential cannot be replayed on another instance sharing the chain id and
+// keys. The default instance keeps the original payload.
+pub fn hash_packed_message(packed_message: &[u8], instance: &Pubkey) -> Hash {
+    let mut message_hash = keccak::hash(packed_message);
+    if *instance != DEFAULT_INSTANCE {
+        message_hash = keccak::hashv(&[message_hash.as_ref(), instance.as_ref()]);
+    }
+    convert_to_eth_signed_message_hash(message_hash)
+}
+
+// We are hashing and truncating the trading pubkey to last 20 bytes
+// in order to match message packing on ethereum as ethereum addresses are
+// 20 bytes.
+pub fn truncate_trading_address(trading_address: &Pubkey) -> Vec<u8> {
+    let trading_address_bytes = trading_address.to_bytes();
+    let trading_address_hash = keccak::hash(&trading_address_bytes).to_bytes();
+    trading_address_hash[..20].to_vec()
 }
 
 pub fn create_signature_payload(
@@ -89,6 +211,27 @@ pub fn create_signature_payload(
     Ok(eth_signed_message_hash)
 }
 
+pub fn create_extended_signature_payload(
+    trading_address: Vec<u8>,
+    policy_id: u64,
+    chain_id: ChainId,
+    valid_until: u64,
+    cost: u64,
+    backdoor: Vec<u8>,
+) -> Hash {
+    let packed_message = pack_extended_auth_message(
+        trading_address,
+        policy_id,
+        chain_id,
+        valid_until,
+        cost,
+        backdoor,
+    );
+
+    let message_hash = keccak::hash(packed_message.as_slice());
+    convert_to_eth_signed_message_hash(message_hash)
+}
+
 pub fn convert_to_eth_signed_message_hash(message_hash: Hash) -> Hash {
     let mut buffer = vec![];
     buffer.extend_from_slice(&ETH_SIGNED_MESSAGE_PREFIX);
@@ -102,7 +245,7 @@ pub fn convert_to_eth_signed_message_hash(message_hash: Hash) -> Hash {
 //    tradingAddress,
 //    uint8(0),
 //    uint24(policyId),
-//    uint32(validFrom),
+//    uint32(chainId),
 //    uint32(validUntil),
 //    uint160(cost),
 //    backdoor
@@ -131,16 +274,55 @@ pub fn pack_auth_message(
         return Err(error!(KeyringError::ErrAuthMessageParameterOutOfRange));
     }
     let encoded_valid_until = (valid_until as u32).to_be_bytes().to_vec();
-    let encoded_cost = (cost as u128).to_be_bytes().to_vec();
 
     packed.extend_from_slice(&trading_address.as_slice());
     packed.push(reserved_byte);
     packed.extend_from_slice(&encoded_policy_id.as_slice());
     packed.extend_from_slice(&chain_id.chain_id[0..4]);
     packed.extend_from_slice(&encoded_valid_until.as_slice());
-    packed.extend_from_slice(vec![0u8; 4].as_slice());
-    packed.extend_from_slice(&encoded_cost.as_slice());
+    packed.extend_from_slice(&encode_uint160(cost));
     packed.extend_from_slice(backdoor.as_slice());
 
     Ok(packed)
 }
+
+// Packs the extended auth message, which has no range limits on the policy id
+// and the expiry:
+// return abi.encodePacked(
+//    tradingAddress,
+//    uint8(1),
+//    uint64(policyId),
+//    uint32(chainId),
+//    uint64(validUntil),
+//    uint160(cost),
+//    backdoor
+// );
+// The version byte takes the place of the reserved byte of the original
+// layout, so a message cannot be valid in both layouts.
+pub fn pack_extended_auth_message(
+    trading_address: Vec<u8>,
+    policy_id: u64,
+    chain_id: ChainId,
+    valid_until: u64,
+    cost: u64,
+    backdoor: Vec<u8>,
+) -> Vec<u8> {
+    let mut packed = vec![];
+
+    packed.extend_from_slice(trading_address.as_slice());
+    packed.push(EXTENDED_AUTH_MESSAGE_VERSION);
+    packed.extend_from_slice(&policy_id.to_be_bytes());
+    packed.extend_from_slice(&chain_id.chain_id[0..4]);
+    packed.extend_from_slice(&valid_until.to_be_bytes());
+    packed.extend_from_slice(&encode_uint160(cost));
+    packed.extend_from_slice(backdoor.as_slice());
+
+    packed
+}
+
+// Big endian `uint160` as packed by `abi.encodePacked`
+fn encode_uint160(value: u64) -> [u8; 20] {
+    let mut encoded = [0u8; 20];
+    encoded[12..].copy_from_slice(&value.to_be_bytes());
+    encoded
+}
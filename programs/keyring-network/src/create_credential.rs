@@ -1,20 +1,74 @@
+use crate::blacklist_entity::resize_entity_mapping;
 use crate::common::error::KeyringError;
-use crate::common::types::{EntityData, KeyEntry, ProgramState, ToHash, CURRENT_VERSION};
-use crate::common::verify_auth_message::verify_auth_message;
+use crate::common::types::{
+    instance_seed, AttestationFormat, Blacklisting, EntityData, KeyEntry, KeyQuota, PolicyConfig,
+    ProgramConfig, ProgramState, ToHash, CURRENT_VERSION, DEFAULT_INSTANCE, PAUSE_CREDENTIALS,
+};
+use crate::common::verify_auth_message::{create_attestation_payload, verify_signature};
+use crate::register_key::resize_key_mapping;
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::keccak;
 use anchor_lang::{system_program, Accounts};
 
 #[event]
+#[derive(Debug, PartialEq)]
 pub struct CredentialsCreated {
-    key: Vec<u8>,
-    policy_id: u64,
-    trading_address: Pubkey,
+    pub instance: Pubkey,
+    pub key: Vec<u8>,
+    pub policy_id: u64,
+    pub trading_address: Pubkey,
+    pub signer: Pubkey,
+    pub previous_exp: u64,
+    pub valid_until: u64,
+    pub cost: u64,
+    pub backdoor: Vec<u8>,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[derive(Accounts)]
-#[instruction(key: Vec<u8>, policy_id: u64, trading_address: Pubkey)]
+#[instruction(instance: Pubkey, key: Vec<u8>, policy_id: u64, trading_address: Pubkey)]
 pub struct CreateCredential<'info> {
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"global_state".as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"_key_mapping".as_ref(), &key.to_hash().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub key_mapping: Account<'info, KeyEntry>,
+
+    /// CHECK: Threshold configuration of the policy. It only exists once a
+    /// threshold was set for the policy and is read by `policy_threshold`.
+    #[account(
+        seeds = [b"keyring_program".as_ref(), b"_policy_config".as_ref(), &policy_id.to_le_bytes(), instance_seed(&instance)],
+        bump
+    )]
+    pub policy_config: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"_entity_mapping".as_ref(), &policy_id.to_le_bytes(), &trading_address.to_bytes(), instance_seed(&instance)],
+        bump,
+        space = EntityData::space(entity_mapping)
+    )]
+    pub entity_mapping: Account<'info, EntityData>,
+    pub system_program: Program<'info, System>,
+}
+
+// Accounts of the original `create_credential`, for the default instance. The
+// key mapping was read-only, so keys with a quota, or which still have to be
+// resized, need it passed as writable to count the credential.
+#[derive(Accounts)]
+#[instruction(key: Vec<u8>, policy_id: u64, trading_address: Pubkey)]
+pub struct CreateCredentialLegacy<'info> {
     #[account(
         mut,
         seeds = [b"keyring_program".as_ref(), b"global_state".as_ref()],
@@ -35,14 +89,16 @@ pub struct CreateCredential<'info> {
         payer = signer,
         seeds = [b"keyring_program".as_ref(), b"_entity_mapping".as_ref(), &policy_id.to_le_bytes(), &trading_address.to_bytes()],
         bump,
-        space = 8 + EntityData::MAX_SIZE
+        space = EntityData::space(entity_mapping)
     )]
     pub entity_mapping: Account<'info, EntityData>,
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn do_create_credential(
     ctx: Context<CreateCredential>,
+    instance: Pubkey,
     key: Vec<u8>,
     policy_id: u64,
     trading_address: Pubkey,
@@ -50,12 +106,16 @@ pub fn do_create_credential(
     valid_until: u64,
     cost: u64,
     backdoor: Vec<u8>,
+    format: AttestationFormat,
 ) -> Result<()> {
     if cost == 0 {
         return Err(error!(KeyringError::ErrCostParameterZero));
     }
+    check_program_config(&ctx.accounts.program_state.config, valid_until, cost)?;
 
-    let chain_id = ctx.accounts.program_state.chain_id.clone();
+    if policy_threshold(&ctx.accounts.policy_config)? > 1 {
+        return Err(error!(KeyringError::ErrThresholdNotMet));
+    }
 
     // Transfer the cost to our PDA
     let cpi_context = CpiContext::new(
@@ -67,54 +127,225 @@ pub fn do_create_credential(
     );
     system_program::transfer(cpi_context, cost)?;
 
-    // We are hashing and truncating the trading pubkey to last 20 bytes
-    // in order to match message packing on ethereum as ethereum addresses are
-    // 20 bytes.
-    let trading_address_bytes = trading_address.to_bytes();
-    let trading_address_hash = keccak::hash(&trading_address_bytes).to_bytes();
-    let truncated_trading_address = trading_address_hash[..20].to_vec();
+    let message_hash = create_attestation_payload(
+        format,
+        &instance,
+        &trading_address,
+        policy_id,
+        ctx.accounts.program_state.chain_id.clone(),
+        valid_until,
+        cost,
+        backdoor.clone(),
+    )?;
+    if !verify_signature(&key, &message_hash, signature)? {
+        return Err(error!(KeyringError::ErrSignatureMismatch));
+    }
+
+    let clock: Clock = Clock::get()?;
+    let current_timestamp = clock.unix_timestamp.try_into().unwrap();
+
+    resize_key_mapping(
+        &ctx.accounts.key_mapping.to_account_info(),
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+    check_signing_key(&mut ctx.accounts.key_mapping, policy_id, current_timestamp)?;
+
+    resize_entity_mapping(
+        &ctx.accounts.entity_mapping,
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+    let previous_exp = update_entity_exp(
+        &mut ctx.accounts.entity_mapping,
+        valid_until,
+        current_timestamp,
+    )?;
 
-    if !verify_auth_message(
-        key.clone(),
+    emit!(CredentialsCreated {
+        instance,
+        key,
         policy_id,
-        truncated_trading_address,
+        trading_address,
+        signer: ctx.accounts.signer.key(),
+        previous_exp,
+        valid_until,
+        cost,
+        backdoor,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn do_create_credential_legacy<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateCredentialLegacy<'info>>,
+    key: Vec<u8>,
+    policy_id: u64,
+    trading_address: Pubkey,
+    signature: Vec<u8>,
+    valid_until: u64,
+    cost: u64,
+    backdoor: Vec<u8>,
+) -> Result<()> {
+    let legacy = ctx.accounts;
+    // The legacy accounts have no policy config, so thresholds could be
+    // bypassed once a policy has one
+    if legacy.program_state.threshold_policies > 0 {
+        return Err(error!(KeyringError::ErrLegacyInstructionUnsupported));
+    }
+    let key_info = legacy.key_mapping.to_account_info();
+    if !key_info.is_writable
+        && (legacy.key_mapping.quota != KeyQuota::default()
+            || key_info.data_len() < 8 + KeyEntry::MAX_SIZE)
+    {
+        return Err(error!(KeyringError::ErrLegacyInstructionUnsupported));
+    }
+
+    // Any account not owned by the program reads as a policy without threshold
+    let mut accounts = CreateCredential {
+        program_state: legacy.program_state.clone(),
+        signer: legacy.signer.clone(),
+        key_mapping: legacy.key_mapping.clone(),
+        policy_config: legacy.system_program.to_account_info(),
+        entity_mapping: legacy.entity_mapping.clone(),
+        system_program: legacy.system_program.clone(),
+    };
+    do_create_credential(
+        Context::new(
+            ctx.program_id,
+            &mut accounts,
+            ctx.remaining_accounts,
+            CreateCredentialBumps::default(),
+        ),
+        DEFAULT_INSTANCE,
+        key,
+        policy_id,
+        trading_address,
         signature,
-        chain_id,
         valid_until,
         cost,
         backdoor,
-    )? {
-        return Err(error!(KeyringError::ErrInvalidCredentials));
+        AttestationFormat::Packed,
+    )?;
+    if key_info.is_writable {
+        accounts.key_mapping.exit(ctx.program_id)?;
     }
+    // Written back when the legacy accounts exit
+    legacy.program_state = accounts.program_state;
+    legacy.entity_mapping = accounts.entity_mapping;
 
-    let clock: Clock = Clock::get()?;
-    let current_timestamp = clock.unix_timestamp.try_into().unwrap();
+    Ok(())
+}
+
+// Checks the credential against the global config set with `update_config`.
+pub(crate) fn check_program_config(
+    config: &ProgramConfig,
+    valid_until: u64,
+    cost: u64,
+) -> Result<()> {
+    if config.is_paused(PAUSE_CREDENTIALS) {
+        return Err(error!(KeyringError::ErrProgramPaused));
+    }
+    if cost < config.min_credential_cost {
+        return Err(error!(KeyringError::ErrCostBelowMinimum));
+    }
+    if config.max_credential_validity > 0 {
+        let current_timestamp: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+        if valid_until.saturating_sub(current_timestamp) > config.max_credential_validity {
+            return Err(error!(KeyringError::ErrCredentialValidityTooLong));
+        }
+    }
+
+    Ok(())
+}
 
-    let is_valid = ctx.accounts.key_mapping.is_valid
-        && current_timestamp >= ctx.accounts.key_mapping.valid_from
-        && current_timestamp <= ctx.accounts.key_mapping.valid_to;
-    if !is_valid {
-        return Err(error!(KeyringError::ErrInvalidCredentials));
+// Number of signatures required by the policy, the config account is only
+// initialized for policies with a threshold.
+pub(crate) fn policy_threshold(policy_config: &AccountInfo) -> Result<u8> {
+    if policy_config.owner != &crate::ID || policy_config.data_is_empty() {
+        return Ok(1);
+    }
+    let policy_config = PolicyConfig::try_deserialize(&mut &policy_config.data.borrow()[..])?;
+    Ok(policy_config.threshold.max(1))
+}
+
+// Checks that the key may sign a credential for the policy right now and
+// counts the credential against its quota.
+pub(crate) fn check_signing_key(
+    key_mapping: &mut KeyEntry,
+    policy_id: u64,
+    current_timestamp: u64,
+) -> Result<()> {
+    if !key_mapping.is_valid {
+        return Err(error!(KeyringError::ErrKeyRevoked));
     }
+    if current_timestamp < key_mapping.valid_from {
+        return Err(error!(KeyringError::ErrKeyNotYetValid));
+    }
+    if current_timestamp > key_mapping.valid_to {
+        return Err(error!(KeyringError::ErrKeyExpired));
+    }
+
+    if !key_mapping.policy_scope.allows(policy_id) {
+        return Err(error!(KeyringError::ErrPolicyNotAllowedForKey));
+    }
+
+    consume_key_quota(key_mapping, current_timestamp)
+}
 
+// Extends the credential of the entity, returning the previous expiry.
+pub(crate) fn update_entity_exp(
+    entity_mapping: &mut EntityData,
+    valid_until: u64,
+    current_timestamp: u64,
+) -> Result<u64> {
     if valid_until < current_timestamp {
-        return Err(error!(KeyringError::ErrInvalidCredentials));
+        return Err(error!(KeyringError::ErrCredentialExpired));
     }
 
-    if ctx.accounts.entity_mapping.blacklisted {
-        return Err(error!(KeyringError::ErrInvalidCredentials));
+    if entity_mapping.is_blacklisted(current_timestamp) {
+        return Err(error!(KeyringError::ErrEntityBlacklisted));
+    }
+    // Clears an expired blacklisting
+    if entity_mapping.blacklisted {
+        entity_mapping.blacklisted = false;
+        entity_mapping.blacklisting = Blacklisting::default();
     }
-    if valid_until <= ctx.accounts.entity_mapping.exp {
-        return Err(error!(KeyringError::ErrInvalidCredentials));
+    let previous_exp = entity_mapping.exp;
+    if valid_until <= previous_exp {
+        return Err(error!(KeyringError::ErrExpiryNotIncreasing));
     }
-    ctx.accounts.entity_mapping.exp = valid_until;
-    ctx.accounts.entity_mapping.version = CURRENT_VERSION;
+    entity_mapping.exp = valid_until;
+    entity_mapping.version = CURRENT_VERSION;
 
-    emit!(CredentialsCreated {
-        key,
-        policy_id,
-        trading_address
-    });
+    Ok(previous_exp)
+}
+
+// Counts the credential against the quota of the signing key.
+fn consume_key_quota(key_mapping: &mut KeyEntry, current_timestamp: u64) -> Result<()> {
+    let quota = &key_mapping.quota;
+    let usage = &mut key_mapping.usage;
+
+    if quota.max_credentials > 0 && usage.credentials_issued >= quota.max_credentials {
+        return Err(error!(KeyringError::ErrKeyQuotaExceeded));
+    }
+
+    if quota.window_duration > 0 {
+        let window_start = quota.window_start(current_timestamp);
+        if usage.window_start != window_start {
+            usage.window_start = window_start;
+            usage.window_credentials_issued = 0;
+        }
+        if usage.window_credentials_issued >= quota.max_credentials_per_window {
+            return Err(error!(KeyringError::ErrKeyRateLimitExceeded));
+        }
+        usage.window_credentials_issued += 1;
+    }
+
+    usage.credentials_issued += 1;
 
     Ok(())
 }
@@ -0,0 +1,160 @@
+use crate::blacklist_entity::resize_entity_mapping;
+use crate::common::error::KeyringError;
+use crate::common::types::{
+    instance_seed, AttestationFormat, EntityData, KeyEntry, ProgramState, ToHash,
+};
+use crate::common::verify_auth_message::{
+    create_attestation_payload, recover_signer, signer_address,
+};
+use crate::create_credential::{
+    check_program_config, check_signing_key, policy_threshold, update_entity_exp,
+    CredentialsCreated,
+};
+use crate::register_key::resize_key_mapping;
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Accounts};
+
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, policy_id: u64, trading_address: Pubkey)]
+pub struct CreateCredentialBySigner<'info> {
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"global_state".as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Key mapping of the recovered signer, registered either by its
+    /// public key or by its address. It is validated against the signer in
+    /// `do_create_credential_by_signer`.
+    #[account(mut)]
+    pub key_mapping: AccountInfo<'info>,
+
+    /// CHECK: Threshold configuration of the policy. It only exists once a
+    /// threshold was set for the policy and is read by `policy_threshold`.
+    #[account(
+        seeds = [b"keyring_program".as_ref(), b"_policy_config".as_ref(), &policy_id.to_le_bytes(), instance_seed(&instance)],
+        bump
+    )]
+    pub policy_config: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"_entity_mapping".as_ref(), &policy_id.to_le_bytes(), &trading_address.to_bytes(), instance_seed(&instance)],
+        bump,
+        space = EntityData::space(entity_mapping)
+    )]
+    pub entity_mapping: Account<'info, EntityData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn do_create_credential_by_signer(
+    ctx: Context<CreateCredentialBySigner>,
+    instance: Pubkey,
+    policy_id: u64,
+    trading_address: Pubkey,
+    signature: Vec<u8>,
+    valid_until: u64,
+    cost: u64,
+    backdoor: Vec<u8>,
+    format: AttestationFormat,
+) -> Result<()> {
+    if cost == 0 {
+        return Err(error!(KeyringError::ErrCostParameterZero));
+    }
+    check_program_config(&ctx.accounts.program_state.config, valid_until, cost)?;
+
+    if policy_threshold(&ctx.accounts.policy_config)? > 1 {
+        return Err(error!(KeyringError::ErrThresholdNotMet));
+    }
+
+    // Transfer the cost to our PDA
+    let cpi_context = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        system_program::Transfer {
+            from: ctx.accounts.signer.to_account_info(),
+            to: ctx.accounts.program_state.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_context, cost)?;
+
+    let message_hash = create_attestation_payload(
+        format,
+        &instance,
+        &trading_address,
+        policy_id,
+        ctx.accounts.program_state.chain_id.clone(),
+        valid_until,
+        cost,
+        backdoor.clone(),
+    )?;
+    let recovered_pubkey = recover_signer(&message_hash, signature)?;
+
+    // The signer may be registered by its public key or by its address
+    let key = [
+        recovered_pubkey.to_vec(),
+        signer_address(&recovered_pubkey)?.to_vec(),
+    ]
+    .into_iter()
+    .find(|key| {
+        let (key_mapping_address, _) = Pubkey::find_program_address(
+            &[
+                b"keyring_program".as_ref(),
+                b"_key_mapping".as_ref(),
+                key.to_hash().as_ref(),
+                instance_seed(&instance),
+            ],
+            &crate::ID,
+        );
+        ctx.accounts.key_mapping.key() == key_mapping_address
+    })
+    .ok_or_else(|| error!(KeyringError::ErrKeyMappingMismatch))?;
+
+    let key_mapping_info = &ctx.accounts.key_mapping;
+    if key_mapping_info.owner != &crate::ID || key_mapping_info.data_is_empty() {
+        return Err(error!(KeyringError::ErrKeyNotRegistered));
+    }
+    let mut key_mapping = KeyEntry::try_deserialize(&mut &key_mapping_info.data.borrow()[..])?;
+
+    let clock: Clock = Clock::get()?;
+    let current_timestamp = clock.unix_timestamp.try_into().unwrap();
+
+    check_signing_key(&mut key_mapping, policy_id, current_timestamp)?;
+    resize_key_mapping(
+        key_mapping_info,
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+    key_mapping.try_serialize(&mut &mut key_mapping_info.data.borrow_mut()[..])?;
+
+    resize_entity_mapping(
+        &ctx.accounts.entity_mapping,
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+    let previous_exp = update_entity_exp(
+        &mut ctx.accounts.entity_mapping,
+        valid_until,
+        current_timestamp,
+    )?;
+
+    emit!(CredentialsCreated {
+        instance,
+        key,
+        policy_id,
+        trading_address,
+        signer: ctx.accounts.signer.key(),
+        previous_exp,
+        valid_until,
+        cost,
+        backdoor,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use crate::blacklist_entity::resize_entity_mapping;
+use crate::common::error::KeyringError;
+use crate::common::types::{
+    instance_seed, AttestationFormat, EntityData, KeyEntry, ProgramState, ToHash,
+    MAX_ATTESTATION_KEYS,
+};
+use crate::common::verify_auth_message::{
+    create_attestation_payload, signer_address, verify_signature,
+};
+use crate::create_credential::{
+    check_program_config, check_signing_key, policy_threshold, update_entity_exp,
+};
+use crate::register_key::resize_key_mapping;
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Accounts};
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct ThresholdCredentialsCreated {
+    pub instance: Pubkey,
+    pub keys: Vec<Vec<u8>>,
+    pub policy_id: u64,
+    pub trading_address: Pubkey,
+    pub signer: Pubkey,
+    pub previous_exp: u64,
+    pub valid_until: u64,
+    pub cost: u64,
+    pub backdoor: Vec<u8>,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+// Key mappings of the attesting keys are passed as writable remaining
+// accounts, in the same order as the keys.
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, policy_id: u64, trading_address: Pubkey)]
+pub struct CreateThresholdCredential<'info> {
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"global_state".as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Threshold configuration of the policy, see `policy_threshold`.
+    #[account(
+        seeds = [b"keyring_program".as_ref(), b"_policy_config".as_ref(), &policy_id.to_le_bytes(), instance_seed(&instance)],
+        bump
+    )]
+    pub policy_config: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"_entity_mapping".as_ref(), &policy_id.to_le_bytes(), &trading_address.to_bytes(), instance_seed(&instance)],
+        bump,
+        space = EntityData::space(entity_mapping)
+    )]
+    pub entity_mapping: Account<'info, EntityData>,
+    pub system_program: Program<'info, System>,
+}
+
+// Creates a credential attested by several distinct registered keys signing
+// the same payload, as required by policies with a threshold.
+#[allow(clippy::too_many_arguments)]
+pub fn do_create_threshold_credential<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateThresholdCredential<'info>>,
+    instance: Pubkey,
+    policy_id: u64,
+    trading_address: Pubkey,
+    keys: Vec<Vec<u8>>,
+    signatures: Vec<Vec<u8>>,
+    valid_until: u64,
+    cost: u64,
+    backdoor: Vec<u8>,
+    format: AttestationFormat,
+) -> Result<()> {
+    if cost == 0 {
+        return Err(error!(KeyringError::ErrCostParameterZero));
+    }
+    check_program_config(&ctx.accounts.program_state.config, valid_until, cost)?;
+
+    if keys.len() != signatures.len()
+        || keys.len() != ctx.remaining_accounts.len()
+        || keys.len() > MAX_ATTESTATION_KEYS as usize
+    {
+        return Err(error!(KeyringError::ErrInvalidAttestationAccounts));
+    }
+
+    // A key may be registered both as a public key and as its address, compare
+    // the addresses so that it cannot attest twice
+    let addresses = keys
+        .iter()
+        .map(|key| signer_address(key))
+        .collect::<Result<Vec<_>>>()?;
+    for (index, address) in addresses.iter().enumerate() {
+        if addresses[..index].contains(address) {
+            return Err(error!(KeyringError::ErrDuplicateAttestationKey));
+        }
+    }
+
+    if keys.len() < policy_threshold(&ctx.accounts.policy_config)? as usize {
+        return Err(error!(KeyringError::ErrThresholdNotMet));
+    }
+
+    // Transfer the cost to our PDA
+    let cpi_context = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        system_program::Transfer {
+            from: ctx.accounts.signer.to_account_info(),
+            to: ctx.accounts.program_state.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_context, cost)?;
+
+    let message_hash = create_attestation_payload(
+        format,
+        &instance,
+        &trading_address,
+        policy_id,
+        ctx.accounts.program_state.chain_id.clone(),
+        valid_until,
+        cost,
+        backdoor.clone(),
+    )?;
+
+    let clock: Clock = Clock::get()?;
+    let current_timestamp = clock.unix_timestamp.try_into().unwrap();
+
+    for ((key, signature), key_mapping_info) in keys
+        .iter()
+        .zip(signatures)
+        .zip(ctx.remaining_accounts.iter())
+    {
+        let (key_mapping_address, _) = Pubkey::find_program_address(
+            &[
+                b"keyring_program".as_ref(),
+                b"_key_mapping".as_ref(),
+                key.to_hash().as_ref(),
+                instance_seed(&instance),
+            ],
+            &crate::ID,
+        );
+        if key_mapping_info.key() != key_mapping_address || !key_mapping_info.is_writable {
+            return Err(error!(KeyringError::ErrInvalidAttestationAccounts));
+        }
+
+        if !verify_signature(key, &message_hash, signature)? {
+            return Err(error!(KeyringError::ErrSignatureMismatch));
+        }
+
+        let mut key_mapping = Account::<KeyEntry>::try_from(key_mapping_info)?;
+        check_signing_key(&mut key_mapping, policy_id, current_timestamp)?;
+        resize_key_mapping(
+            key_mapping_info,
+            &ctx.accounts.signer,
+            &ctx.accounts.system_program,
+        )?;
+        key_mapping.exit(&crate::ID)?;
+    }
+
+    resize_entity_mapping(
+        &ctx.accounts.entity_mapping,
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+    let previous_exp = update_entity_exp(
+        &mut ctx.accounts.entity_mapping,
+        valid_until,
+        current_timestamp,
+    )?;
+
+    emit!(ThresholdCredentialsCreated {
+        instance,
+        keys,
+        policy_id,
+        trading_address,
+        signer: ctx.accounts.signer.key(),
+        previous_exp,
+        valid_until,
+        cost,
+        backdoor,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
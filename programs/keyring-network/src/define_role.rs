@@ -0,0 +1,85 @@
+use crate::common::error::KeyringError;
+use crate::common::types::{
+    instance_seed, Role, RoleDefinition, DEFAULT_ADMIN_ROLE, MAX_ROLE_NAME_LENGTH,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::Accounts;
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct RoleDefined {
+    pub instance: Pubkey,
+    pub role: [u8; 32],
+    pub name: String,
+    pub admin_role: [u8; 32],
+    pub description_hash: [u8; 32],
+    pub signer: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, role: [u8; 32])]
+pub struct DefineRole<'info> {
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + RoleDefinition::MAX_SIZE,
+        seeds = [b"keyring_program".as_ref(), b"role_definition".as_ref(), role.as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub role_definition: Account<'info, RoleDefinition>,
+    #[account(
+        seeds = [DEFAULT_ADMIN_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub default_admin_role: Account<'info, Role>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn do_define_role(
+    ctx: Context<DefineRole>,
+    instance: Pubkey,
+    role: [u8; 32],
+    name: String,
+    admin_role: [u8; 32],
+    description_hash: [u8; 32],
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx
+        .accounts
+        .default_admin_role
+        .is_active(clock.unix_timestamp)
+    {
+        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
+    }
+    // The default admin role administers itself, anything else could lock
+    // the admins out.
+    if role == DEFAULT_ADMIN_ROLE {
+        return Err(error!(KeyringError::ErrInvalidRoleDefinition));
+    }
+    if name.is_empty() || name.len() > MAX_ROLE_NAME_LENGTH {
+        return Err(error!(KeyringError::ErrInvalidRoleName));
+    }
+
+    *ctx.accounts.role_definition = RoleDefinition {
+        name: name.clone(),
+        admin_role,
+        description_hash,
+    };
+
+    emit!(RoleDefined {
+        instance,
+        role,
+        name,
+        admin_role,
+        description_hash,
+        signer: ctx.accounts.signer.key(),
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use crate::common::error::KeyringError;
+use crate::common::types::{instance_seed, ProgramState, Proposal, APPROVER_ROLE};
+use crate::manage_role::holds_role;
+use crate::program::KeyringNetwork;
+use crate::schedule_proposal::timelock_authority;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::Accounts;
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct ProposalExecuted {
+    pub instance: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub signer: Pubkey,
+    pub approvals: u8,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+// Accounts of the proposed instruction are passed as remaining accounts, in
+// the same order as in the proposal, followed by the approver role account of
+// each approval, in the order of the approvals. Approvals are only counted
+// while the approver still holds an active approver role.
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, proposal_id: u64)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"keyring_program".as_ref(), b"proposal".as_ref(), &proposal_id.to_le_bytes(), instance_seed(&instance)],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: Receives the rent of the proposal back.
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: AccountInfo<'info>,
+    #[account(
+        seeds = [b"keyring_program".as_ref(), b"global_state".as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub signer: Signer<'info>,
+    // Callee of the proposed instruction
+    pub keyring_program: Program<'info, KeyringNetwork>,
+}
+
+pub fn do_execute_proposal<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+    instance: Pubkey,
+    proposal_id: u64,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    let proposal = &ctx.accounts.proposal;
+    if clock.unix_timestamp < proposal.execute_after {
+        return Err(error!(KeyringError::ErrProposalNotReady));
+    }
+    if ctx.remaining_accounts.len() < proposal.accounts.len() {
+        return Err(error!(KeyringError::ErrProposalAccountMismatch));
+    }
+    let (instruction_accounts, approver_roles) =
+        ctx.remaining_accounts.split_at(proposal.accounts.len());
+    if approver_roles.len() > proposal.approvals.len() {
+        return Err(error!(KeyringError::ErrProposalAccountMismatch));
+    }
+    let mut approvals: u8 = 0;
+    for (approver_role, approver) in approver_roles.iter().zip(proposal.approvals.iter()) {
+        if holds_role(
+            approver_role,
+            &APPROVER_ROLE,
+            approver,
+            &instance,
+            clock.unix_timestamp,
+        )? {
+            approvals += 1;
+        }
+    }
+    if approvals < ctx.accounts.program_state.config.approval_threshold {
+        return Err(error!(KeyringError::ErrProposalNotApproved));
+    }
+
+    if instruction_accounts
+        .iter()
+        .zip(proposal.accounts.iter())
+        .any(|(account_info, account)| account_info.key() != account.pubkey)
+    {
+        return Err(error!(KeyringError::ErrProposalAccountMismatch));
+    }
+
+    let instruction = Instruction {
+        program_id: crate::ID,
+        accounts: proposal
+            .accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: proposal.data.clone(),
+    };
+    let (_, bump) = timelock_authority(&instance);
+    invoke_signed(
+        &instruction,
+        instruction_accounts,
+        &[&[
+            b"keyring_program".as_ref(),
+            b"timelock".as_ref(),
+            instance_seed(&instance),
+            &[bump],
+        ]],
+    )?;
+
+    emit!(ProposalExecuted {
+        instance,
+        proposal_id,
+        proposer: proposal.proposer,
+        signer: ctx.accounts.signer.key(),
+        approvals,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
@@ -1,18 +1,76 @@
 use crate::common::error::KeyringError;
 use crate::common::types::{
-    ChainId, KeyRegistry, ProgramState, Role, CURRENT_VERSION, DEFAULT_ADMIN_ROLE,
+    instance_seed, ChainId, KeyRegistry, ProgramConfig, ProgramState, Role, RoleMembers,
+    CURRENT_VERSION, DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE,
 };
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
 use anchor_lang::Accounts;
 
 #[event]
+#[derive(Debug, PartialEq)]
 pub struct Initialized {
-    admin: Pubkey,
-    chain_id: ChainId,
+    pub instance: Pubkey,
+    pub admin: Pubkey,
+    pub chain_id: ChainId,
+    pub version: u8,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[derive(Accounts)]
+#[instruction(instance: Pubkey)]
 pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"global_state".as_ref(), instance_seed(&instance)],
+        bump,
+        space = 8 + ProgramState::MAX_SIZE
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"active_keys".as_ref(), instance_seed(&instance)],
+        bump,
+        space = 8 + KeyRegistry::MAX_SIZE
+    )]
+    pub key_registry: Account<'info, KeyRegistry>,
+    #[account(
+        init,
+        payer = signer,
+        seeds = [DEFAULT_ADMIN_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump,
+        space = 8 + Role::MAX_SIZE
+    )]
+    pub default_admin_role: Account<'info, Role>,
+    #[account(
+        init,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"role_members".as_ref(), DEFAULT_ADMIN_ROLE.as_ref(), instance_seed(&instance)],
+        bump,
+        space = 8 + RoleMembers::MAX_SIZE
+    )]
+    pub default_admin_role_members: Account<'info, RoleMembers>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    // Only optional with the `skip-upgrade-authority-check` feature, for
+    // programs deployed without the upgradeable loader
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: Option<Account<'info, ProgramData>>,
+    pub system_program: Program<'info, System>,
+}
+
+// Accounts of the original `initialize`, for the default instance. The upgrade
+// authority check needs the program data account and the member index of the
+// default admin role is only created if passed, so both can be appended.
+#[derive(Accounts)]
+pub struct InitializeLegacy<'info> {
     #[account(
         init,
         payer = signer,
@@ -40,20 +98,91 @@ pub struct Initialize<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
     pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: Option<Account<'info, ProgramData>>,
+    #[account(
+        init,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"role_members".as_ref(), DEFAULT_ADMIN_ROLE.as_ref()],
+        bump,
+        space = 8 + RoleMembers::MAX_SIZE
+    )]
+    pub default_admin_role_members: Option<Account<'info, RoleMembers>>,
+}
+
+pub fn do_initialize(ctx: Context<Initialize>, instance: Pubkey, chain_id: Vec<u8>) -> Result<()> {
+    let accounts = ctx.accounts;
+    initialize_instance(
+        &mut accounts.program_state,
+        &mut accounts.default_admin_role,
+        Some(&mut accounts.default_admin_role_members),
+        &accounts.signer,
+        accounts.program_data.as_ref(),
+        instance,
+        chain_id,
+    )
+}
+
+pub fn do_initialize_legacy(ctx: Context<InitializeLegacy>, chain_id: Vec<u8>) -> Result<()> {
+    let accounts = ctx.accounts;
+    initialize_instance(
+        &mut accounts.program_state,
+        &mut accounts.default_admin_role,
+        accounts.default_admin_role_members.as_mut(),
+        &accounts.signer,
+        accounts.program_data.as_ref(),
+        DEFAULT_INSTANCE,
+        chain_id,
+    )
 }
 
-pub fn do_initialize(ctx: Context<Initialize>, chain_id: Vec<u8>) -> Result<()> {
+#[cfg_attr(feature = "skip-upgrade-authority-check", allow(unused_variables))]
+fn initialize_instance(
+    program_state: &mut ProgramState,
+    default_admin_role: &mut Role,
+    default_admin_role_members: Option<&mut Account<RoleMembers>>,
+    signer: &Signer,
+    program_data: Option<&Account<ProgramData>>,
+    instance: Pubkey,
+    chain_id: Vec<u8>,
+) -> Result<()> {
+    // Whoever initializes becomes the default admin, so only the upgrade
+    // authority may do it. Otherwise the deployment could be front-run between
+    // `anchor deploy` and `anchor migrate`. Immutable deployments have no
+    // upgrade authority and have to be built with the
+    // `skip-upgrade-authority-check` feature before they are deployed.
+    #[cfg(not(feature = "skip-upgrade-authority-check"))]
+    if program_data.and_then(|program_data| program_data.upgrade_authority_address)
+        != Some(signer.key())
+    {
+        return Err(error!(KeyringError::ErrNotUpgradeAuthority));
+    }
+
     let chain_id = ChainId::new(chain_id).map_err(|_| KeyringError::ErrInvalidChainId)?;
 
-    *ctx.accounts.program_state = ProgramState {
+    *program_state = ProgramState {
         version: CURRENT_VERSION,
         chain_id: chain_id.clone(),
+        config: ProgramConfig::default(),
+        threshold_policies: 0,
     };
-    ctx.accounts.default_admin_role.has_role = true;
+    default_admin_role.has_role = true;
+    if let Some(default_admin_role_members) = default_admin_role_members {
+        default_admin_role_members.members.push(signer.key());
+    }
 
+    let clock: Clock = Clock::get()?;
     emit!(Initialized {
-        admin: ctx.accounts.signer.key.clone(),
-        chain_id: chain_id.clone(),
+        instance,
+        admin: signer.key(),
+        chain_id,
+        version: CURRENT_VERSION,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
     });
     Ok(())
 }
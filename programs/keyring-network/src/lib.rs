@@ -1,26 +1,84 @@
+mod approve_proposal;
+mod batch_blacklist_entities;
+mod batch_unblacklist_entities;
 mod blacklist_entity;
+mod cancel_proposal;
 mod check_credentials;
 mod collect_fees;
 pub mod common;
 mod create_credential;
+mod create_credential_by_signer;
+mod create_threshold_credential;
+mod define_role;
+mod execute_proposal;
 mod init;
 mod manage_role;
 mod register_key;
 mod revoke_key;
+mod rotate_key;
+mod schedule_proposal;
+mod set_policy_threshold;
 mod unblacklist_entity;
+mod update_config;
+mod update_key_policy_scope;
+mod update_key_quota;
+mod update_key_validity;
 
 use anchor_lang::prelude::*;
+use approve_proposal::*;
+use batch_blacklist_entities::*;
+use batch_unblacklist_entities::*;
 use blacklist_entity::*;
+use cancel_proposal::*;
 use check_credentials::*;
 use collect_fees::*;
+use common::types::{
+    AttestationFormat, BlacklistEntry, BlacklistReason, KeyQuota, PolicyScope, ProgramConfig,
+    ProposalAccount,
+};
 use create_credential::*;
+use create_credential_by_signer::*;
+use create_threshold_credential::*;
+use define_role::*;
+use execute_proposal::*;
 use init::*;
 use manage_role::*;
 use register_key::*;
 use revoke_key::*;
+use rotate_key::*;
+use schedule_proposal::*;
+use set_policy_threshold::*;
 #[cfg(not(feature = "no-entrypoint"))]
 use solana_security_txt::security_txt;
 use unblacklist_entity::*;
+use update_config::*;
+use update_key_policy_scope::*;
+use update_key_quota::*;
+use update_key_validity::*;
+
+pub mod events {
+    pub use crate::approve_proposal::ProposalApproved;
+    pub use crate::blacklist_entity::BlacklistedEntity;
+    pub use crate::cancel_proposal::ProposalCancelled;
+    pub use crate::check_credentials::ValidCredentials;
+    pub use crate::collect_fees::FeesCollected;
+    pub use crate::create_credential::CredentialsCreated;
+    pub use crate::create_threshold_credential::ThresholdCredentialsCreated;
+    pub use crate::define_role::RoleDefined;
+    pub use crate::execute_proposal::ProposalExecuted;
+    pub use crate::init::Initialized;
+    pub use crate::manage_role::RoleManaged;
+    pub use crate::register_key::KeyRegistered;
+    pub use crate::revoke_key::KeyRevoked;
+    pub use crate::rotate_key::KeyRotated;
+    pub use crate::schedule_proposal::ProposalScheduled;
+    pub use crate::set_policy_threshold::PolicyThresholdUpdated;
+    pub use crate::unblacklist_entity::UnBlackListedEntity;
+    pub use crate::update_config::ConfigUpdated;
+    pub use crate::update_key_policy_scope::KeyPolicyScopeUpdated;
+    pub use crate::update_key_quota::KeyQuotaUpdated;
+    pub use crate::update_key_validity::KeyValidityUpdated;
+}
 
 #[cfg(not(feature = "no-entrypoint"))]
 security_txt! {
@@ -39,54 +97,221 @@ declare_id!("3MxhjuscSykCxXaozceBAUySdp6qHieB7sTXdarYAXTp");
 pub mod keyring_network {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, chain_id: Vec<u8>) -> Result<()> {
-        do_initialize(ctx, chain_id)
+    pub fn initialize(ctx: Context<InitializeLegacy>, chain_id: Vec<u8>) -> Result<()> {
+        do_initialize_legacy(ctx, chain_id)
+    }
+
+    pub fn initialize_v2(
+        ctx: Context<Initialize>,
+        instance: Pubkey,
+        chain_id: Vec<u8>,
+    ) -> Result<()> {
+        do_initialize(ctx, instance, chain_id)
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        instance: Pubkey,
+        chain_id: Vec<u8>,
+        config: ProgramConfig,
+    ) -> Result<()> {
+        do_update_config(ctx, instance, chain_id, config)
     }
 
     pub fn manage_roles(
+        ctx: Context<ManageRoleLegacy>,
+        role: [u8; 32],
+        user: Pubkey,
+        has_role: bool,
+    ) -> Result<()> {
+        do_manage_role_legacy(ctx, role, user, has_role)
+    }
+
+    pub fn manage_roles_v2(
         ctx: Context<ManageRole>,
+        instance: Pubkey,
         role: [u8; 32],
         user: Pubkey,
         has_role: bool,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        do_manage_role(ctx, instance, role, user, has_role, expires_at)
+    }
+
+    pub fn define_role(
+        ctx: Context<DefineRole>,
+        instance: Pubkey,
+        role: [u8; 32],
+        name: String,
+        admin_role: [u8; 32],
+        description_hash: [u8; 32],
     ) -> Result<()> {
-        do_manage_role(ctx, role, user, has_role)
+        do_define_role(ctx, instance, role, name, admin_role, description_hash)
     }
 
-    pub fn register_key(
+    pub fn register_key<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterKeyLegacy<'info>>,
+        key: Vec<u8>,
+        valid_from: u64,
+        valid_to: u64,
+    ) -> Result<()> {
+        do_register_key_legacy(ctx, key, valid_from, valid_to)
+    }
+
+    pub fn register_key_v2(
         ctx: Context<RegisterKey>,
+        instance: Pubkey,
+        key: Vec<u8>,
+        valid_from: u64,
+        valid_to: u64,
+    ) -> Result<()> {
+        do_register_key(ctx, instance, key, valid_from, valid_to)
+    }
+
+    pub fn revoke_key<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevokeKeyLegacy<'info>>,
+        key: Vec<u8>,
+    ) -> Result<()> {
+        do_revoke_key_legacy(ctx, key)
+    }
+
+    pub fn revoke_key_v2(ctx: Context<RevokeKey>, instance: Pubkey, key: Vec<u8>) -> Result<()> {
+        do_revoke_key(ctx, instance, key)
+    }
+
+    pub fn rotate_key(
+        ctx: Context<RotateKey>,
+        instance: Pubkey,
+        old_key: Vec<u8>,
+        new_key: Vec<u8>,
+        valid_from: u64,
+        valid_to: u64,
+        overlap: u64,
+    ) -> Result<()> {
+        do_rotate_key(
+            ctx, instance, old_key, new_key, valid_from, valid_to, overlap,
+        )
+    }
+
+    pub fn update_key_validity(
+        ctx: Context<UpdateKeyValidity>,
+        instance: Pubkey,
         key: Vec<u8>,
         valid_from: u64,
         valid_to: u64,
     ) -> Result<()> {
-        do_register_key(ctx, key, valid_from, valid_to)
+        do_update_key_validity(ctx, instance, key, valid_from, valid_to)
+    }
+
+    pub fn update_key_policy_scope(
+        ctx: Context<UpdateKeyPolicyScope>,
+        instance: Pubkey,
+        key: Vec<u8>,
+        policy_scope: PolicyScope,
+    ) -> Result<()> {
+        do_update_key_policy_scope(ctx, instance, key, policy_scope)
     }
 
-    pub fn revoke_key(ctx: Context<RevokeKey>, key: Vec<u8>) -> Result<()> {
-        do_revoke_key(ctx, key)
+    pub fn update_key_quota(
+        ctx: Context<UpdateKeyQuota>,
+        instance: Pubkey,
+        key: Vec<u8>,
+        quota: KeyQuota,
+    ) -> Result<()> {
+        do_update_key_quota(ctx, instance, key, quota)
+    }
+
+    pub fn blacklist_entity<'info>(
+        ctx: Context<'_, '_, '_, 'info, BlacklistEntityLegacy<'info>>,
+        policy_id: u64,
+        trading_address: Pubkey,
+    ) -> Result<()> {
+        do_blacklist_entity_legacy(ctx, policy_id, trading_address)
     }
 
-    pub fn blacklist_entity(
+    pub fn blacklist_entity_v2(
         ctx: Context<BlacklistEntity>,
+        instance: Pubkey,
         policy_id: u64,
         trading_address: Pubkey,
+        reason: BlacklistReason,
+        until: Option<u64>,
     ) -> Result<()> {
-        do_blacklist_entity(ctx, policy_id, trading_address)
+        do_blacklist_entity(ctx, instance, policy_id, trading_address, reason, until)
     }
 
-    pub fn unblacklist_entity(
+    pub fn unblacklist_entity<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnblacklistEntityLegacy<'info>>,
+        policy_id: u64,
+        trading_address: Pubkey,
+    ) -> Result<()> {
+        do_unblacklist_entity_legacy(ctx, policy_id, trading_address)
+    }
+
+    pub fn unblacklist_entity_v2(
         ctx: Context<UnblacklistEntity>,
+        instance: Pubkey,
         policy_id: u64,
         trading_address: Pubkey,
     ) -> Result<()> {
-        do_unblacklist_entity(ctx, policy_id, trading_address)
+        do_unblacklist_entity(ctx, instance, policy_id, trading_address)
     }
 
-    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
-        do_collect_fees(ctx)
+    pub fn batch_blacklist_entities<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchBlacklistEntities<'info>>,
+        instance: Pubkey,
+        entries: Vec<BlacklistEntry>,
+        reason: BlacklistReason,
+        until: Option<u64>,
+    ) -> Result<()> {
+        do_batch_blacklist_entities(ctx, instance, entries, reason, until)
+    }
+
+    pub fn batch_unblacklist_entities<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchUnblacklistEntities<'info>>,
+        instance: Pubkey,
+        entries: Vec<BlacklistEntry>,
+    ) -> Result<()> {
+        do_batch_unblacklist_entities(ctx, instance, entries)
     }
 
-    pub fn create_credential(
+    pub fn collect_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, CollectFeesLegacy<'info>>,
+    ) -> Result<()> {
+        do_collect_fees_legacy(ctx)
+    }
+
+    pub fn collect_fees_v2(ctx: Context<CollectFees>, instance: Pubkey) -> Result<()> {
+        do_collect_fees(ctx, instance)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_credential<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateCredentialLegacy<'info>>,
+        key: Vec<u8>,
+        policy_id: u64,
+        trading_address: Pubkey,
+        signature: Vec<u8>,
+        valid_until: u64,
+        cost: u64,
+        backdoor: Vec<u8>,
+    ) -> Result<()> {
+        do_create_credential_legacy(
+            ctx,
+            key,
+            policy_id,
+            trading_address,
+            signature,
+            valid_until,
+            cost,
+            backdoor,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_credential_v2(
         ctx: Context<CreateCredential>,
+        instance: Pubkey,
         key: Vec<u8>,
         policy_id: u64,
         trading_address: Pubkey,
@@ -94,9 +319,11 @@ pub mod keyring_network {
         valid_until: u64,
         cost: u64,
         backdoor: Vec<u8>,
+        format: AttestationFormat,
     ) -> Result<()> {
         do_create_credential(
             ctx,
+            instance,
             key,
             policy_id,
             trading_address,
@@ -104,14 +331,119 @@ pub mod keyring_network {
             valid_until,
             cost,
             backdoor,
+            format,
         )
     }
 
-    pub fn check_credential(
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_credential_by_signer(
+        ctx: Context<CreateCredentialBySigner>,
+        instance: Pubkey,
+        policy_id: u64,
+        trading_address: Pubkey,
+        signature: Vec<u8>,
+        valid_until: u64,
+        cost: u64,
+        backdoor: Vec<u8>,
+        format: AttestationFormat,
+    ) -> Result<()> {
+        do_create_credential_by_signer(
+            ctx,
+            instance,
+            policy_id,
+            trading_address,
+            signature,
+            valid_until,
+            cost,
+            backdoor,
+            format,
+        )
+    }
+
+    pub fn set_policy_threshold(
+        ctx: Context<SetPolicyThreshold>,
+        instance: Pubkey,
+        policy_id: u64,
+        threshold: u8,
+    ) -> Result<()> {
+        do_set_policy_threshold(ctx, instance, policy_id, threshold)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_threshold_credential<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateThresholdCredential<'info>>,
+        instance: Pubkey,
+        policy_id: u64,
+        trading_address: Pubkey,
+        keys: Vec<Vec<u8>>,
+        signatures: Vec<Vec<u8>>,
+        valid_until: u64,
+        cost: u64,
+        backdoor: Vec<u8>,
+        format: AttestationFormat,
+    ) -> Result<()> {
+        do_create_threshold_credential(
+            ctx,
+            instance,
+            policy_id,
+            trading_address,
+            keys,
+            signatures,
+            valid_until,
+            cost,
+            backdoor,
+            format,
+        )
+    }
+
+    pub fn check_credential<'info>(
+        ctx: Context<'_, '_, '_, 'info, CheckCredentialLegacy<'info>>,
+        policy_id: u64,
+        trading_address: Pubkey,
+    ) -> Result<()> {
+        do_check_credential_legacy(ctx, policy_id, trading_address)
+    }
+
+    pub fn check_credential_v2(
         ctx: Context<CheckCredential>,
+        instance: Pubkey,
         policy_id: u64,
         trading_address: Pubkey,
     ) -> Result<()> {
-        do_check_credential(ctx, policy_id, trading_address)
+        do_check_credential(ctx, instance, policy_id, trading_address)
+    }
+
+    pub fn schedule_proposal(
+        ctx: Context<ScheduleProposal>,
+        instance: Pubkey,
+        proposal_id: u64,
+        accounts: Vec<ProposalAccount>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        do_schedule_proposal(ctx, instance, proposal_id, accounts, data)
+    }
+
+    pub fn cancel_proposal(
+        ctx: Context<CancelProposal>,
+        instance: Pubkey,
+        proposal_id: u64,
+    ) -> Result<()> {
+        do_cancel_proposal(ctx, instance, proposal_id)
+    }
+
+    pub fn approve_proposal(
+        ctx: Context<ApproveProposal>,
+        instance: Pubkey,
+        proposal_id: u64,
+    ) -> Result<()> {
+        do_approve_proposal(ctx, instance, proposal_id)
+    }
+
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+        instance: Pubkey,
+        proposal_id: u64,
+    ) -> Result<()> {
+        do_execute_proposal(ctx, instance, proposal_id)
     }
 }
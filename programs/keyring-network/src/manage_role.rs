@@ -1,27 +1,76 @@
 use crate::common::error::KeyringError;
+use crate::common::types::instance_seed;
 use crate::common::types::Role;
+use crate::common::types::RoleDefinition;
+use crate::common::types::RoleMembers;
+use crate::common::types::BUILTIN_ROLES;
 use crate::common::types::DEFAULT_ADMIN_ROLE;
+use crate::common::types::DEFAULT_INSTANCE;
+use crate::common::types::MAX_ROLE_MEMBERS;
 use anchor_lang::prelude::*;
-use anchor_lang::Accounts;
+use anchor_lang::{system_program, Accounts};
 
 #[event]
+#[derive(Debug, PartialEq)]
 pub struct RoleManaged {
-    role: [u8; 32],
-    user: Pubkey,
-    has_role: bool,
+    pub instance: Pubkey,
+    pub role: [u8; 32],
+    pub user: Pubkey,
+    pub signer: Pubkey,
+    pub previous_has_role: bool,
+    pub has_role: bool,
+    pub previous_expires_at: Option<i64>,
+    pub expires_at: Option<i64>,
+    // Number of indexed members of the role after the change, zero if the
+    // legacy `manage_roles` was not given the index
+    pub members: u8,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[derive(Accounts)]
-#[instruction(role_identifier: [u8; 32], user: Pubkey, has_role: bool)]
+#[instruction(instance: Pubkey, role_identifier: [u8; 32], user: Pubkey, has_role: bool, expires_at: Option<i64>)]
 pub struct ManageRole<'info> {
+    /// CHECK: Definition of the role. It only exists for roles defined with
+    /// `define_role` and is read by `role_admin`.
+    #[account(
+        seeds = [b"keyring_program".as_ref(), b"role_definition".as_ref(), role_identifier.as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub role_definition: AccountInfo<'info>,
+    /// CHECK: Role account of the signer for the admin role of the role,
+    /// which depends on the definition and is checked by `holds_role`.
+    pub admin_role: AccountInfo<'info>,
     #[account(
         init_if_needed,
         payer = signer,
         space = 8 + Role::MAX_SIZE,
-        seeds = [DEFAULT_ADMIN_ROLE.as_ref(), signer.key().to_bytes().as_ref()],
+        seeds = [role_identifier.as_ref(), user.to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    role: Account<'info, Role>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + RoleMembers::MAX_SIZE,
+        seeds = [b"keyring_program".as_ref(), b"role_members".as_ref(), role_identifier.as_ref(), instance_seed(&instance)],
         bump
     )]
-    pub default_admin_role: Account<'info, Role>,
+    pub role_members: Account<'info, RoleMembers>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Accounts of the original `manage_roles`, for the default instance. Every role
+// is administered by the default admin role, which can define any other admin
+// role anyway. The member index of the role is only updated if it is appended.
+#[derive(Accounts)]
+#[instruction(role_identifier: [u8; 32], user: Pubkey)]
+pub struct ManageRoleLegacy<'info> {
+    /// CHECK: Role account of the signer for the default admin role, checked
+    /// by `holds_role`.
+    pub default_admin_role: AccountInfo<'info>,
     #[account(
         init_if_needed,
         payer = signer,
@@ -33,25 +82,259 @@ pub struct ManageRole<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
     pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"role_members".as_ref(), role_identifier.as_ref()],
+        bump
+    )]
+    pub role_members: Option<Account<'info, RoleMembers>>,
 }
 
 pub fn do_manage_role(
     ctx: Context<ManageRole>,
+    instance: Pubkey,
+    role_identifier: [u8; 32],
+    user: Pubkey,
+    has_role: bool,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    let admin_role = role_admin(&role_identifier, &ctx.accounts.role_definition)?;
+    let accounts = ctx.accounts;
+    update_role(
+        &mut accounts.role,
+        Some(&mut accounts.role_members),
+        &accounts.signer,
+        &accounts.admin_role,
+        &accounts.system_program,
+        ctx.remaining_accounts,
+        instance,
+        admin_role,
+        role_identifier,
+        user,
+        has_role,
+        expires_at,
+    )
+}
+
+pub fn do_manage_role_legacy(
+    ctx: Context<ManageRoleLegacy>,
+    role_identifier: [u8; 32],
+    user: Pubkey,
+    has_role: bool,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    update_role(
+        &mut accounts.role,
+        accounts.role_members.as_mut(),
+        &accounts.signer,
+        &accounts.default_admin_role,
+        &accounts.system_program,
+        ctx.remaining_accounts,
+        DEFAULT_INSTANCE,
+        DEFAULT_ADMIN_ROLE,
+        role_identifier,
+        user,
+        has_role,
+        None,
+    )
+}
+
+// Grants `role_identifier` to `user`, or revokes it, if the signer holds
+// `admin_role` or renounces its own role
+#[allow(clippy::too_many_arguments)]
+fn update_role<'info>(
+    role: &mut Account<'info, Role>,
+    role_members: Option<&mut Account<'info, RoleMembers>>,
+    signer: &Signer<'info>,
+    signer_role: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    remaining_accounts: &[AccountInfo],
+    instance: Pubkey,
+    admin_role: [u8; 32],
     role_identifier: [u8; 32],
     user: Pubkey,
     has_role: bool,
+    expires_at: Option<i64>,
 ) -> Result<()> {
-    if !ctx.accounts.default_admin_role.has_role && has_role == true {
+    let clock: Clock = Clock::get()?;
+    // Grants and revocations need the admin role of the role, except for
+    // members renouncing their own role
+    let renounce = !has_role && user == signer.key();
+    if !renounce
+        && !holds_role(
+            signer_role,
+            &admin_role,
+            &signer.key(),
+            &instance,
+            clock.unix_timestamp,
+        )?
+    {
         return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
     }
+    if expires_at.is_some_and(|expires_at| expires_at <= clock.unix_timestamp) {
+        return Err(error!(KeyringError::ErrInvalidRoleExpiry));
+    }
 
-    ctx.accounts.role.has_role = has_role;
+    // An expiry is meaningless without the role
+    let expires_at = if has_role { expires_at } else { None };
+    let previous_has_role = role.has_role;
+    let previous_expires_at = role.expires_at;
+    role.has_role = has_role;
+    role.expires_at = expires_at;
+
+    let mut no_members = vec![];
+    let members = match role_members {
+        Some(role_members) => &mut role_members.members,
+        None => &mut no_members,
+    };
+    match (has_role, members.iter().position(|member| *member == user)) {
+        (true, None) => {
+            if members.len() + 1 > MAX_ROLE_MEMBERS as usize {
+                return Err(error!(KeyringError::ErrBreachedMaxRoleMemberLimit));
+            }
+            members.push(user);
+        }
+        (false, Some(position)) => {
+            members.remove(position);
+        }
+        _ => {}
+    }
+    // Revoking an admin or letting its grant expire must leave a permanent one
+    if role_identifier == DEFAULT_ADMIN_ROLE
+        && ((previous_has_role && !has_role) || expires_at.is_some())
+        && !has_other_admin(
+            &user,
+            &signer.key(),
+            signer_role,
+            members,
+            remaining_accounts,
+            &instance,
+            clock.unix_timestamp,
+        )?
+    {
+        return Err(error!(KeyringError::ErrLastAdmin));
+    }
+    let member_count = members.len() as u8;
+
+    // Role accounts created before grants could expire only have room for
+    // grants without an expiry
+    let role_info = role.to_account_info();
+    if expires_at.is_some() && role_info.data_len() < 8 + Role::MAX_SIZE {
+        let rent = Rent::get()?
+            .minimum_balance(8 + Role::MAX_SIZE)
+            .saturating_sub(role_info.lamports());
+        if rent != 0 {
+            let cpi_context = CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: signer.to_account_info(),
+                    to: role_info.clone(),
+                },
+            );
+            system_program::transfer(cpi_context, rent)?;
+        }
+        role_info.realloc(8 + Role::MAX_SIZE, false)?;
+    }
 
     emit!(RoleManaged {
+        instance,
         role: role_identifier,
         user,
-        has_role
+        signer: signer.key(),
+        previous_has_role,
+        has_role,
+        previous_expires_at,
+        expires_at,
+        members: member_count,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
     });
 
     Ok(())
 }
+
+// Role whose members may grant the role. Built-in roles without a definition
+// are administered by the default admin role, other roles must be defined.
+pub(crate) fn role_admin(role: &[u8; 32], role_definition: &AccountInfo) -> Result<[u8; 32]> {
+    if role_definition.owner != &crate::ID || role_definition.data_is_empty() {
+        if BUILTIN_ROLES.contains(role) {
+            return Ok(DEFAULT_ADMIN_ROLE);
+        }
+        return Err(error!(KeyringError::ErrRoleNotDefined));
+    }
+    let role_definition = RoleDefinition::try_deserialize(&mut &role_definition.data.borrow()[..])?;
+    Ok(role_definition.admin_role)
+}
+
+// Whether a permanent default admin other than `user` remains. The signer
+// counts if it is such an admin, other admins have to be indexed members whose
+// role accounts are passed as `remaining_accounts`. The address of each member
+// is derived once and matched against the accounts by key.
+fn has_other_admin(
+    user: &Pubkey,
+    signer: &Pubkey,
+    signer_role: &AccountInfo,
+    members: &[Pubkey],
+    remaining_accounts: &[AccountInfo],
+    instance: &Pubkey,
+    now: i64,
+) -> Result<bool> {
+    let is_permanent_admin = |role_account: &AccountInfo| -> Result<bool> {
+        Ok(read_role(role_account)?.is_some_and(|role| role.is_active(now) && role.is_permanent()))
+    };
+    if signer != user
+        && signer_role.key() == role_address(&DEFAULT_ADMIN_ROLE, signer, instance)
+        && is_permanent_admin(signer_role)?
+    {
+        return Ok(true);
+    }
+    if remaining_accounts.is_empty() {
+        return Ok(false);
+    }
+    for member in members.iter().filter(|member| *member != user) {
+        let address = role_address(&DEFAULT_ADMIN_ROLE, member, instance);
+        if let Some(role_account) = remaining_accounts
+            .iter()
+            .find(|account| account.key() == address)
+        {
+            if is_permanent_admin(role_account)? {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+// Address of the account of the role for the user
+fn role_address(role: &[u8; 32], user: &Pubkey, instance: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[role.as_ref(), user.as_ref(), instance_seed(instance)],
+        &crate::ID,
+    )
+    .0
+}
+
+// Role stored in `role_account`, if it is an initialized account of the program
+fn read_role(role_account: &AccountInfo) -> Result<Option<Role>> {
+    if role_account.owner != &crate::ID || role_account.data_is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Role::try_deserialize(
+        &mut &role_account.data.borrow()[..],
+    )?))
+}
+
+// Whether `role_account` is the account of the role for the user and the
+// grant is honored at `now`
+pub(crate) fn holds_role(
+    role_account: &AccountInfo,
+    role: &[u8; 32],
+    user: &Pubkey,
+    instance: &Pubkey,
+    now: i64,
+) -> Result<bool> {
+    if role_account.key() != role_address(role, user, instance) {
+        return Ok(false);
+    }
+    Ok(read_role(role_account)?.is_some_and(|role| role.is_active(now)))
+}
@@ -1,21 +1,57 @@
 use crate::common::error::KeyringError;
 use crate::common::types::{
-    KeyEntry, KeyRegistry, Role, ToHash, CURRENT_VERSION, KEY_MANAGER_ROLE, MAX_ACTIVE_KEYS,
+    instance_seed, KeyEntry, KeyRegistry, KeyUsage, Role, ToHash, CURRENT_VERSION,
+    DEFAULT_INSTANCE, ETH_ADDRESS_LENGTH, KEY_MANAGER_ROLE, MAX_ACTIVE_KEYS,
 };
+use crate::common::verify_auth_message::signer_address;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::secp256k1_recover::SECP256K1_PUBLIC_KEY_LENGTH;
-use anchor_lang::Accounts;
+use anchor_lang::{system_program, Accounts};
 
 #[event]
+#[derive(Debug, PartialEq)]
 pub struct KeyRegistered {
-    key: Vec<u8>,
-    valid_from: u64,
-    valid_to: u64,
+    pub instance: Pubkey,
+    pub key: Vec<u8>,
+    pub signer: Pubkey,
+    pub valid_from: u64,
+    pub valid_to: u64,
+    pub active_keys: u8,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[derive(Accounts)]
-#[instruction(key: Vec<u8>)]
+#[instruction(instance: Pubkey, key: Vec<u8>)]
 pub struct RegisterKey<'info> {
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"active_keys".as_ref(), instance_seed(&instance)],
+        bump,
+    )]
+    pub key_registry: Account<'info, KeyRegistry>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [KEY_MANAGER_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub key_manager_role: Account<'info, Role>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"_key_mapping".as_ref(), &key.to_hash().as_ref(), instance_seed(&instance)],
+        bump,
+        space = KeyEntry::space(key_mapping)
+    )]
+    pub key_mapping: Account<'info, KeyEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+// Accounts of the original `register_key`, for the default instance
+#[derive(Accounts)]
+#[instruction(key: Vec<u8>)]
+pub struct RegisterKeyLegacy<'info> {
     #[account(
         mut,
         seeds = [b"keyring_program".as_ref(), b"active_keys".as_ref()],
@@ -34,7 +70,7 @@ pub struct RegisterKey<'info> {
         payer = signer,
         seeds = [b"keyring_program".as_ref(), b"_key_mapping".as_ref(), &key.to_hash().as_ref()],
         bump,
-        space = 8 + KeyEntry::MAX_SIZE
+        space = KeyEntry::space(key_mapping)
     )]
     pub key_mapping: Account<'info, KeyEntry>,
     pub system_program: Program<'info, System>,
@@ -42,18 +78,95 @@ pub struct RegisterKey<'info> {
 
 pub fn do_register_key(
     ctx: Context<RegisterKey>,
+    instance: Pubkey,
     key: Vec<u8>,
     valid_from: u64,
     valid_to: u64,
 ) -> Result<()> {
-    if !ctx.accounts.key_manager_role.has_role {
+    let clock: Clock = Clock::get()?;
+    if !ctx
+        .accounts
+        .key_manager_role
+        .is_active(clock.unix_timestamp)
+    {
         return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
     }
 
-    let clock: Clock = Clock::get()?;
     let time_stamp = clock.unix_timestamp;
 
-    if key.len() != SECP256K1_PUBLIC_KEY_LENGTH {
+    resize_key_mapping(
+        &ctx.accounts.key_mapping.to_account_info(),
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+    register_key_entry(
+        &mut ctx.accounts.key_mapping,
+        &mut ctx.accounts.key_registry,
+        &key,
+        valid_from,
+        valid_to,
+        time_stamp as u64,
+    )?;
+
+    emit!(KeyRegistered {
+        instance,
+        key,
+        signer: ctx.accounts.signer.key(),
+        valid_from,
+        valid_to,
+        active_keys: ctx.accounts.key_registry.active_keys.len() as u8,
+        timestamp: time_stamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
+
+pub fn do_register_key_legacy<'info>(
+    ctx: Context<'_, '_, '_, 'info, RegisterKeyLegacy<'info>>,
+    key: Vec<u8>,
+    valid_from: u64,
+    valid_to: u64,
+) -> Result<()> {
+    let legacy = ctx.accounts;
+    let mut accounts = RegisterKey {
+        key_registry: legacy.key_registry.clone(),
+        signer: legacy.signer.clone(),
+        key_manager_role: legacy.key_manager_role.clone(),
+        key_mapping: legacy.key_mapping.clone(),
+        system_program: legacy.system_program.clone(),
+    };
+    do_register_key(
+        Context::new(
+            ctx.program_id,
+            &mut accounts,
+            ctx.remaining_accounts,
+            RegisterKeyBumps::default(),
+        ),
+        DEFAULT_INSTANCE,
+        key,
+        valid_from,
+        valid_to,
+    )?;
+    // Written back when the legacy accounts exit
+    legacy.key_registry = accounts.key_registry;
+    legacy.key_mapping = accounts.key_mapping;
+
+    Ok(())
+}
+
+// Validates registration parameters and marks the key as valid, shared by
+// `register_key` and `rotate_key`. The key is either an uncompressed public key
+// or the Ethereum address derived from it.
+pub(crate) fn register_key_entry(
+    key_mapping: &mut KeyEntry,
+    key_registry: &mut KeyRegistry,
+    key: &[u8],
+    valid_from: u64,
+    valid_to: u64,
+    current_timestamp: u64,
+) -> Result<()> {
+    if key.len() != SECP256K1_PUBLIC_KEY_LENGTH && key.len() != ETH_ADDRESS_LENGTH {
         return Err(error!(KeyringError::ErrInvalidPubkeyLength));
     }
 
@@ -61,31 +174,68 @@ pub fn do_register_key(
         return Err(error!(KeyringError::ErrInvalidKeyRegistrationParams));
     }
 
-    if valid_to < time_stamp as u64 {
+    if valid_to < current_timestamp {
         return Err(error!(KeyringError::ErrInvalidKeyRegistrationParams));
     }
 
-    if ctx.accounts.key_mapping.is_valid {
+    if key_mapping.is_valid {
         return Err(error!(KeyringError::ErrKeyAlreadyRegistered));
     }
 
-    *ctx.accounts.key_mapping = KeyEntry {
+    // A public key and its address have separate key mappings, so only one of
+    // them may be registered at a time
+    let address = signer_address(key)?;
+    for active_key in &key_registry.active_keys {
+        if active_key.as_slice() != key && signer_address(active_key)? == address {
+            return Err(error!(KeyringError::ErrKeyAlreadyRegistered));
+        }
+    }
+
+    // Keys registered again keep the policy scope and quota set for them, new
+    // keys read as allowed for any policy without a quota. Usage starts over.
+    *key_mapping = KeyEntry {
         version: CURRENT_VERSION,
         is_valid: true,
         valid_from,
         valid_to,
+        policy_scope: key_mapping.policy_scope.clone(),
+        quota: key_mapping.quota.clone(),
+        usage: KeyUsage::default(),
     };
 
-    if ctx.accounts.key_registry.active_keys.len() + 1 > MAX_ACTIVE_KEYS as usize {
+    if key_registry.active_keys.len() + 1 > MAX_ACTIVE_KEYS as usize {
         return Err(error!(KeyringError::ErrBreachedMaxActiveKeyLimit));
     }
-    ctx.accounts.key_registry.active_keys.push(key.clone());
+    key_registry.active_keys.push(key.to_vec());
 
-    emit!(KeyRegistered {
-        key,
-        valid_from,
-        valid_to
-    });
+    Ok(())
+}
+
+// Grows key mappings registered before keys had a policy scope, quota and
+// usage to the current size, so that they can be written.
+pub(crate) fn resize_key_mapping<'info>(
+    key_mapping: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if key_mapping.data_len() >= 8 + KeyEntry::MAX_SIZE {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?
+        .minimum_balance(8 + KeyEntry::MAX_SIZE)
+        .saturating_sub(key_mapping.lamports());
+    if rent != 0 {
+        let cpi_context = CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: key_mapping.clone(),
+            },
+        );
+        system_program::transfer(cpi_context, rent)?;
+    }
+    key_mapping.realloc(8 + KeyEntry::MAX_SIZE, false)?;
 
     Ok(())
 }
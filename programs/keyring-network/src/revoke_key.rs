@@ -1,16 +1,54 @@
 use crate::common::error::KeyringError;
-use crate::common::types::{KeyEntry, KeyRegistry, Role, ToHash, KEY_MANAGER_ROLE};
+use crate::common::types::{
+    instance_seed, KeyEntry, KeyRegistry, Role, ToHash, DEFAULT_INSTANCE, KEY_MANAGER_ROLE,
+};
+use crate::register_key::resize_key_mapping;
 use anchor_lang::prelude::*;
 use anchor_lang::Accounts;
 
 #[event]
+#[derive(Debug, PartialEq)]
 pub struct KeyRevoked {
-    key: Vec<u8>,
+    pub instance: Pubkey,
+    pub key: Vec<u8>,
+    pub signer: Pubkey,
+    pub was_valid: bool,
+    pub valid_from: u64,
+    pub valid_to: u64,
+    pub active_keys: u8,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[derive(Accounts)]
-#[instruction(key: Vec<u8>)]
+#[instruction(instance: Pubkey, key: Vec<u8>)]
 pub struct RevokeKey<'info> {
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"active_keys".as_ref(), instance_seed(&instance)],
+        bump,
+    )]
+    pub key_registry: Account<'info, KeyRegistry>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [KEY_MANAGER_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub key_manager_role: Account<'info, Role>,
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"_key_mapping".as_ref(), &key.to_hash().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub key_mapping: Account<'info, KeyEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+// Accounts of the original `revoke_key`, for the default instance
+#[derive(Accounts)]
+#[instruction(key: Vec<u8>)]
+pub struct RevokeKeyLegacy<'info> {
     #[account(
         mut,
         seeds = [b"keyring_program".as_ref(), b"active_keys".as_ref()],
@@ -33,19 +71,83 @@ pub struct RevokeKey<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn do_revoke_key(ctx: Context<RevokeKey>, key: Vec<u8>) -> Result<()> {
-    if !ctx.accounts.key_manager_role.has_role {
+pub fn do_revoke_key(ctx: Context<RevokeKey>, instance: Pubkey, key: Vec<u8>) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx
+        .accounts
+        .key_manager_role
+        .is_active(clock.unix_timestamp)
+    {
         return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
     }
 
-    ctx.accounts.key_mapping.is_valid = false;
+    resize_key_mapping(
+        &ctx.accounts.key_mapping.to_account_info(),
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+    let was_valid = ctx.accounts.key_mapping.is_valid;
+    revoke_key_entry(
+        &mut ctx.accounts.key_mapping,
+        &mut ctx.accounts.key_registry,
+        &key,
+    );
 
-    let active_keys = &mut ctx.accounts.key_registry.active_keys;
-    if let Some(index) = active_keys.iter().position(|x| x.eq(&key)) {
-        active_keys.swap_remove(index);
-    }
+    emit!(KeyRevoked {
+        instance,
+        key,
+        signer: ctx.accounts.signer.key(),
+        was_valid,
+        valid_from: ctx.accounts.key_mapping.valid_from,
+        valid_to: ctx.accounts.key_mapping.valid_to,
+        active_keys: ctx.accounts.key_registry.active_keys.len() as u8,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
 
-    emit!(KeyRevoked { key });
+    Ok(())
+}
+
+pub fn do_revoke_key_legacy<'info>(
+    ctx: Context<'_, '_, '_, 'info, RevokeKeyLegacy<'info>>,
+    key: Vec<u8>,
+) -> Result<()> {
+    let legacy = ctx.accounts;
+    let mut accounts = RevokeKey {
+        key_registry: legacy.key_registry.clone(),
+        signer: legacy.signer.clone(),
+        key_manager_role: legacy.key_manager_role.clone(),
+        key_mapping: legacy.key_mapping.clone(),
+        system_program: legacy.system_program.clone(),
+    };
+    do_revoke_key(
+        Context::new(
+            ctx.program_id,
+            &mut accounts,
+            ctx.remaining_accounts,
+            RevokeKeyBumps::default(),
+        ),
+        DEFAULT_INSTANCE,
+        key,
+    )?;
+    // Written back when the legacy accounts exit
+    legacy.key_registry = accounts.key_registry;
+    legacy.key_mapping = accounts.key_mapping;
 
     Ok(())
 }
+
+// Marks the key as invalid and removes it from active keys, shared by
+// `revoke_key` and `rotate_key`.
+pub(crate) fn revoke_key_entry(
+    key_mapping: &mut KeyEntry,
+    key_registry: &mut KeyRegistry,
+    key: &[u8],
+) {
+    key_mapping.is_valid = false;
+
+    let active_keys = &mut key_registry.active_keys;
+    if let Some(index) = active_keys.iter().position(|x| x.eq(key)) {
+        active_keys.swap_remove(index);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::common::error::KeyringError;
+use crate::common::types::{instance_seed, KeyEntry, KeyRegistry, Role, ToHash, KEY_MANAGER_ROLE};
+use crate::register_key::{register_key_entry, resize_key_mapping};
+use crate::revoke_key::revoke_key_entry;
+use anchor_lang::prelude::*;
+use anchor_lang::Accounts;
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct KeyRotated {
+    pub instance: Pubkey,
+    pub old_key: Vec<u8>,
+    pub new_key: Vec<u8>,
+    pub signer: Pubkey,
+    pub old_key_revoked: bool,
+    pub old_key_valid_to: u64,
+    pub valid_from: u64,
+    pub valid_to: u64,
+    pub active_keys: u8,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, old_key: Vec<u8>, new_key: Vec<u8>)]
+pub struct RotateKey<'info> {
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"active_keys".as_ref(), instance_seed(&instance)],
+        bump,
+    )]
+    pub key_registry: Account<'info, KeyRegistry>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [KEY_MANAGER_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub key_manager_role: Account<'info, Role>,
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"_key_mapping".as_ref(), &old_key.to_hash().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub old_key_mapping: Account<'info, KeyEntry>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"_key_mapping".as_ref(), &new_key.to_hash().as_ref(), instance_seed(&instance)],
+        bump,
+        space = KeyEntry::space(new_key_mapping)
+    )]
+    pub new_key_mapping: Account<'info, KeyEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+// Replaces `old_key` with `new_key` in a single instruction. With a non-zero
+// `overlap` the old key stays valid for `overlap` more seconds (but never past
+// its original `valid_to`) so that credentials signed during the switch are
+// still accepted, otherwise it is revoked immediately. An overlapping old key
+// keeps its slot in the active keys after the overlap ends, until a key
+// manager revokes it with `revoke_key`. The new key inherits the policy scope
+// and quota of the old key, its usage counters start from zero.
+pub fn do_rotate_key(
+    ctx: Context<RotateKey>,
+    instance: Pubkey,
+    old_key: Vec<u8>,
+    new_key: Vec<u8>,
+    valid_from: u64,
+    valid_to: u64,
+    overlap: u64,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx
+        .accounts
+        .key_manager_role
+        .is_active(clock.unix_timestamp)
+    {
+        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
+    }
+
+    if old_key == new_key {
+        return Err(error!(KeyringError::ErrInvalidKeyRotationParams));
+    }
+
+    if !ctx.accounts.old_key_mapping.is_valid {
+        return Err(error!(KeyringError::ErrKeyNotRegistered));
+    }
+
+    let current_timestamp = clock.unix_timestamp as u64;
+
+    resize_key_mapping(
+        &ctx.accounts.old_key_mapping.to_account_info(),
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+    resize_key_mapping(
+        &ctx.accounts.new_key_mapping.to_account_info(),
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+
+    let old_key_revoked = overlap == 0;
+    if old_key_revoked {
+        revoke_key_entry(
+            &mut ctx.accounts.old_key_mapping,
+            &mut ctx.accounts.key_registry,
+            &old_key,
+        );
+    } else {
+        let overlap_end = current_timestamp.saturating_add(overlap);
+        if overlap_end < ctx.accounts.old_key_mapping.valid_to {
+            ctx.accounts.old_key_mapping.valid_to = overlap_end;
+        }
+    }
+
+    register_key_entry(
+        &mut ctx.accounts.new_key_mapping,
+        &mut ctx.accounts.key_registry,
+        &new_key,
+        valid_from,
+        valid_to,
+        current_timestamp,
+    )?;
+    ctx.accounts.new_key_mapping.policy_scope = ctx.accounts.old_key_mapping.policy_scope.clone();
+    ctx.accounts.new_key_mapping.quota = ctx.accounts.old_key_mapping.quota.clone();
+
+    emit!(KeyRotated {
+        instance,
+        old_key,
+        new_key,
+        signer: ctx.accounts.signer.key(),
+        old_key_revoked,
+        old_key_valid_to: ctx.accounts.old_key_mapping.valid_to,
+        valid_from,
+        valid_to,
+        active_keys: ctx.accounts.key_registry.active_keys.len() as u8,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
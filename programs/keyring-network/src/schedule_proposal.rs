@@ -0,0 +1,112 @@
+use crate::common::error::KeyringError;
+use crate::common::types::{
+    instance_seed, ProgramState, Proposal, ProposalAccount, Role, MAX_PROPOSAL_ACCOUNTS,
+    MAX_PROPOSAL_DATA_LENGTH, PROPOSER_ROLE,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::Accounts;
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct ProposalScheduled {
+    pub instance: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub accounts: Vec<ProposalAccount>,
+    pub data: Vec<u8>,
+    pub execute_after: i64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, proposal_id: u64)]
+pub struct ScheduleProposal<'info> {
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + Proposal::MAX_SIZE,
+        seeds = [b"keyring_program".as_ref(), b"proposal".as_ref(), &proposal_id.to_le_bytes(), instance_seed(&instance)],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        seeds = [b"keyring_program".as_ref(), b"global_state".as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        seeds = [PROPOSER_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub proposer_role: Account<'info, Role>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Schedules an instruction of this program to be executed by anyone, signed
+// by the timelock authority, once the timelock delay has passed.
+pub fn do_schedule_proposal(
+    ctx: Context<ScheduleProposal>,
+    instance: Pubkey,
+    proposal_id: u64,
+    accounts: Vec<ProposalAccount>,
+    data: Vec<u8>,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx.accounts.proposer_role.is_active(clock.unix_timestamp) {
+        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
+    }
+
+    if accounts.len() > MAX_PROPOSAL_ACCOUNTS as usize || data.len() > MAX_PROPOSAL_DATA_LENGTH {
+        return Err(error!(KeyringError::ErrInvalidProposal));
+    }
+    // Anyone can execute the proposal, so nobody but the timelock authority
+    // can be required to sign it
+    let (timelock, _) = timelock_authority(&instance);
+    if accounts
+        .iter()
+        .any(|account| account.is_signer && account.pubkey != timelock)
+    {
+        return Err(error!(KeyringError::ErrInvalidProposal));
+    }
+
+    let delay = ctx.accounts.program_state.config.timelock_delay;
+    let execute_after = clock
+        .unix_timestamp
+        .saturating_add(delay.try_into().unwrap_or(i64::MAX));
+    *ctx.accounts.proposal = Proposal {
+        proposer: ctx.accounts.signer.key(),
+        accounts: accounts.clone(),
+        data: data.clone(),
+        execute_after,
+        approvals: vec![],
+    };
+
+    emit!(ProposalScheduled {
+        instance,
+        proposal_id,
+        proposer: ctx.accounts.signer.key(),
+        accounts,
+        data,
+        execute_after,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
+
+// Account signing executed proposals. It holds no data, so that it can pay
+// for the accounts created by the instructions it signs.
+pub(crate) fn timelock_authority(instance: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"timelock".as_ref(),
+            instance_seed(instance),
+        ],
+        &crate::ID,
+    )
+}
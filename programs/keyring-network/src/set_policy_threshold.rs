@@ -0,0 +1,89 @@
+use crate::common::error::KeyringError;
+use crate::common::types::{
+    instance_seed, PolicyConfig, ProgramState, Role, CURRENT_VERSION, DEFAULT_ADMIN_ROLE,
+    MAX_ATTESTATION_KEYS,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::Accounts;
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct PolicyThresholdUpdated {
+    pub instance: Pubkey,
+    pub policy_id: u64,
+    pub signer: Pubkey,
+    pub previous_threshold: u8,
+    pub threshold: u8,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, policy_id: u64)]
+pub struct SetPolicyThreshold<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [DEFAULT_ADMIN_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub default_admin_role: Account<'info, Role>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + PolicyConfig::MAX_SIZE,
+        seeds = [b"keyring_program".as_ref(), b"_policy_config".as_ref(), &policy_id.to_le_bytes(), instance_seed(&instance)],
+        bump
+    )]
+    pub policy_config: Account<'info, PolicyConfig>,
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"global_state".as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn do_set_policy_threshold(
+    ctx: Context<SetPolicyThreshold>,
+    instance: Pubkey,
+    policy_id: u64,
+    threshold: u8,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx
+        .accounts
+        .default_admin_role
+        .is_active(clock.unix_timestamp)
+    {
+        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
+    }
+
+    if threshold == 0 || threshold > MAX_ATTESTATION_KEYS {
+        return Err(error!(KeyringError::ErrInvalidPolicyThreshold));
+    }
+
+    let previous_threshold = ctx.accounts.policy_config.threshold.max(1);
+    ctx.accounts.policy_config.version = CURRENT_VERSION;
+    ctx.accounts.policy_config.threshold = threshold;
+    // Counted for the legacy `create_credential`, which cannot see thresholds
+    let threshold_policies = &mut ctx.accounts.program_state.threshold_policies;
+    match (previous_threshold > 1, threshold > 1) {
+        (false, true) => *threshold_policies += 1,
+        (true, false) => *threshold_policies -= 1,
+        _ => {}
+    }
+
+    emit!(PolicyThresholdUpdated {
+        instance,
+        policy_id,
+        signer: ctx.accounts.signer.key(),
+        previous_threshold,
+        threshold,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
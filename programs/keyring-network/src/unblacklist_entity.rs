@@ -1,32 +1,61 @@
-use crate::common::error::KeyringError;
+use crate::blacklist_entity::{check_blacklist_manager, resize_entity_mapping};
 use crate::common::types::{
-    EntityData, Role, BLACKLIST_MANAGER_ROLE, CURRENT_VERSION,
+    instance_seed, BlacklistReason, Blacklisting, EntityData, CURRENT_VERSION, DEFAULT_INSTANCE,
 };
 use anchor_lang::prelude::*;
 use anchor_lang::Accounts;
 
 #[event]
+#[derive(Debug, PartialEq)]
 pub struct UnBlackListedEntity {
-    policy_id: u64,
-    trading_address: Pubkey,
+    pub instance: Pubkey,
+    pub policy_id: u64,
+    pub trading_address: Pubkey,
+    pub signer: Pubkey,
+    pub previously_blacklisted: bool,
+    pub previous_exp: u64,
+    // Reason of the lifted blacklisting
+    pub previous_reason: BlacklistReason,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[derive(Accounts)]
-#[instruction(policy_id: u64, trading_address: Pubkey)]
+#[instruction(instance: Pubkey, policy_id: u64, trading_address: Pubkey)]
 pub struct UnblacklistEntity<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
+    /// CHECK: Role account of the signer for `BLACKLIST_MANAGER_ROLE` or the
+    /// blacklist manager role of the policy, checked by
+    /// `check_blacklist_manager`.
+    pub blacklist_manager_role: AccountInfo<'info>,
     #[account(
-        seeds = [BLACKLIST_MANAGER_ROLE.as_ref(), signer.key().to_bytes().as_ref()],
-        bump
+        init_if_needed,
+        payer = signer,
+        seeds = [b"keyring_program".as_ref(), b"_entity_mapping".as_ref(), &policy_id.to_le_bytes(), &trading_address.to_bytes(), instance_seed(&instance)],
+        bump,
+        space = EntityData::space(entity_mapping)
     )]
-    pub blacklist_manager_role: Account<'info, Role>,
+    pub entity_mapping: Account<'info, EntityData>,
+    pub system_program: Program<'info, System>,
+}
+
+// Accounts of the original `unblacklist_entity`, for the default instance
+#[derive(Accounts)]
+#[instruction(policy_id: u64, trading_address: Pubkey)]
+pub struct UnblacklistEntityLegacy<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// CHECK: Role account of the signer for `BLACKLIST_MANAGER_ROLE` or the
+    /// blacklist manager role of the policy, checked by
+    /// `check_blacklist_manager`.
+    pub blacklist_manager_role: AccountInfo<'info>,
     #[account(
         init_if_needed,
         payer = signer,
         seeds = [b"keyring_program".as_ref(), b"_entity_mapping".as_ref(), &policy_id.to_le_bytes(), &trading_address.to_bytes()],
         bump,
-        space = 8 + EntityData::MAX_SIZE
+        space = EntityData::space(entity_mapping)
     )]
     pub entity_mapping: Account<'info, EntityData>,
     pub system_program: Program<'info, System>,
@@ -34,28 +63,95 @@ pub struct UnblacklistEntity<'info> {
 
 pub fn do_unblacklist_entity(
     ctx: Context<UnblacklistEntity>,
+    instance: Pubkey,
     policy_id: u64,
     trading_address: Pubkey,
 ) -> Result<()> {
-    if !ctx.accounts.blacklist_manager_role.has_role {
-        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
-    }
+    let clock: Clock = Clock::get()?;
+    check_blacklist_manager(
+        &ctx.accounts.blacklist_manager_role,
+        &ctx.accounts.signer.key(),
+        Some(policy_id),
+        &instance,
+        clock.unix_timestamp,
+    )?;
 
-    if !ctx.accounts.entity_mapping.blacklisted {
-        ctx.accounts.entity_mapping.version = CURRENT_VERSION;
-        return Ok(());
-    }
+    resize_entity_mapping(
+        &ctx.accounts.entity_mapping,
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
 
-    *ctx.accounts.entity_mapping = EntityData {
-        version: CURRENT_VERSION,
-        blacklisted: false,
-        exp: 0,
-    };
+    let (previously_blacklisted, previous_exp, previous_reason) = unblacklist(
+        &mut ctx.accounts.entity_mapping,
+        clock.unix_timestamp.try_into().unwrap(),
+    );
 
     emit!(UnBlackListedEntity {
+        instance,
         policy_id,
-        trading_address
+        trading_address,
+        signer: ctx.accounts.signer.key(),
+        previously_blacklisted,
+        previous_exp,
+        previous_reason,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
     });
 
     Ok(())
 }
+
+pub fn do_unblacklist_entity_legacy<'info>(
+    ctx: Context<'_, '_, '_, 'info, UnblacklistEntityLegacy<'info>>,
+    policy_id: u64,
+    trading_address: Pubkey,
+) -> Result<()> {
+    let legacy = ctx.accounts;
+    let mut accounts = UnblacklistEntity {
+        signer: legacy.signer.clone(),
+        blacklist_manager_role: legacy.blacklist_manager_role.clone(),
+        entity_mapping: legacy.entity_mapping.clone(),
+        system_program: legacy.system_program.clone(),
+    };
+    do_unblacklist_entity(
+        Context::new(
+            ctx.program_id,
+            &mut accounts,
+            ctx.remaining_accounts,
+            UnblacklistEntityBumps::default(),
+        ),
+        DEFAULT_INSTANCE,
+        policy_id,
+        trading_address,
+    )?;
+    // Written back when the legacy accounts exit
+    legacy.entity_mapping = accounts.entity_mapping;
+
+    Ok(())
+}
+
+// Unblacklists the entity, returning whether it was blacklisted, its previous
+// expiry and the reason of the lifted blacklisting. Expired blacklistings are
+// cleared along with active ones.
+pub(crate) fn unblacklist(
+    entity_mapping: &mut EntityData,
+    current_timestamp: u64,
+) -> (bool, u64, BlacklistReason) {
+    let previously_blacklisted = entity_mapping.is_blacklisted(current_timestamp);
+    let previous_exp = entity_mapping.exp;
+    let previous_reason = entity_mapping.blacklisting.reason;
+
+    if !entity_mapping.blacklisted {
+        entity_mapping.version = CURRENT_VERSION;
+    } else {
+        *entity_mapping = EntityData {
+            version: CURRENT_VERSION,
+            blacklisted: false,
+            exp: 0,
+            blacklisting: Blacklisting::default(),
+        };
+    }
+
+    (previously_blacklisted, previous_exp, previous_reason)
+}
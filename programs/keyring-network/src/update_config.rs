@@ -0,0 +1,79 @@
+use crate::common::error::KeyringError;
+use crate::common::types::{
+    instance_seed, ChainId, ProgramConfig, ProgramState, Role, CURRENT_VERSION, DEFAULT_ADMIN_ROLE,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::Accounts;
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct ConfigUpdated {
+    pub instance: Pubkey,
+    pub signer: Pubkey,
+    pub previous_chain_id: ChainId,
+    pub chain_id: ChainId,
+    pub previous_config: ProgramConfig,
+    pub config: ProgramConfig,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(instance: Pubkey)]
+pub struct UpdateConfig<'info> {
+    // States created before the config was added are smaller than
+    // `ProgramState::MAX_SIZE` and are grown on their first update.
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"global_state".as_ref(), instance_seed(&instance)],
+        bump,
+        realloc = 8 + ProgramState::MAX_SIZE,
+        realloc::payer = signer,
+        realloc::zero = false
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        seeds = [DEFAULT_ADMIN_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub default_admin_role: Account<'info, Role>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn do_update_config(
+    ctx: Context<UpdateConfig>,
+    instance: Pubkey,
+    chain_id: Vec<u8>,
+    config: ProgramConfig,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx
+        .accounts
+        .default_admin_role
+        .is_active(clock.unix_timestamp)
+    {
+        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
+    }
+
+    let chain_id = ChainId::new(chain_id).map_err(|_| KeyringError::ErrInvalidChainId)?;
+
+    let program_state = &mut ctx.accounts.program_state;
+    let previous_chain_id = std::mem::replace(&mut program_state.chain_id, chain_id.clone());
+    let previous_config = std::mem::replace(&mut program_state.config, config.clone());
+    program_state.version = CURRENT_VERSION;
+
+    emit!(ConfigUpdated {
+        instance,
+        signer: ctx.accounts.signer.key(),
+        previous_chain_id,
+        chain_id,
+        previous_config,
+        config,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
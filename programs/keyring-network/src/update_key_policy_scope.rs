@@ -0,0 +1,83 @@
+use crate::common::error::KeyringError;
+use crate::common::types::{
+    instance_seed, KeyEntry, PolicyScope, Role, ToHash, CURRENT_VERSION, KEY_MANAGER_ROLE,
+};
+use crate::register_key::resize_key_mapping;
+use anchor_lang::prelude::*;
+use anchor_lang::Accounts;
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct KeyPolicyScopeUpdated {
+    pub instance: Pubkey,
+    pub key: Vec<u8>,
+    pub signer: Pubkey,
+    pub previous_policy_scope: PolicyScope,
+    pub policy_scope: PolicyScope,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, key: Vec<u8>)]
+pub struct UpdateKeyPolicyScope<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [KEY_MANAGER_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub key_manager_role: Account<'info, Role>,
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"_key_mapping".as_ref(), &key.to_hash().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub key_mapping: Account<'info, KeyEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn do_update_key_policy_scope(
+    ctx: Context<UpdateKeyPolicyScope>,
+    instance: Pubkey,
+    key: Vec<u8>,
+    policy_scope: PolicyScope,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx
+        .accounts
+        .key_manager_role
+        .is_active(clock.unix_timestamp)
+    {
+        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
+    }
+
+    if !ctx.accounts.key_mapping.is_valid {
+        return Err(error!(KeyringError::ErrKeyNotRegistered));
+    }
+
+    if !policy_scope.is_valid() {
+        return Err(error!(KeyringError::ErrInvalidPolicyScope));
+    }
+
+    resize_key_mapping(
+        &ctx.accounts.key_mapping.to_account_info(),
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+    let previous_policy_scope = ctx.accounts.key_mapping.policy_scope.clone();
+    ctx.accounts.key_mapping.version = CURRENT_VERSION;
+    ctx.accounts.key_mapping.policy_scope = policy_scope.clone();
+
+    emit!(KeyPolicyScopeUpdated {
+        instance,
+        key,
+        signer: ctx.accounts.signer.key(),
+        previous_policy_scope,
+        policy_scope,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
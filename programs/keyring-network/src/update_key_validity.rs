@@ -0,0 +1,98 @@
+use crate::common::error::KeyringError;
+use crate::common::types::{
+    instance_seed, KeyEntry, Role, ToHash, CURRENT_VERSION, KEY_MANAGER_ROLE,
+};
+use crate::register_key::resize_key_mapping;
+use anchor_lang::prelude::*;
+use anchor_lang::Accounts;
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct KeyValidityUpdated {
+    pub instance: Pubkey,
+    pub key: Vec<u8>,
+    pub signer: Pubkey,
+    pub previous_valid_from: u64,
+    pub previous_valid_to: u64,
+    pub valid_from: u64,
+    pub valid_to: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(instance: Pubkey, key: Vec<u8>)]
+pub struct UpdateKeyValidity<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [KEY_MANAGER_ROLE.as_ref(), signer.key().to_bytes().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub key_manager_role: Account<'info, Role>,
+    #[account(
+        mut,
+        seeds = [b"keyring_program".as_ref(), b"_key_mapping".as_ref(), &key.to_hash().as_ref(), instance_seed(&instance)],
+        bump
+    )]
+    pub key_mapping: Account<'info, KeyEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn do_update_key_validity(
+    ctx: Context<UpdateKeyValidity>,
+    instance: Pubkey,
+    key: Vec<u8>,
+    valid_from: u64,
+    valid_to: u64,
+) -> Result<()> {
+    let clock: Clock = Clock::get()?;
+    if !ctx
+        .accounts
+        .key_manager_role
+        .is_active(clock.unix_timestamp)
+    {
+        return Err(error!(KeyringError::ErrCallerDoesNotHaveRole));
+    }
+
+    // Revoked keys must be registered again instead of being extended
+    if !ctx.accounts.key_mapping.is_valid {
+        return Err(error!(KeyringError::ErrKeyNotRegistered));
+    }
+
+    let time_stamp = clock.unix_timestamp;
+
+    if valid_to <= valid_from {
+        return Err(error!(KeyringError::ErrInvalidKeyRegistrationParams));
+    }
+
+    if valid_to < time_stamp as u64 {
+        return Err(error!(KeyringError::ErrInvalidKeyRegistrationParams));
+    }
+
+    resize_key_mapping(
+        &ctx.accounts.key_mapping.to_account_info(),
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+    )?;
+    let previous_valid_from = ctx.accounts.key_mapping.valid_from;
+    let previous_valid_to = ctx.accounts.key_mapping.valid_to;
+
+    ctx.accounts.key_mapping.version = CURRENT_VERSION;
+    ctx.accounts.key_mapping.valid_from = valid_from;
+    ctx.accounts.key_mapping.valid_to = valid_to;
+
+    emit!(KeyValidityUpdated {
+        instance,
+        key,
+        signer: ctx.accounts.signer.key(),
+        previous_valid_from,
+        previous_valid_to,
+        valid_from,
+        valid_to,
+        timestamp: time_stamp,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
@@ -10,28 +10,89 @@ anchor build
 anchor test test_initialize --skip-build
 sleep 5s
 
+anchor test test_instances --skip-build
+sleep 5s
+
+anchor test test_legacy_instructions --skip-build
+sleep 5s
+
+anchor test test_proposal_approvals --skip-build
+sleep 5s
+
 anchor test test_register_key --skip-build
 sleep 5s
 
+anchor test test_register_key_address --skip-build
+sleep 5s
+
 anchor test test_revoke_key --skip-build
 sleep 5s
 
+anchor test test_role_definitions --skip-build
+sleep 5s
+
+anchor test test_role_expiry --skip-build
+sleep 5s
+
+anchor test test_role_members --skip-build
+sleep 5s
+
+anchor test test_rotate_key --skip-build
+sleep 5s
+
+anchor test test_update_key_validity --skip-build
+sleep 5s
+
+anchor test test_key_policy_scope --skip-build
+sleep 5s
+
+anchor test test_key_quota --skip-build
+sleep 5s
+
 anchor test test_manage_roles --skip-build
 sleep 5s
 
 anchor test test_blacklist_entity --skip-build
 sleep 5s
 
+anchor test test_timelock --skip-build
+sleep 5s
+
 anchor test test_unblacklist_entity --skip-build
 sleep 5s
 
+anchor test test_temporary_blacklist --skip-build
+sleep 5s
+
+anchor test test_batch_blacklist --skip-build
+sleep 5s
+
 anchor test test_create_credentials --skip-build
 sleep 5s
 
+anchor test test_create_credential_by_signer --skip-build
+sleep 5s
+
+anchor test test_credential_errors --skip-build
+sleep 5s
+
+anchor test test_attestation_formats --skip-build
+sleep 5s
+
+anchor test test_threshold_credential --skip-build
+sleep 5s
+
 anchor test test_collect_fees --skip-build
 sleep 5s
 
+anchor test test_update_config --skip-build
+sleep 5s
+
 anchor test test_check_credential --skip-build
 sleep 5s
 
 anchor test test_verify_auth_message --skip-build
+
+sleep 5s
+
+anchor test test_indexer --skip-build
@@ -1,11 +1,14 @@
 import * as anchor from "@coral-xyz/anchor";
 
 import { setup } from "./utils/setup";
+import { DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE } from "./utils/constants";
 import { Config } from "./utils/types";
 import {
     getDefaultAdminRolePda,
     getKeyRegistryPda,
+    getProgramDataPda,
     getProgramStatePda,
+    getRoleMembersPda,
 } from "./utils/getPda";
 
 async function initialize() {
@@ -17,7 +20,7 @@ async function initialize() {
     console.log("Initializing program...");
 
     const txSignature = await config.program.methods
-        .initialize(bufferSolanaChainId)
+        .initializeV2(DEFAULT_INSTANCE, bufferSolanaChainId)
         .accounts({
             programState: getProgramStatePda(config.program.programId),
             keyRegistry: getKeyRegistryPda(config.program.programId),
@@ -25,7 +28,12 @@ async function initialize() {
                 config.program.programId,
                 config.provider.wallet.publicKey
             ),
+            defaultAdminRoleMembers: getRoleMembersPda(
+                config.program.programId,
+                Buffer.from(DEFAULT_ADMIN_ROLE)
+            ),
             signer: config.provider.wallet.publicKey,
+            programData: getProgramDataPda(config.program.programId),
             systemProgram: anchor.web3.SystemProgram.programId,
         })
         .rpc();
@@ -1,6 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 
 import { setup } from "./utils/setup";
+import { DEFAULT_INSTANCE } from "./utils/constants";
 import { Config } from "./utils/types";
 import {
     getBlacklistManagerRolePda,
@@ -24,13 +25,23 @@ async function manageBlacklist() {
     const policyId = new anchor.BN(policyIdString);
     const user = new anchor.web3.PublicKey(userString);
     let blacklist = blacklistString === "true" ? true : false;
+    // Reason of the blacklisting (unspecified, sanctions, fraud-review,
+    // court-order or other) and timestamp from which it is lifted
+    const reasonString = process.env.REASON || "unspecified";
+    const untilString = process.env.UNTIL || "";
+    const reasons = ["unspecified", "sanctions", "fraud-review", "court-order", "other"];
+    if (!reasons.includes(reasonString)) throw new Error("Invalid params");
+    const reason = {
+        [reasonString.replace(/-(\w)/g, (_, c) => c.toUpperCase())]: {},
+    };
+    const until = untilString === "" ? null : new anchor.BN(untilString);
 
     console.log("Managing blacklist...");
 
     let txSignature: string;
     if (blacklist) {
         txSignature = await config.program.methods
-            .blacklistEntity(policyId, user, blacklist)
+            .blacklistEntityV2(DEFAULT_INSTANCE, policyId, user, reason, until)
             .accounts({
                 blacklistManagerRole: getBlacklistManagerRolePda(
                     config.program.programId,
@@ -47,7 +58,7 @@ async function manageBlacklist() {
             .rpc();
     } else {
         txSignature = await config.program.methods
-            .unblacklistEntity(policyId, user, blacklist)
+            .unblacklistEntityV2(DEFAULT_INSTANCE, policyId, user, blacklist)
             .accounts({
                 blacklistManagerRole: getBlacklistManagerRolePda(
                     config.program.programId,
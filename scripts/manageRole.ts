@@ -7,12 +7,21 @@ import {
     DEFAULT_ADMIN_ROLE,
     KEY_MANAGER_ROLE,
     OPERATOR_ROLE,
+    PROPOSER_ROLE,
+    CANCELLER_ROLE,
+    APPROVER_ROLE,
+    DEFAULT_INSTANCE,
 } from "./utils/constants";
 import {
     getBlacklistManagerRolePda,
     getDefaultAdminRolePda,
     getKeyManagerRolePda,
     getOperatorRolePda,
+    getProposerRolePda,
+    getCancellerRolePda,
+    getApproverRolePda,
+    getRoleDefinitionPda,
+    getRoleMembersPda,
 } from "./utils/getPda";
 
 async function manageRole() {
@@ -20,6 +29,7 @@ async function manageRole() {
     const userString = process.env.USER || "";
     const roleString = process.env.ROLE || "";
     const hasRoleString = process.env.HAS_ROLE || "";
+    const expiresAtString = process.env.EXPIRES_AT || "";
 
     if (
         userString === "" ||
@@ -33,6 +43,9 @@ async function manageRole() {
     let role: Buffer<ArrayBufferLike>;
     let roleAccountPda: anchor.web3.PublicKey;
     let hasRole = hasRoleString === "true" ? true : false;
+    // Optional unix timestamp from which the grant is no longer honored
+    let expiresAt =
+        expiresAtString === "" ? null : new anchor.BN(expiresAtString);
 
     switch (roleString) {
         case "DEFAULT_ADMIN_ROLE":
@@ -60,6 +73,21 @@ async function manageRole() {
             role = Buffer.from(OPERATOR_ROLE);
             roleAccountPda = getOperatorRolePda(config.program.programId, user);
             break;
+        case "PROPOSER_ROLE":
+            role = Buffer.from(PROPOSER_ROLE);
+            roleAccountPda = getProposerRolePda(config.program.programId, user);
+            break;
+        case "CANCELLER_ROLE":
+            role = Buffer.from(CANCELLER_ROLE);
+            roleAccountPda = getCancellerRolePda(
+                config.program.programId,
+                user
+            );
+            break;
+        case "APPROVER_ROLE":
+            role = Buffer.from(APPROVER_ROLE);
+            roleAccountPda = getApproverRolePda(config.program.programId, user);
+            break;
         default:
             throw new Error("Invalid role");
     }
@@ -67,13 +95,16 @@ async function manageRole() {
     console.log("Managing role...");
 
     const txSignature = await config.program.methods
-        .manageRoles(role, user, hasRole)
+        .manageRolesV2(DEFAULT_INSTANCE, role, user, hasRole, expiresAt)
         .accounts({
-            defaultAdminRole: getDefaultAdminRolePda(
+            roleDefinition: getRoleDefinitionPda(config.program.programId, role),
+            // Built-in roles are administered by the default admin role
+            adminRole: getDefaultAdminRolePda(
                 config.program.programId,
                 config.provider.wallet.publicKey
             ),
             role: roleAccountPda,
+            roleMembers: getRoleMembersPda(config.program.programId, role),
             signer: config.provider.wallet.publicKey,
             systemProgram: anchor.web3.SystemProgram.programId,
         })
@@ -1,6 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 
 import { setup } from "./utils/setup";
+import { DEFAULT_INSTANCE } from "./utils/constants";
 import { Config } from "./utils/types";
 import {
     getKeyMappingPda,
@@ -15,64 +16,67 @@ async function rotateKey() {
     const keyString = process.env.KEY || "";
     const validFrom = process.env.VALID_FROM || "";
     const validUntil = process.env.VALID_UNTIL || "";
+    const overlap = process.env.OVERLAP || "0";
 
     if (keyString === "" || validFrom === "" || validUntil === "")
         throw new Error("Invalid key rotation params");
 
     console.log("Rotating key...");
 
-    const revokeKey = previousKeyString === "" ? false : true;
-    let previousKeyArray: number[];
-    let previousKey: Buffer<ArrayBufferLike>;
-    if (revokeKey) {
-        previousKeyArray = JSON.parse(previousKeyString);
-        previousKey = Buffer.from(previousKeyArray);
-    }
     const keyArray: number[] = JSON.parse(keyString);
     const key = Buffer.from(keyArray);
+    const accounts = {
+        keyRegistry: getKeyRegistryPda(config.program.programId),
+        signer: config.provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        keyManagerRole: getKeyManagerRolePda(
+            config.program.programId,
+            config.provider.wallet.publicKey
+        ),
+    };
+
+    let txSignature: string;
+    if (previousKeyString !== "") {
+        const previousKeyArray: number[] = JSON.parse(previousKeyString);
+        const previousKey = Buffer.from(previousKeyArray);
 
-    let txSignature1: string;
-    if (revokeKey) {
-        txSignature1 = await config.program.methods
-            .revokeKey(previousKey)
+        // Old key is revoked and new key registered atomically, the old key
+        // stays valid for OVERLAP seconds if set.
+        txSignature = await config.program.methods
+            .rotateKey(
+                DEFAULT_INSTANCE,
+                previousKey,
+                key,
+                new anchor.BN(validFrom),
+                new anchor.BN(validUntil),
+                new anchor.BN(overlap)
+            )
             .accounts({
-                keyRegistry: getKeyRegistryPda(config.program.programId),
-                signer: config.provider.wallet.publicKey,
-                systemProgram: anchor.web3.SystemProgram.programId,
-                keyManagerRole: getKeyManagerRolePda(
-                    config.program.programId,
-                    config.provider.wallet.publicKey
-                ),
-                keyMapping: getKeyMappingPda(
+                ...accounts,
+                oldKeyMapping: getKeyMappingPda(
                     previousKey,
                     config.program.programId
                 ),
+                newKeyMapping: getKeyMappingPda(key, config.program.programId),
             })
             .rpc();
-    }
-    const txSignature2 = await config.program.methods
-        .registerKey(key, new anchor.BN(validFrom), new anchor.BN(validUntil))
-        .accounts({
-            keyRegistry: getKeyRegistryPda(config.program.programId),
-            signer: config.provider.wallet.publicKey,
-            systemProgram: anchor.web3.SystemProgram.programId,
-            keyManagerRole: getKeyManagerRolePda(
-                config.program.programId,
-                config.provider.wallet.publicKey
-            ),
-            keyMapping: getKeyMappingPda(key, config.program.programId),
-        })
-        .rpc();
-
-    console.log("Successfully rotated key");
-    if (revokeKey) {
-        console.log(
-            "Key rotation transaction signatures: ",
-            txSignature1,
-            txSignature2
-        );
+        console.log("Successfully rotated key");
+        console.log("Key rotation transaction signature: ", txSignature);
     } else {
-        console.log("Register key transaction signature: ", txSignature2);
+        txSignature = await config.program.methods
+            .registerKeyV2(
+                DEFAULT_INSTANCE,
+                key,
+                new anchor.BN(validFrom),
+                new anchor.BN(validUntil)
+            )
+            .accounts({
+                ...accounts,
+                keyMapping: getKeyMappingPda(key, config.program.programId),
+            })
+            .rpc();
+        console.log("Successfully registered key");
+        console.log("Register key transaction signature: ", txSignature);
     }
 }
 
@@ -1,3 +1,5 @@
+import * as anchor from "@coral-xyz/anchor";
+
 const DEFAULT_ADMIN_ROLE = new Array(32).fill(0);
 const KEY_MANAGER_ROLE = [
     27, 30, 232, 100, 197, 54, 57, 215, 70, 43, 119, 63, 124, 139, 76, 234, 20,
@@ -11,10 +13,29 @@ const OPERATOR_ROLE = [
     151, 102, 112, 112, 197, 78, 241, 130, 176, 245, 133, 139, 3, 75, 234, 193,
     182, 243, 8, 154, 162, 211, 24, 139, 177, 232, 146, 159, 79, 169, 185, 41,
 ];
+const PROPOSER_ROLE = [
+    176, 154, 165, 174, 179, 112, 44, 253, 80, 182, 182, 43, 196, 83, 38, 4,
+    147, 143, 33, 36, 138, 39, 161, 213, 202, 115, 96, 130, 182, 129, 156, 193,
+];
+const CANCELLER_ROLE = [
+    253, 100, 60, 114, 113, 12, 99, 192, 24, 2, 89, 171, 166, 178, 208, 84, 81,
+    227, 89, 26, 36, 229, 139, 98, 35, 147, 120, 8, 87, 38, 247, 131,
+];
+const APPROVER_ROLE = [
+    64, 138, 54, 21, 31, 132, 23, 9, 17, 106, 78, 138, 202, 78, 2, 2, 135, 79,
+    127, 84, 104, 125, 203, 134, 59, 30, 164, 103, 45, 201, 216, 207,
+];
+
+// Instance whose accounts use the seeds without an instance
+const DEFAULT_INSTANCE = anchor.web3.PublicKey.default;
 
 export {
+    DEFAULT_INSTANCE,
     DEFAULT_ADMIN_ROLE,
     KEY_MANAGER_ROLE,
     BLACKLIST_MANAGER_ROLE,
     OPERATOR_ROLE,
+    PROPOSER_ROLE,
+    CANCELLER_ROLE,
+    APPROVER_ROLE,
 };
@@ -6,6 +6,9 @@ import {
     KEY_MANAGER_ROLE,
     BLACKLIST_MANAGER_ROLE,
     OPERATOR_ROLE,
+    PROPOSER_ROLE,
+    CANCELLER_ROLE,
+    APPROVER_ROLE,
 } from "./constants";
 
 const hash = new Keccak(256);
@@ -16,6 +19,12 @@ const getProgramStatePda = (programId: anchor.web3.PublicKey) =>
         programId
     )[0];
 
+const getProgramDataPda = (programId: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+        [programId.toBuffer()],
+        new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    )[0];
+
 const getDefaultAdminRolePda = (
     programId: anchor.web3.PublicKey,
     user: anchor.web3.PublicKey
@@ -52,6 +61,58 @@ const getOperatorRolePda = (
         programId
     )[0];
 
+const getProposerRolePda = (
+    programId: anchor.web3.PublicKey,
+    user: anchor.web3.PublicKey
+) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(PROPOSER_ROLE), user.toBuffer()],
+        programId
+    )[0];
+
+const getCancellerRolePda = (
+    programId: anchor.web3.PublicKey,
+    user: anchor.web3.PublicKey
+) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(CANCELLER_ROLE), user.toBuffer()],
+        programId
+    )[0];
+
+const getApproverRolePda = (
+    programId: anchor.web3.PublicKey,
+    user: anchor.web3.PublicKey
+) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from(APPROVER_ROLE), user.toBuffer()],
+        programId
+    )[0];
+
+const getRoleMembersPda = (
+    programId: anchor.web3.PublicKey,
+    role: Buffer<ArrayBufferLike>
+) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("keyring_program"), Buffer.from("role_members"), role],
+        programId
+    )[0];
+
+const getRoleDefinitionPda = (
+    programId: anchor.web3.PublicKey,
+    role: Buffer<ArrayBufferLike>
+) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("keyring_program"), Buffer.from("role_definition"), role],
+        programId
+    )[0];
+
+// Signer of executed timelock proposals
+const getTimelockPda = (programId: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("keyring_program"), Buffer.from("timelock")],
+        programId
+    )[0];
+
 const getKeyRegistryPda = (programId: anchor.web3.PublicKey) =>
     anchor.web3.PublicKey.findProgramAddressSync(
         [Buffer.from("keyring_program"), Buffer.from("active_keys")],
@@ -88,10 +149,17 @@ const getEntityMappingPda = (
 
 export {
     getProgramStatePda,
+    getProgramDataPda,
     getDefaultAdminRolePda,
     getKeyManagerRolePda,
     getBlacklistManagerRolePda,
     getOperatorRolePda,
+    getProposerRolePda,
+    getCancellerRolePda,
+    getApproverRolePda,
+    getRoleMembersPda,
+    getRoleDefinitionPda,
+    getTimelockPda,
     getKeyRegistryPda,
     getKeyMappingPda,
     getEntityMappingPda,
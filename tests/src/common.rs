@@ -1,18 +1,90 @@
 use anchor_client::anchor_lang::prelude::{Clock, Pubkey, System};
 use anchor_client::anchor_lang::Id;
 use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::RpcTransactionConfig;
+use anchor_client::solana_sdk::bpf_loader_upgradeable;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::instruction::InstructionError;
 use anchor_client::solana_sdk::keccak;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
 use anchor_client::solana_sdk::secp256k1_recover::Secp256k1Pubkey;
-use anchor_client::solana_sdk::signature::{Keypair, Signer};
+use anchor_client::solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
 use anchor_client::solana_sdk::sysvar::clock;
-use anchor_client::Program;
+use anchor_client::solana_sdk::transaction::TransactionError;
+use anchor_client::{ClientError, Program};
+use keyring_indexer::events::KeyringEvent;
+use keyring_indexer::logs::parse_logs;
+use keyring_network::common::error::KeyringError;
 use keyring_network::common::types::{
-    ProgramState, Role, CHAIN_ID_MAX_SIZE, CHAIN_ID_MIN_SIZE, DEFAULT_ADMIN_ROLE,
+    instance_seed, AttestationFormat, ChainId, ProgramState, Role, ToHash, CHAIN_ID_MAX_SIZE,
+    CHAIN_ID_MIN_SIZE, DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE,
 };
+use keyring_network::common::verify_auth_message::{
+    create_attestation_payload, create_signature_payload,
+};
+use libsecp256k1::{sign, Message, SecretKey};
 use rand::RngCore;
 use std::thread::sleep;
 use std::time::Duration;
 
+pub fn get_program_data(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+pub fn initialize_accounts(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    signer: &Pubkey,
+) -> keyring_network::accounts::Initialize {
+    keyring_network::accounts::Initialize {
+        program_state: Pubkey::find_program_address(
+            &[
+                b"keyring_program".as_ref(),
+                b"global_state".as_ref(),
+                instance_seed(instance),
+            ],
+            program_id,
+        )
+        .0,
+        key_registry: Pubkey::find_program_address(
+            &[
+                b"keyring_program".as_ref(),
+                b"active_keys".as_ref(),
+                instance_seed(instance),
+            ],
+            program_id,
+        )
+        .0,
+        default_admin_role: get_role_account(program_id, instance, &DEFAULT_ADMIN_ROLE, signer),
+        default_admin_role_members: get_role_members(program_id, instance, &DEFAULT_ADMIN_ROLE),
+        signer: *signer,
+        program_data: Some(get_program_data(program_id)),
+        system_program: System::id(),
+    }
+}
+
+// `anchor test` deploys the program as upgradeable with the provider wallet
+// as upgrade authority
+pub fn get_upgrade_authority() -> Keypair {
+    let wallet = std::env::var("ANCHOR_WALLET").expect("ANCHOR_WALLET must be set");
+    read_keypair_file(wallet).expect("Provider wallet must be readable")
+}
+
+// Hands the program over to `new_authority`, which may then initialize it
+pub fn set_upgrade_authority(program: &Program<&Keypair>, new_authority: &Pubkey) {
+    let upgrade_authority = get_upgrade_authority();
+    program
+        .request()
+        .instruction(bpf_loader_upgradeable::set_upgrade_authority(
+            &program.id(),
+            &upgrade_authority.pubkey(),
+            Some(new_authority),
+        ))
+        .signer(&upgrade_authority)
+        .send()
+        .expect("Upgrade authority must be able to hand over the program");
+}
+
 pub fn init_program(
     program: &Program<&Keypair>,
     payer: &Keypair,
@@ -21,34 +93,24 @@ pub fn init_program(
     // We need to wait a bit for validator to start
     sleep(Duration::from_secs(3));
 
-    let (program_state, _) = Pubkey::find_program_address(
-        &[b"keyring_program".as_ref(), b"global_state".as_ref()],
-        &program.id(),
-    );
-    let (key_registry, _) = Pubkey::find_program_address(
-        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
-        &program.id(),
-    );
-    let (default_admin_role, _) = Pubkey::find_program_address(
-        &[
-            DEFAULT_ADMIN_ROLE.as_ref(),
-            payer.pubkey().to_bytes().as_ref(),
-        ],
-        &program.id(),
-    );
+    let accounts = initialize_accounts(&program.id(), &DEFAULT_INSTANCE, &payer.pubkey());
+    let program_state = accounts.program_state;
+    let default_admin_role = accounts.default_admin_role;
+
+    // Only the upgrade authority may initialize
+    set_upgrade_authority(program, &payer.pubkey());
 
     // Initialization with invalid chain id should not work.
     let invalid_chain_id = vec![1; CHAIN_ID_MAX_SIZE + 1];
     program
         .request()
-        .accounts(keyring_network::accounts::Initialize {
-            program_state: program_state.clone(),
-            key_registry: key_registry.clone(),
-            signer: payer.pubkey(),
-            default_admin_role,
-            system_program: System::id(),
-        })
-        .args(keyring_network::instruction::Initialize {
+        .accounts(initialize_accounts(
+            &program.id(),
+            &DEFAULT_INSTANCE,
+            &payer.pubkey(),
+        ))
+        .args(keyring_network::instruction::InitializeV2 {
+            instance: DEFAULT_INSTANCE,
             chain_id: invalid_chain_id,
         })
         .send()
@@ -57,14 +119,13 @@ pub fn init_program(
     let invalid_chain_id = vec![1; CHAIN_ID_MIN_SIZE - 1];
     program
         .request()
-        .accounts(keyring_network::accounts::Initialize {
-            program_state: program_state.clone(),
-            key_registry: key_registry.clone(),
-            signer: payer.pubkey(),
-            default_admin_role,
-            system_program: System::id(),
-        })
-        .args(keyring_network::instruction::Initialize {
+        .accounts(initialize_accounts(
+            &program.id(),
+            &DEFAULT_INSTANCE,
+            &payer.pubkey(),
+        ))
+        .args(keyring_network::instruction::InitializeV2 {
+            instance: DEFAULT_INSTANCE,
             chain_id: invalid_chain_id,
         })
         .send()
@@ -73,14 +134,13 @@ pub fn init_program(
     // First initialization should be successful
     program
         .request()
-        .accounts(keyring_network::accounts::Initialize {
-            program_state: program_state.clone(),
-            key_registry: key_registry.clone(),
-            signer: payer.pubkey(),
-            default_admin_role,
-            system_program: System::id(),
-        })
-        .args(keyring_network::instruction::Initialize {
+        .accounts(initialize_accounts(
+            &program.id(),
+            &DEFAULT_INSTANCE,
+            &payer.pubkey(),
+        ))
+        .args(keyring_network::instruction::InitializeV2 {
+            instance: DEFAULT_INSTANCE,
             chain_id: chain_id.clone(),
         })
         .send()
@@ -89,14 +149,13 @@ pub fn init_program(
     // Second initialization should return an error
     program
         .request()
-        .accounts(keyring_network::accounts::Initialize {
-            program_state: program_state.clone(),
-            key_registry: key_registry.clone(),
-            signer: payer.pubkey(),
-            default_admin_role,
-            system_program: System::id(),
-        })
-        .args(keyring_network::instruction::Initialize {
+        .accounts(initialize_accounts(
+            &program.id(),
+            &DEFAULT_INSTANCE,
+            &payer.pubkey(),
+        ))
+        .args(keyring_network::instruction::InitializeV2 {
+            instance: DEFAULT_INSTANCE,
             chain_id: chain_id.clone(),
         })
         .send()
@@ -104,25 +163,98 @@ pub fn init_program(
 
     // We need to check if admin is set to payer
     let default_admin_role_data: Role = program
-        .account(default_admin_role.clone())
+        .account(default_admin_role)
         .expect("Default admin role must be granted after initialization");
     if !default_admin_role_data.has_role {
         panic!("Payer must have admin role");
     }
 
     let program_state_data: ProgramState = program
-        .account(program_state.clone())
+        .account(program_state)
         .expect("Program state must exist after initialization");
 
     (program_state, program_state_data, default_admin_role)
 }
 
+// Grants `role` to `user`, or revokes it if `has_role` is false, signed by a
+// member of `admin_role`
+#[allow(clippy::too_many_arguments)]
+pub fn manage_role(
+    program: &Program<&Keypair>,
+    instance: Pubkey,
+    signer: &Keypair,
+    admin_role: [u8; 32],
+    role: [u8; 32],
+    user: Pubkey,
+    has_role: bool,
+    expires_at: Option<i64>,
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRole {
+            role_definition: get_role_definition(&program.id(), &instance, &role),
+            admin_role: get_role_account(&program.id(), &instance, &admin_role, &signer.pubkey()),
+            role: get_role_account(&program.id(), &instance, &role, &user),
+            role_members: get_role_members(&program.id(), &instance, &role),
+            signer: signer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance,
+            role,
+            user,
+            has_role,
+            expires_at,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+// Grants `role` to `user` in the default instance without expiry, signed by a
+// default admin
+pub fn grant_role(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    role: [u8; 32],
+    user: Pubkey,
+) -> Result<(), Box<ClientError>> {
+    manage_role(
+        program,
+        DEFAULT_INSTANCE,
+        signer,
+        DEFAULT_ADMIN_ROLE,
+        role,
+        user,
+        true,
+        None,
+    )
+}
+
 pub fn get_timestamp(rpc: &RpcClient) -> u64 {
     let clock = rpc.get_account(&clock::ID).unwrap();
     let clock_sysvar: Clock = bincode::deserialize(&clock.data).unwrap();
     clock_sysvar.unix_timestamp.try_into().unwrap()
 }
 
+// Slot of a confirmed transaction and the events the program emitted in it
+pub fn get_events(rpc: &RpcClient, signature: &Signature) -> (u64, Vec<KeyringEvent>) {
+    let transaction = rpc
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: None,
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .unwrap();
+    let logs: Option<Vec<String>> = transaction.transaction.meta.unwrap().log_messages.into();
+    let events = parse_logs(&keyring_network::ID, &logs.unwrap()).unwrap();
+    (transaction.slot, events)
+}
+
 pub fn convert_pubkey_to_address(pubkey: &Pubkey) -> Vec<u8> {
     let hashed_pubkey = keccak::hash(&pubkey.to_bytes()).to_bytes();
     hashed_pubkey[..20].to_vec()
@@ -142,3 +274,339 @@ pub fn generate_random_chain_id<R: RngCore>(rng: &mut R) -> Vec<u8> {
     rng.fill_bytes(&mut chain_id_bytes);
     chain_id_bytes
 }
+
+pub fn get_key_mapping(program: &Pubkey, key: &[u8]) -> Pubkey {
+    let key_hash = key.to_vec().to_hash();
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"_key_mapping".as_ref(),
+            key_hash.as_ref(),
+        ],
+        program,
+    )
+    .0
+}
+
+// Address of the account with `seeds` in `instance`
+pub fn instance_address(program_id: &Pubkey, seeds: &[&[u8]], instance: &Pubkey) -> Pubkey {
+    let mut seeds = seeds.to_vec();
+    seeds.push(instance_seed(instance));
+    Pubkey::find_program_address(&seeds, program_id).0
+}
+
+pub fn get_role_account(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    role: &[u8; 32],
+    user: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[role.as_ref(), user.as_ref(), instance_seed(instance)],
+        program_id,
+    )
+    .0
+}
+
+pub fn get_entity_mapping(
+    program_id: &Pubkey,
+    instance: &Pubkey,
+    policy_id: u64,
+    trading_address: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"_entity_mapping".as_ref(),
+            &policy_id.to_le_bytes(),
+            trading_address.as_ref(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn get_role_members(program_id: &Pubkey, instance: &Pubkey, role: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"role_members".as_ref(),
+            role.as_ref(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn get_role_definition(program_id: &Pubkey, instance: &Pubkey, role: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"role_definition".as_ref(),
+            role.as_ref(),
+            instance_seed(instance),
+        ],
+        program_id,
+    )
+    .0
+}
+
+// Ethereum style 65 byte signature of an already hashed payload
+pub fn sign_payload(secret_key: &SecretKey, payload: &[u8]) -> Vec<u8> {
+    let message = Message::parse_slice(payload).unwrap();
+    let (signature, recovery_id) = sign(&message, secret_key);
+    let mut serialized_signature = signature.serialize().to_vec();
+    serialized_signature.push(recovery_id.serialize() + 27u8);
+    serialized_signature
+}
+
+pub fn get_policy_config(program: &Pubkey, policy_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"_policy_config".as_ref(),
+            &policy_id.to_le_bytes(),
+        ],
+        program,
+    )
+    .0
+}
+
+// Signs a credential for a fresh trading address with `secret_key` and
+// submits it, paid by the program payer.
+pub fn create_credential(
+    program: &Program<&Keypair>,
+    program_state: Pubkey,
+    chain_id: &[u8],
+    secret_key: &SecretKey,
+    policy_id: u64,
+    valid_until: u64,
+) -> Result<(), Box<ClientError>> {
+    let key = libsecp256k1::PublicKey::from_secret_key(secret_key).serialize()[1..].to_vec();
+    create_credential_with_key(
+        program,
+        program_state,
+        chain_id,
+        secret_key,
+        key,
+        policy_id,
+        valid_until,
+    )
+}
+
+// Same as `create_credential`, passing `key` as the registered form of the
+// signing key (public key or address).
+pub fn create_credential_with_key(
+    program: &Program<&Keypair>,
+    program_state: Pubkey,
+    chain_id: &[u8],
+    secret_key: &SecretKey,
+    key: Vec<u8>,
+    policy_id: u64,
+    valid_until: u64,
+) -> Result<(), Box<ClientError>> {
+    submit_credential(
+        program,
+        DEFAULT_INSTANCE,
+        program_state,
+        chain_id,
+        secret_key,
+        &key,
+        policy_id,
+        Pubkey::new_unique(),
+        valid_until,
+    )
+}
+
+// Cost and backdoor of the credentials submitted by `submit_credential`
+pub const CREDENTIAL_COST: u64 = LAMPORTS_PER_SOL;
+pub const CREDENTIAL_BACKDOOR: [u8; 20] = [2; 20];
+
+// Signs a packed credential for `trading_address` in `instance`
+pub fn sign_credential(
+    instance: Pubkey,
+    chain_id: &[u8],
+    secret_key: &SecretKey,
+    policy_id: u64,
+    trading_address: Pubkey,
+    valid_until: u64,
+) -> Vec<u8> {
+    let payload = create_attestation_payload(
+        AttestationFormat::Packed,
+        &instance,
+        &trading_address,
+        policy_id,
+        ChainId::new(chain_id.to_vec()).unwrap(),
+        valid_until,
+        CREDENTIAL_COST,
+        CREDENTIAL_BACKDOOR.to_vec(),
+    )
+    .unwrap();
+    sign_payload(secret_key, payload.as_ref())
+}
+
+// Submits a packed credential for `trading_address` in `instance`, signed
+// with `secret_key` whose registered form is `key`.
+#[allow(clippy::too_many_arguments)]
+pub fn submit_credential(
+    program: &Program<&Keypair>,
+    instance: Pubkey,
+    program_state: Pubkey,
+    chain_id: &[u8],
+    secret_key: &SecretKey,
+    key: &[u8],
+    policy_id: u64,
+    trading_address: Pubkey,
+    valid_until: u64,
+) -> Result<(), Box<ClientError>> {
+    let signature = sign_credential(
+        instance,
+        chain_id,
+        secret_key,
+        policy_id,
+        trading_address,
+        valid_until,
+    );
+    submit_signed_credential(
+        program,
+        instance,
+        program_state,
+        key,
+        policy_id,
+        trading_address,
+        valid_until,
+        signature,
+    )
+}
+
+// Submits a credential signed with `sign_credential`, possibly for another
+// instance
+#[allow(clippy::too_many_arguments)]
+pub fn submit_signed_credential(
+    program: &Program<&Keypair>,
+    instance: Pubkey,
+    program_state: Pubkey,
+    key: &[u8],
+    policy_id: u64,
+    trading_address: Pubkey,
+    valid_until: u64,
+    signature: Vec<u8>,
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::CreateCredential {
+            program_state,
+            key_mapping: instance_address(
+                &program.id(),
+                &[
+                    b"keyring_program",
+                    b"_key_mapping",
+                    key.to_vec().to_hash().as_ref(),
+                ],
+                &instance,
+            ),
+            policy_config: instance_address(
+                &program.id(),
+                &[
+                    b"keyring_program",
+                    b"_policy_config",
+                    &policy_id.to_le_bytes(),
+                ],
+                &instance,
+            ),
+            signer: program.payer(),
+            entity_mapping: get_entity_mapping(
+                &program.id(),
+                &instance,
+                policy_id,
+                &trading_address,
+            ),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance,
+            key: key.to_vec(),
+            policy_id,
+            trading_address,
+            signature,
+            valid_until,
+            cost: CREDENTIAL_COST,
+            backdoor: CREDENTIAL_BACKDOOR.to_vec(),
+            format: AttestationFormat::Packed,
+        })
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+// Same as `create_credential` through `create_credential_by_signer`, which
+// recovers the key from the signature and validates `key_mapping` against it.
+pub fn create_credential_by_signer(
+    program: &Program<&Keypair>,
+    program_state: Pubkey,
+    chain_id: &[u8],
+    secret_key: &SecretKey,
+    key_mapping: Pubkey,
+    policy_id: u64,
+    valid_until: u64,
+) -> Result<(), Box<ClientError>> {
+    let trading_address = Pubkey::new_unique();
+    let cost = LAMPORTS_PER_SOL;
+    let backdoor = vec![2; 20];
+    let entity_mapping = get_entity_mapping(
+        &program.id(),
+        &DEFAULT_INSTANCE,
+        policy_id,
+        &trading_address,
+    );
+
+    let packed_message = create_signature_payload(
+        convert_pubkey_to_address(&trading_address),
+        policy_id,
+        ChainId::new(chain_id.to_vec()).unwrap(),
+        valid_until,
+        cost,
+        backdoor.clone(),
+    )
+    .unwrap();
+    let serialized_signature = sign_payload(secret_key, packed_message.as_ref());
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::CreateCredentialBySigner {
+            program_state,
+            signer: program.payer(),
+            key_mapping,
+            policy_config: get_policy_config(&program.id(), policy_id),
+            entity_mapping,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::CreateCredentialBySigner {
+            instance: DEFAULT_INSTANCE,
+            policy_id,
+            trading_address,
+            signature: serialized_signature,
+            valid_until,
+            cost,
+            backdoor,
+            format: AttestationFormat::Packed,
+        })
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+// Asserts that the transaction was rejected by the program with `expected`
+pub fn assert_program_error(result: Result<(), Box<ClientError>>, expected: KeyringError) {
+    let err = result.expect_err(&format!("Transaction must fail with {}", expected.name()));
+    let code = match *err {
+        ClientError::SolanaClientError(ref err) => match err.get_transaction_error() {
+            Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => code,
+            other => panic!("Unexpected transaction error {:?}", other),
+        },
+        other => panic!("Unexpected client error {:?}", other),
+    };
+    assert_eq!(code, u32::from(expected), "Expected {}", expected.name());
+}
@@ -1,21 +1,61 @@
 #[cfg(test)]
 mod common;
 #[cfg(test)]
+mod test_attestation_formats;
+#[cfg(test)]
+mod test_batch_blacklist;
+#[cfg(test)]
 mod test_blacklist_entity;
 #[cfg(test)]
 mod test_collect_fees;
 #[cfg(test)]
+mod test_create_credential_by_signer;
+#[cfg(test)]
 mod test_create_credentials;
 #[cfg(test)]
+mod test_credential_errors;
+#[cfg(test)]
+mod test_indexer;
+#[cfg(test)]
 mod test_initialize;
 #[cfg(test)]
+mod test_instances;
+#[cfg(test)]
+mod test_key_policy_scope;
+#[cfg(test)]
+mod test_key_quota;
+#[cfg(test)]
+mod test_legacy_instructions;
+#[cfg(test)]
 mod test_manage_roles;
 #[cfg(test)]
+mod test_proposal_approvals;
+#[cfg(test)]
 mod test_register_key;
 #[cfg(test)]
+mod test_register_key_address;
+#[cfg(test)]
 mod test_revoke_key;
 #[cfg(test)]
+mod test_role_definitions;
+#[cfg(test)]
+mod test_role_expiry;
+#[cfg(test)]
+mod test_role_members;
+#[cfg(test)]
+mod test_rotate_key;
+#[cfg(test)]
+mod test_temporary_blacklist;
+#[cfg(test)]
+mod test_threshold_credential;
+#[cfg(test)]
+mod test_timelock;
+#[cfg(test)]
 mod test_unblacklist_entity;
+#[cfg(test)]
+mod test_update_config;
+#[cfg(test)]
+mod test_update_key_validity;
 
 #[cfg(test)]
 mod test_check_credential;
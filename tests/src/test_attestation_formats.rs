@@ -0,0 +1,223 @@
+use crate::common::{
+    generate_random_chain_id, get_key_mapping, get_policy_config, get_role_definition,
+    get_role_members, get_timestamp, init_program, sign_payload,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program,
+};
+use keyring_network::common::types::{
+    AttestationFormat, Blacklisting, ChainId, EntityData, CURRENT_VERSION, DEFAULT_INSTANCE,
+    KEY_MANAGER_ROLE,
+};
+use keyring_network::common::verify_auth_message::create_attestation_payload;
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+
+// Submits a credential signed over the payload in `signed_format` while
+// claiming `format` in the instruction.
+fn create_credential(
+    program: &Program<&Keypair>,
+    program_state: Pubkey,
+    chain_id: &[u8],
+    secret_key: &SecretKey,
+    trading_address: Pubkey,
+    signed_format: AttestationFormat,
+    format: AttestationFormat,
+) -> Result<(), Box<ClientError>> {
+    let key = libsecp256k1::PublicKey::from_secret_key(secret_key).serialize()[1..].to_vec();
+    // Out of the range of the packed format, the expiry is a far-future
+    // sentinel
+    let policy_id: u64 = 1 << 24;
+    let valid_until = u64::MAX;
+    let cost = LAMPORTS_PER_SOL;
+    let backdoor = vec![2; 20];
+    let (entity_mapping, _) = Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"_entity_mapping".as_ref(),
+            &policy_id.to_le_bytes(),
+            &trading_address.to_bytes(),
+        ],
+        &program.id(),
+    );
+
+    let payload = create_attestation_payload(
+        signed_format,
+        &DEFAULT_INSTANCE,
+        &trading_address,
+        policy_id,
+        ChainId::new(chain_id.to_vec()).unwrap(),
+        valid_until,
+        cost,
+        backdoor.clone(),
+    )
+    .unwrap();
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::CreateCredential {
+            program_state,
+            key_mapping: get_key_mapping(&program.id(), &key),
+            policy_config: get_policy_config(&program.id(), policy_id),
+            signer: program.payer(),
+            entity_mapping,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
+            key,
+            policy_id,
+            trading_address,
+            signature: sign_payload(secret_key, payload.as_ref()),
+            valid_until,
+            cost,
+            backdoor,
+            format,
+        })
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+#[test]
+fn attestation_formats() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (program_state_pubkey, _, default_admin_role_pubkey) =
+        init_program(&program, &payer, chain_id.clone());
+
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_admin, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRole {
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
+            role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
+            role: KEY_MANAGER_ROLE,
+            user: payer.pubkey(),
+            has_role: true,
+            expires_at: None,
+        })
+        .send()
+        .expect("Current admin must be able to grant key manager role");
+
+    let secret_key = SecretKey::random(&mut rng);
+    let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+    let timestamp = get_timestamp(&rpc);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: get_key_mapping(&program.id(), &key),
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key,
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect("Valid key registration must be successful");
+
+    for format in [AttestationFormat::Eip712, AttestationFormat::PackedExtended] {
+        let trading_address = Pubkey::new_unique();
+        create_credential(
+            &program,
+            program_state_pubkey,
+            &chain_id,
+            &secret_key,
+            trading_address,
+            format,
+            AttestationFormat::Packed,
+        )
+        .expect_err("Out of range parameters must be rejected in the packed format");
+        let other_format = if format == AttestationFormat::Eip712 {
+            AttestationFormat::PackedExtended
+        } else {
+            AttestationFormat::Eip712
+        };
+        create_credential(
+            &program,
+            program_state_pubkey,
+            &chain_id,
+            &secret_key,
+            trading_address,
+            format,
+            other_format,
+        )
+        .expect_err("Signature must not verify in another format");
+        create_credential(
+            &program,
+            program_state_pubkey,
+            &chain_id,
+            &secret_key,
+            trading_address,
+            format,
+            format,
+        )
+        .expect("Signature in the claimed format must be accepted");
+
+        let entity_data: EntityData = program
+            .account(
+                Pubkey::find_program_address(
+                    &[
+                        b"keyring_program".as_ref(),
+                        b"_entity_mapping".as_ref(),
+                        &(1u64 << 24).to_le_bytes(),
+                        &trading_address.to_bytes(),
+                    ],
+                    &program.id(),
+                )
+                .0,
+            )
+            .unwrap();
+        assert_eq!(
+            entity_data,
+            EntityData {
+                version: CURRENT_VERSION,
+                blacklisted: false,
+                exp: u64::MAX,
+                blacklisting: Blacklisting::default(),
+            }
+        );
+    }
+}
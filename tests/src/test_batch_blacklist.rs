@@ -0,0 +1,309 @@
+use crate::common::{
+    assert_program_error, generate_random_chain_id, get_entity_mapping, get_role_account,
+    get_timestamp, grant_role, init_program,
+};
+use anchor_client::anchor_lang::prelude::{AccountMeta, System};
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::solana_sdk::transaction::Transaction;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program, RequestBuilder,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    BlacklistEntry, BlacklistReason, Blacklisting, EntityData, BLACKLIST_MANAGER_ROLE,
+    CURRENT_VERSION, DEFAULT_INSTANCE, MAX_BLACKLIST_BATCH_SIZE,
+};
+use keyring_network::ID as program_id;
+use rand::rngs::OsRng;
+
+fn entity_mappings(entries: &[BlacklistEntry]) -> Vec<AccountMeta> {
+    entries
+        .iter()
+        .map(|entry| {
+            let entity_mapping = get_entity_mapping(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                entry.policy_id,
+                &entry.trading_address,
+            );
+            AccountMeta::new(entity_mapping, false)
+        })
+        .collect()
+}
+
+// Compute unit limit of an instruction without a compute budget instruction
+const DEFAULT_INSTRUCTION_COMPUTE_UNITS: u64 = 200_000;
+
+fn batch_blacklist_entities(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    entries: Vec<BlacklistEntry>,
+    entity_mappings: Vec<AccountMeta>,
+    reason: BlacklistReason,
+    until: Option<u64>,
+) -> Result<(), Box<ClientError>> {
+    batch_blacklist_request(program, signer, entries, entity_mappings, reason, until)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn batch_blacklist_request<'a>(
+    program: &'a Program<&'a Keypair>,
+    signer: &'a Keypair,
+    entries: Vec<BlacklistEntry>,
+    entity_mappings: Vec<AccountMeta>,
+    reason: BlacklistReason,
+    until: Option<u64>,
+) -> RequestBuilder<'a, &'a Keypair> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::BatchBlacklistEntities {
+            signer: signer.pubkey(),
+            blacklist_manager_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &BLACKLIST_MANAGER_ROLE,
+                &signer.pubkey(),
+            ),
+            system_program: System::id(),
+        })
+        .accounts(entity_mappings)
+        .args(keyring_network::instruction::BatchBlacklistEntities {
+            instance: DEFAULT_INSTANCE,
+            entries,
+            reason,
+            until,
+        })
+        .signer(signer)
+}
+
+fn batch_unblacklist_entities(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    entries: Vec<BlacklistEntry>,
+    entity_mappings: Vec<AccountMeta>,
+) -> Result<(), Box<ClientError>> {
+    batch_unblacklist_request(program, signer, entries, entity_mappings)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn batch_unblacklist_request<'a>(
+    program: &'a Program<&'a Keypair>,
+    signer: &'a Keypair,
+    entries: Vec<BlacklistEntry>,
+    entity_mappings: Vec<AccountMeta>,
+) -> RequestBuilder<'a, &'a Keypair> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::BatchUnblacklistEntities {
+            signer: signer.pubkey(),
+            blacklist_manager_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &BLACKLIST_MANAGER_ROLE,
+                &signer.pubkey(),
+            ),
+            system_program: System::id(),
+        })
+        .accounts(entity_mappings)
+        .args(keyring_network::instruction::BatchUnblacklistEntities {
+            instance: DEFAULT_INSTANCE,
+            entries,
+        })
+        .signer(signer)
+}
+
+// Compute units consumed by the request, simulated as sent by `signer`
+// without a compute budget instruction
+fn simulated_units(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    request: RequestBuilder<&Keypair>,
+) -> u64 {
+    let rpc = program.rpc();
+    let transaction = Transaction::new_signed_with_payer(
+        &request.instructions().unwrap(),
+        Some(&signer.pubkey()),
+        &[signer],
+        rpc.get_latest_blockhash().unwrap(),
+    );
+    let simulation = rpc.simulate_transaction(&transaction).unwrap().value;
+    assert!(simulation.err.is_none(), "{:?}", simulation.logs);
+    simulation.units_consumed.unwrap()
+}
+
+#[test]
+fn test_batch_blacklist() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    let mut rng = OsRng;
+    init_program(&program, &payer, generate_random_chain_id(&mut rng));
+
+    let entries: Vec<BlacklistEntry> = (1..=4)
+        .map(|policy_id| BlacklistEntry {
+            policy_id,
+            trading_address: Pubkey::new_unique(),
+        })
+        .collect();
+
+    // Signers without the blacklist manager role cannot blacklist
+    assert!(batch_blacklist_entities(
+        &program,
+        &payer,
+        entries.clone(),
+        entity_mappings(&entries),
+        BlacklistReason::Sanctions,
+        None,
+    )
+    .is_err());
+
+    grant_role(&program, &payer, BLACKLIST_MANAGER_ROLE, payer.pubkey())
+        .expect("Current admin must be able to grant role");
+
+    assert_program_error(
+        batch_blacklist_entities(
+            &program,
+            &payer,
+            vec![],
+            vec![],
+            BlacklistReason::Sanctions,
+            None,
+        ),
+        KeyringError::ErrInvalidBatchSize,
+    );
+
+    // Every entry needs its entity mapping, in the same order
+    assert_program_error(
+        batch_blacklist_entities(
+            &program,
+            &payer,
+            entries.clone(),
+            entity_mappings(&entries[1..]),
+            BlacklistReason::Sanctions,
+            None,
+        ),
+        KeyringError::ErrEntityMappingMismatch,
+    );
+    let mut swapped = entity_mappings(&entries);
+    swapped.swap(0, 1);
+    assert_program_error(
+        batch_blacklist_entities(
+            &program,
+            &payer,
+            entries.clone(),
+            swapped,
+            BlacklistReason::Sanctions,
+            None,
+        ),
+        KeyringError::ErrEntityMappingMismatch,
+    );
+
+    let until = get_timestamp(&anchor_rpc_client) + 1000;
+    assert_program_error(
+        batch_blacklist_entities(
+            &program,
+            &payer,
+            entries.clone(),
+            entity_mappings(&entries),
+            BlacklistReason::FraudReview,
+            Some(until - 2000),
+        ),
+        KeyringError::ErrInvalidBlacklistExpiry,
+    );
+
+    batch_blacklist_entities(
+        &program,
+        &payer,
+        entries.clone(),
+        entity_mappings(&entries),
+        BlacklistReason::FraudReview,
+        Some(until),
+    )
+    .expect("Blacklist manager should be able to blacklist entities in a batch");
+    for entity_mapping in entity_mappings(&entries) {
+        let entity_data: EntityData = program.account(entity_mapping.pubkey).unwrap();
+        assert_eq!(
+            entity_data,
+            EntityData {
+                version: CURRENT_VERSION,
+                blacklisted: true,
+                exp: 0,
+                blacklisting: Blacklisting {
+                    reason: BlacklistReason::FraudReview,
+                    until: Some(until),
+                },
+            }
+        );
+    }
+
+    batch_unblacklist_entities(
+        &program,
+        &payer,
+        entries[..2].to_vec(),
+        entity_mappings(&entries[..2]),
+    )
+    .expect("Blacklist manager should be able to unblacklist entities in a batch");
+    for (index, entity_mapping) in entity_mappings(&entries).iter().enumerate() {
+        let entity_data: EntityData = program.account(entity_mapping.pubkey).unwrap();
+        assert_eq!(entity_data.blacklisted, index >= 2);
+    }
+
+    // A full batch fits a transaction and the default compute budget,
+    // creating every entity account
+    let entries: Vec<BlacklistEntry> = (0..MAX_BLACKLIST_BATCH_SIZE as u64)
+        .map(|policy_id| BlacklistEntry {
+            policy_id: u64::MAX - policy_id,
+            trading_address: Pubkey::new_unique(),
+        })
+        .collect();
+    let units = simulated_units(
+        &program,
+        &payer,
+        batch_blacklist_request(
+            &program,
+            &payer,
+            entries.clone(),
+            entity_mappings(&entries),
+            BlacklistReason::Sanctions,
+            Some(until),
+        ),
+    );
+    assert!(units <= DEFAULT_INSTRUCTION_COMPUTE_UNITS, "{units} units");
+    batch_blacklist_entities(
+        &program,
+        &payer,
+        entries.clone(),
+        entity_mappings(&entries),
+        BlacklistReason::Sanctions,
+        Some(until),
+    )
+    .expect("Blacklist manager should be able to blacklist a full batch");
+    let units = simulated_units(
+        &program,
+        &payer,
+        batch_unblacklist_request(&program, &payer, entries.clone(), entity_mappings(&entries)),
+    );
+    assert!(units <= DEFAULT_INSTRUCTION_COMPUTE_UNITS, "{units} units");
+    batch_unblacklist_entities(&program, &payer, entries.clone(), entity_mappings(&entries))
+        .expect("Blacklist manager should be able to unblacklist a full batch");
+    for entity_mapping in entity_mappings(&entries) {
+        let entity_data: EntityData = program.account(entity_mapping.pubkey).unwrap();
+        assert!(!entity_data.blacklisted);
+    }
+}
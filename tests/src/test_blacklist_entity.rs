@@ -1,4 +1,7 @@
-use crate::common::{generate_random_chain_id, init_program};
+use crate::common::{
+    generate_random_chain_id, get_events, get_role_definition, get_role_members, get_timestamp,
+    init_program,
+};
 use anchor_client::anchor_lang::prelude::System;
 use anchor_client::anchor_lang::Id;
 use anchor_client::solana_client::rpc_client::RpcClient;
@@ -9,7 +12,12 @@ use anchor_client::{
     solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
     Client, Cluster,
 };
-use keyring_network::common::types::{EntityData, BLACKLIST_MANAGER_ROLE, CURRENT_VERSION};
+use keyring_indexer::events::KeyringEvent;
+use keyring_network::common::types::{
+    BlacklistReason, Blacklisting, EntityData, BLACKLIST_MANAGER_ROLE, CURRENT_VERSION,
+    DEFAULT_INSTANCE,
+};
+use keyring_network::events::BlacklistedEntity;
 use keyring_network::ID as program_id;
 use rand::rngs::OsRng;
 
@@ -63,15 +71,23 @@ fn test_blacklist_entity() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &BLACKLIST_MANAGER_ROLE,
+            ),
+            admin_role: default_admin_role_pubkey,
             role: blacklist_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &BLACKLIST_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: BLACKLIST_MANAGER_ROLE,
             user: payer.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant blacklist manager role");
@@ -85,15 +101,19 @@ fn test_blacklist_entity() {
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::BlacklistEntity {
+        .args(keyring_network::instruction::BlacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
+            reason: BlacklistReason::Unspecified,
+            until: None,
         })
         .payer(&dummy_payer)
         .send()
         .expect_err("Non-blacklist manager should not be able to blacklist entity");
 
-    program
+    let timestamp = get_timestamp(&rpc) as i64;
+    let signature = program
         .request()
         .accounts(keyring_network::accounts::BlacklistEntity {
             signer: payer.pubkey(),
@@ -101,12 +121,37 @@ fn test_blacklist_entity() {
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::BlacklistEntity {
+        .args(keyring_network::instruction::BlacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
+            reason: BlacklistReason::Unspecified,
+            until: None,
         })
         .send()
         .expect("Blacklist manager should be able to blacklist entity");
+    let (slot, events) = get_events(&rpc, &signature);
+    match &events[..] {
+        [KeyringEvent::BlacklistedEntity(event)] => {
+            assert_eq!(
+                event,
+                &BlacklistedEntity {
+                    instance: DEFAULT_INSTANCE,
+                    policy_id,
+                    trading_address,
+                    signer: payer.pubkey(),
+                    previously_blacklisted: false,
+                    previous_exp: 0,
+                    reason: BlacklistReason::Unspecified,
+                    until: None,
+                    timestamp: event.timestamp,
+                    slot,
+                }
+            );
+            assert!(event.timestamp >= timestamp);
+        }
+        events => panic!("Unexpected events {:?}", events),
+    }
 
     let entity_data: EntityData = program.account(entity_mapping_pubkey).unwrap();
     assert_eq!(
@@ -115,11 +160,13 @@ fn test_blacklist_entity() {
             version: CURRENT_VERSION,
             blacklisted: true,
             exp: 0,
+            blacklisting: Blacklisting::default(),
         }
     );
 
     // No error must be thrown when we blacklist already blacklisted entity
-    program
+    let timestamp = get_timestamp(&rpc) as i64;
+    let signature = program
         .request()
         .accounts(keyring_network::accounts::BlacklistEntity {
             signer: payer.pubkey(),
@@ -127,12 +174,37 @@ fn test_blacklist_entity() {
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::BlacklistEntity {
+        .args(keyring_network::instruction::BlacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
+            reason: BlacklistReason::Unspecified,
+            until: None,
         })
         .send()
         .expect("Blacklist manager should be able to alredy blacklisted entity");
+    let (slot, events) = get_events(&rpc, &signature);
+    match &events[..] {
+        [KeyringEvent::BlacklistedEntity(event)] => {
+            assert_eq!(
+                event,
+                &BlacklistedEntity {
+                    instance: DEFAULT_INSTANCE,
+                    policy_id,
+                    trading_address,
+                    signer: payer.pubkey(),
+                    previously_blacklisted: true,
+                    previous_exp: 0,
+                    reason: BlacklistReason::Unspecified,
+                    until: None,
+                    timestamp: event.timestamp,
+                    slot,
+                }
+            );
+            assert!(event.timestamp >= timestamp);
+        }
+        events => panic!("Unexpected events {:?}", events),
+    }
 
     let entity_data: EntityData = program.account(entity_mapping_pubkey).unwrap();
     assert_eq!(
@@ -141,6 +213,7 @@ fn test_blacklist_entity() {
             version: CURRENT_VERSION,
             blacklisted: true,
             exp: 0,
+            blacklisting: Blacklisting::default(),
         }
     );
 }
@@ -1,5 +1,6 @@
 use crate::common::{
-    convert_pubkey_to_address, generate_random_chain_id, get_timestamp, init_program,
+    convert_pubkey_to_address, generate_random_chain_id, get_policy_config, get_role_definition,
+    get_role_members, get_timestamp, init_program,
 };
 use anchor_client::anchor_lang::prelude::System;
 use anchor_client::anchor_lang::Id;
@@ -12,7 +13,8 @@ use anchor_client::{
     Client, Cluster,
 };
 use keyring_network::common::types::{
-    ChainId, EntityData, ToHash, BLACKLIST_MANAGER_ROLE, CURRENT_VERSION, KEY_MANAGER_ROLE,
+    AttestationFormat, BlacklistReason, Blacklisting, ChainId, EntityData, ToHash,
+    BLACKLIST_MANAGER_ROLE, CURRENT_VERSION, DEFAULT_INSTANCE, KEY_MANAGER_ROLE,
 };
 use keyring_network::common::verify_auth_message::create_signature_payload;
 use keyring_network::ID as program_id;
@@ -75,15 +77,19 @@ fn test_check_credential() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
             role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: KEY_MANAGER_ROLE,
             user: payer.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant key manager role");
@@ -98,7 +104,8 @@ fn test_check_credential() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RegisterKey {
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             valid_from: timestamp - 1,
             valid_to: timestamp + 20,
@@ -141,11 +148,13 @@ fn test_check_credential() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -153,6 +162,7 @@ fn test_check_credential() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect("Valid create credentials request must succeed.");
@@ -164,7 +174,8 @@ fn test_check_credential() {
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
         })
-        .args(keyring_network::instruction::CheckCredential {
+        .args(keyring_network::instruction::CheckCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
         })
@@ -174,15 +185,23 @@ fn test_check_credential() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &BLACKLIST_MANAGER_ROLE,
+            ),
+            admin_role: default_admin_role_pubkey,
             role: blacklist_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &BLACKLIST_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: BLACKLIST_MANAGER_ROLE,
             user: payer.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant blacklist manager role");
@@ -195,9 +214,12 @@ fn test_check_credential() {
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::BlacklistEntity {
+        .args(keyring_network::instruction::BlacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
+            reason: BlacklistReason::Unspecified,
+            until: None,
         })
         .send()
         .expect("Admin should be able to blacklist entity");
@@ -209,6 +231,7 @@ fn test_check_credential() {
             version: CURRENT_VERSION,
             blacklisted: true,
             exp: 0,
+            blacklisting: Blacklisting::default(),
         }
     );
 
@@ -219,7 +242,8 @@ fn test_check_credential() {
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
         })
-        .args(keyring_network::instruction::CheckCredential {
+        .args(keyring_network::instruction::CheckCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
         })
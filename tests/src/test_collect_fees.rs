@@ -1,5 +1,6 @@
 use crate::common::{
-    convert_pubkey_to_address, generate_random_chain_id, get_timestamp, init_program,
+    convert_pubkey_to_address, generate_random_chain_id, get_policy_config, get_role_definition,
+    get_role_members, get_timestamp, init_program,
 };
 use anchor_client::anchor_lang::prelude::System;
 use anchor_client::anchor_lang::Id;
@@ -11,7 +12,9 @@ use anchor_client::{
     solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
     Client, Cluster,
 };
-use keyring_network::common::types::{ChainId, ToHash, KEY_MANAGER_ROLE, OPERATOR_ROLE};
+use keyring_network::common::types::{
+    AttestationFormat, ChainId, ToHash, DEFAULT_INSTANCE, KEY_MANAGER_ROLE, OPERATOR_ROLE,
+};
 use keyring_network::common::verify_auth_message::create_signature_payload;
 use keyring_network::ID as program_id;
 use libsecp256k1::{sign, Message};
@@ -70,15 +73,19 @@ fn collect_fees() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
             role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: KEY_MANAGER_ROLE,
             user: payer.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant key manager role");
@@ -86,15 +93,19 @@ fn collect_fees() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &OPERATOR_ROLE),
+            admin_role: default_admin_role_pubkey,
             role: operator_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &OPERATOR_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: OPERATOR_ROLE,
             user: payer.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant operator role");
@@ -109,7 +120,8 @@ fn collect_fees() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RegisterKey {
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             valid_from: timestamp - 1,
             valid_to: timestamp + 20,
@@ -153,11 +165,13 @@ fn collect_fees() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -165,6 +179,7 @@ fn collect_fees() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect("Valid create credentials request must succeed.");
@@ -186,7 +201,9 @@ fn collect_fees() {
             operator_role: operator_role_account_for_admin,
             receiver_account: fee_collector.pubkey(),
         })
-        .args(keyring_network::instruction::CollectFees {})
+        .args(keyring_network::instruction::CollectFeesV2 {
+            instance: DEFAULT_INSTANCE,
+        })
         .payer(&dummy_payer)
         .send()
         .expect_err("Non-operator must not be able to collect fees");
@@ -200,7 +217,9 @@ fn collect_fees() {
             operator_role: operator_role_account_for_admin,
             receiver_account: fee_collector.pubkey(),
         })
-        .args(keyring_network::instruction::CollectFees {})
+        .args(keyring_network::instruction::CollectFeesV2 {
+            instance: DEFAULT_INSTANCE,
+        })
         .send()
         .expect("Operator must be able to collect fees");
 
@@ -216,7 +235,9 @@ fn collect_fees() {
             operator_role: operator_role_account_for_admin,
             receiver_account: fee_collector.pubkey(),
         })
-        .args(keyring_network::instruction::CollectFees {})
+        .args(keyring_network::instruction::CollectFeesV2 {
+            instance: DEFAULT_INSTANCE,
+        })
         .send()
         .expect("Operator must be able to collect fees even when it is 0.");
 
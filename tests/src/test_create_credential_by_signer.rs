@@ -0,0 +1,197 @@
+use crate::common::{
+    convert_secp_pubkey_to_address, create_credential_by_signer, generate_random_chain_id,
+    get_key_mapping, get_role_definition, get_role_members, get_timestamp, init_program,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::secp256k1_recover::Secp256k1Pubkey;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, Cluster,
+};
+use keyring_network::common::types::{KeyEntry, DEFAULT_INSTANCE, KEY_MANAGER_ROLE};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+
+#[test]
+fn create_credential_by_signer_recovers_key() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (program_state_pubkey, _, default_admin_role_pubkey) =
+        init_program(&program, &payer, chain_id.clone());
+
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_admin, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRole {
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
+            role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
+            role: KEY_MANAGER_ROLE,
+            user: payer.pubkey(),
+            has_role: true,
+            expires_at: None,
+        })
+        .send()
+        .expect("Current admin must be able to grant key manager role");
+
+    let timestamp = get_timestamp(&rpc);
+    let register_key = |key: Vec<u8>| {
+        program
+            .request()
+            .accounts(keyring_network::accounts::RegisterKey {
+                key_registry,
+                key_mapping: get_key_mapping(&program.id(), &key),
+                signer: payer.pubkey(),
+                key_manager_role: key_manager_role_account_for_admin,
+                system_program: System::id(),
+            })
+            .args(keyring_network::instruction::RegisterKeyV2 {
+                instance: DEFAULT_INSTANCE,
+                key,
+                valid_from: timestamp - 1,
+                valid_to: timestamp + 10000,
+            })
+            .send()
+            .expect("Valid key registration must be successful");
+    };
+
+    let secret_key = SecretKey::random(&mut rng);
+    let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+    let key_mapping = get_key_mapping(&program.id(), &key);
+    register_key(key.clone());
+
+    let other_secret_key = SecretKey::random(&mut rng);
+    let other_key =
+        libsecp256k1::PublicKey::from_secret_key(&other_secret_key).serialize()[1..].to_vec();
+    let other_key_mapping = get_key_mapping(&program.id(), &other_key);
+    register_key(other_key);
+
+    create_credential_by_signer(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        key_mapping,
+        1,
+        timestamp + 1000,
+    )
+    .expect("Credential signed by a registered key must be accepted");
+    let key_entry: KeyEntry = program.account(key_mapping).unwrap();
+    assert_eq!(key_entry.usage.credentials_issued, 1);
+
+    create_credential_by_signer(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        other_key_mapping,
+        1,
+        timestamp + 1000,
+    )
+    .expect_err("Key mapping of another key must be rejected");
+
+    let unregistered_secret_key = SecretKey::random(&mut rng);
+    let unregistered_key = libsecp256k1::PublicKey::from_secret_key(&unregistered_secret_key)
+        .serialize()[1..]
+        .to_vec();
+    create_credential_by_signer(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &unregistered_secret_key,
+        get_key_mapping(&program.id(), &unregistered_key),
+        1,
+        timestamp + 1000,
+    )
+    .expect_err("Credential signed by an unregistered key must be rejected");
+
+    // Key registered by its address
+    let address_secret_key = SecretKey::random(&mut rng);
+    let address_key =
+        libsecp256k1::PublicKey::from_secret_key(&address_secret_key).serialize()[1..].to_vec();
+    let address = convert_secp_pubkey_to_address(&Secp256k1Pubkey::new(&address_key));
+    register_key(address.clone());
+
+    create_credential_by_signer(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &address_secret_key,
+        get_key_mapping(&program.id(), &address_key),
+        1,
+        timestamp + 1000,
+    )
+    .expect_err("Public key of an address registered key must not be accepted");
+    create_credential_by_signer(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &address_secret_key,
+        get_key_mapping(&program.id(), &address),
+        1,
+        timestamp + 1000,
+    )
+    .expect("Credential signed by the owner of a registered address must be accepted");
+
+    // Revoked key
+    program
+        .request()
+        .accounts(keyring_network::accounts::RevokeKey {
+            key_registry,
+            key_mapping,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RevokeKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key,
+        })
+        .send()
+        .expect("Key manager must be allowed to revoke key");
+
+    create_credential_by_signer(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        key_mapping,
+        1,
+        timestamp + 1000,
+    )
+    .expect_err("Credential signed by a revoked key must be rejected");
+}
@@ -1,5 +1,6 @@
 use crate::common::{
-    convert_pubkey_to_address, generate_random_chain_id, get_timestamp, init_program,
+    convert_pubkey_to_address, generate_random_chain_id, get_policy_config, get_role_definition,
+    get_role_members, get_timestamp, init_program,
 };
 use anchor_client::anchor_lang::prelude::System;
 use anchor_client::anchor_lang::Id;
@@ -12,7 +13,8 @@ use anchor_client::{
     Client, Cluster,
 };
 use keyring_network::common::types::{
-    ChainId, EntityData, ToHash, BLACKLIST_MANAGER_ROLE, CURRENT_VERSION, KEY_MANAGER_ROLE,
+    AttestationFormat, BlacklistReason, Blacklisting, ChainId, EntityData, ToHash,
+    BLACKLIST_MANAGER_ROLE, CURRENT_VERSION, DEFAULT_INSTANCE, KEY_MANAGER_ROLE,
 };
 use keyring_network::common::verify_auth_message::create_signature_payload;
 use keyring_network::ID as program_id;
@@ -75,15 +77,19 @@ fn create_credentials() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
             role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: KEY_MANAGER_ROLE,
             user: payer.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant key manager role");
@@ -91,15 +97,23 @@ fn create_credentials() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &BLACKLIST_MANAGER_ROLE,
+            ),
+            admin_role: default_admin_role_pubkey,
             role: blacklist_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &BLACKLIST_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: BLACKLIST_MANAGER_ROLE,
             user: payer.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant blacklist manager role");
@@ -114,7 +128,8 @@ fn create_credentials() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RegisterKey {
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             valid_from: timestamp - 1,
             valid_to: timestamp + 20,
@@ -159,11 +174,13 @@ fn create_credentials() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -171,6 +188,7 @@ fn create_credentials() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect_err("Invalid signature must not succeed");
@@ -210,11 +228,13 @@ fn create_credentials() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -222,6 +242,7 @@ fn create_credentials() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect_err("Invalid valid_until must not be accepted by the program.");
@@ -261,11 +282,13 @@ fn create_credentials() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -273,6 +296,7 @@ fn create_credentials() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect_err("Without sufficient balance tx cannot succeed.");
@@ -300,11 +324,13 @@ fn create_credentials() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -312,6 +338,7 @@ fn create_credentials() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect_err("Dummy chain id should not be accepted");
@@ -353,11 +380,13 @@ fn create_credentials() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -365,6 +394,7 @@ fn create_credentials() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect("Valid create credentials request must succeed.");
@@ -382,6 +412,7 @@ fn create_credentials() {
             version: CURRENT_VERSION,
             blacklisted: false,
             exp: valid_until,
+            blacklisting: Blacklisting::default(),
         }
     );
 
@@ -422,11 +453,13 @@ fn create_credentials() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -434,6 +467,7 @@ fn create_credentials() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect("Valid create credentials request must succeed.");
@@ -451,6 +485,7 @@ fn create_credentials() {
             version: CURRENT_VERSION,
             blacklisted: false,
             exp: valid_until,
+            blacklisting: Blacklisting::default(),
         }
     );
 
@@ -463,9 +498,12 @@ fn create_credentials() {
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::BlacklistEntity {
+        .args(keyring_network::instruction::BlacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
+            reason: BlacklistReason::Unspecified,
+            until: None,
         })
         .send()
         .expect("Admin should be able to blacklist entity");
@@ -476,6 +514,7 @@ fn create_credentials() {
             version: CURRENT_VERSION,
             blacklisted: true,
             exp: 0,
+            blacklisting: Blacklisting::default(),
         }
     );
 
@@ -484,11 +523,13 @@ fn create_credentials() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -496,6 +537,7 @@ fn create_credentials() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect_err("Blacklisted entity must not be used to create credentials");
@@ -509,7 +551,8 @@ fn create_credentials() {
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::UnblacklistEntity {
+        .args(keyring_network::instruction::UnblacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
         })
@@ -522,6 +565,7 @@ fn create_credentials() {
             version: CURRENT_VERSION,
             blacklisted: false,
             exp: 0,
+            blacklisting: Blacklisting::default(),
         }
     );
 
@@ -530,11 +574,13 @@ fn create_credentials() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -542,6 +588,7 @@ fn create_credentials() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect("Unblacklisted entity can be used to create credentials");
@@ -556,7 +603,10 @@ fn create_credentials() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RevokeKey { key: key.clone() })
+        .args(keyring_network::instruction::RevokeKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+        })
         .send()
         .expect("Admin must be allowed to revoke key");
 
@@ -595,11 +645,13 @@ fn create_credentials() {
         .accounts(keyring_network::accounts::CreateCredential {
             program_state: program_state_pubkey.clone(),
             key_mapping: key_mapping_pubkey.clone(),
+            policy_config: get_policy_config(&program.id(), policy_id),
             signer: payer.pubkey(),
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::CreateCredential {
+        .args(keyring_network::instruction::CreateCredentialV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             policy_id,
             trading_address,
@@ -607,6 +659,7 @@ fn create_credentials() {
             valid_until,
             cost,
             backdoor: backdoor.clone(),
+            format: AttestationFormat::Packed,
         })
         .send()
         .expect_err("Revoked key cannot be used to create credentials");
@@ -0,0 +1,202 @@
+use crate::common::{
+    assert_program_error, generate_random_chain_id, get_entity_mapping, get_key_mapping,
+    get_role_account, get_timestamp, grant_role, init_program, submit_credential,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, Cluster,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    BlacklistReason, BLACKLIST_MANAGER_ROLE, DEFAULT_INSTANCE, KEY_MANAGER_ROLE,
+};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+use std::thread::sleep;
+use std::time::Duration;
+
+const POLICY_ID: u64 = 1;
+
+#[test]
+fn credential_errors() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (program_state_pubkey, _, _) = init_program(&program, &payer, chain_id.clone());
+
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    let role_account =
+        |role: [u8; 32]| get_role_account(&program_id, &DEFAULT_INSTANCE, &role, &payer.pubkey());
+
+    for role in [KEY_MANAGER_ROLE, BLACKLIST_MANAGER_ROLE] {
+        grant_role(&program, &payer, role, payer.pubkey())
+            .expect("Current admin must be able to grant role");
+    }
+
+    let register_key = |key: &[u8], valid_from: u64, valid_to: u64| {
+        program
+            .request()
+            .accounts(keyring_network::accounts::RegisterKey {
+                key_registry,
+                key_mapping: get_key_mapping(&program.id(), key),
+                signer: payer.pubkey(),
+                key_manager_role: role_account(KEY_MANAGER_ROLE),
+                system_program: System::id(),
+            })
+            .args(keyring_network::instruction::RegisterKeyV2 {
+                instance: DEFAULT_INSTANCE,
+                key: key.to_vec(),
+                valid_from,
+                valid_to,
+            })
+            .send()
+            .expect("Valid key registration must be successful");
+    };
+    let new_key = |rng: &mut OsRng| {
+        let secret_key = SecretKey::random(rng);
+        let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+        (secret_key, key)
+    };
+
+    let timestamp = get_timestamp(&rpc);
+    let (secret_key, key) = new_key(&mut rng);
+    register_key(&key, timestamp - 1, timestamp + 10000);
+    let (not_yet_valid_secret_key, not_yet_valid_key) = new_key(&mut rng);
+    register_key(&not_yet_valid_key, timestamp + 5000, timestamp + 10000);
+    let (expiring_secret_key, expiring_key) = new_key(&mut rng);
+    register_key(&expiring_key, timestamp - 1, timestamp + 3);
+    let (revoked_secret_key, revoked_key) = new_key(&mut rng);
+    register_key(&revoked_key, timestamp - 1, timestamp + 10000);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RevokeKey {
+            key_registry,
+            key_mapping: get_key_mapping(&program.id(), &revoked_key),
+            signer: payer.pubkey(),
+            key_manager_role: role_account(KEY_MANAGER_ROLE),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RevokeKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: revoked_key.clone(),
+        })
+        .send()
+        .expect("Key manager must be allowed to revoke key");
+
+    let create = |secret_key: &SecretKey, key: &[u8], trading_address, valid_until| {
+        submit_credential(
+            &program,
+            DEFAULT_INSTANCE,
+            program_state_pubkey,
+            &chain_id,
+            secret_key,
+            key,
+            POLICY_ID,
+            trading_address,
+            valid_until,
+        )
+    };
+
+    let timestamp = get_timestamp(&rpc);
+    let valid_until = timestamp + 1000;
+    assert_program_error(
+        create(
+            &not_yet_valid_secret_key,
+            &key,
+            Pubkey::new_unique(),
+            valid_until,
+        ),
+        KeyringError::ErrSignatureMismatch,
+    );
+    assert_program_error(
+        create(
+            &revoked_secret_key,
+            &revoked_key,
+            Pubkey::new_unique(),
+            valid_until,
+        ),
+        KeyringError::ErrKeyRevoked,
+    );
+    assert_program_error(
+        create(
+            &not_yet_valid_secret_key,
+            &not_yet_valid_key,
+            Pubkey::new_unique(),
+            valid_until,
+        ),
+        KeyringError::ErrKeyNotYetValid,
+    );
+    assert_program_error(
+        create(&secret_key, &key, Pubkey::new_unique(), timestamp - 10),
+        KeyringError::ErrCredentialExpired,
+    );
+
+    let trading_address = Pubkey::new_unique();
+    create(&secret_key, &key, trading_address, valid_until)
+        .expect("Valid credential must be accepted");
+    assert_program_error(
+        create(&secret_key, &key, trading_address, valid_until),
+        KeyringError::ErrExpiryNotIncreasing,
+    );
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::BlacklistEntity {
+            signer: payer.pubkey(),
+            blacklist_manager_role: role_account(BLACKLIST_MANAGER_ROLE),
+            entity_mapping: get_entity_mapping(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                POLICY_ID,
+                &trading_address,
+            ),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::BlacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
+            policy_id: POLICY_ID,
+            trading_address,
+            reason: BlacklistReason::Unspecified,
+            until: None,
+        })
+        .send()
+        .expect("Blacklist manager should be able to blacklist entity");
+    assert_program_error(
+        create(&secret_key, &key, trading_address, valid_until + 1),
+        KeyringError::ErrEntityBlacklisted,
+    );
+
+    // Wait for the short lived key to expire
+    while get_timestamp(&rpc) <= timestamp + 3 {
+        sleep(Duration::from_secs(1));
+    }
+    assert_program_error(
+        create(
+            &expiring_secret_key,
+            &expiring_key,
+            Pubkey::new_unique(),
+            valid_until,
+        ),
+        KeyringError::ErrKeyExpired,
+    );
+}
@@ -0,0 +1,160 @@
+use anchor_client::anchor_lang::prelude::Pubkey;
+use anchor_client::anchor_lang::{Discriminator, Event};
+use keyring_indexer::error::IndexerError;
+use keyring_indexer::events::{decode_event, KeyringEvent};
+use keyring_indexer::logs::parse_logs;
+use keyring_indexer::state::{EntityRecord, KeyRecord, KeyringState, RecordedTransaction};
+use keyring_network::common::types::{
+    Blacklisting, KeyQuota, PolicyScope, ProgramConfig, DEFAULT_ADMIN_ROLE, KEY_MANAGER_ROLE,
+    OPERATOR_ROLE,
+};
+use keyring_network::events::{CredentialsCreated, FeesCollected};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::str::FromStr;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedLogs {
+    pub program_id: String,
+    pub admin: String,
+    pub operator: String,
+    pub trading_address: String,
+    pub receiver: String,
+    #[serde(with = "hex::serde")]
+    pub key: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub chain_id: Vec<u8>,
+    pub transactions: Vec<RecordedTransaction>,
+}
+
+#[test]
+pub fn test_indexer() {
+    let recorded_logs = fs::read("./vectors/indexer_logs.json").unwrap();
+    let recorded_logs = String::from_utf8_lossy(&recorded_logs);
+    let recorded: RecordedLogs = serde_json::from_str(&recorded_logs).unwrap();
+
+    let program_id = Pubkey::from_str(&recorded.program_id).unwrap();
+    let admin = Pubkey::from_str(&recorded.admin).unwrap();
+    let operator = Pubkey::from_str(&recorded.operator).unwrap();
+    let trading_address = Pubkey::from_str(&recorded.trading_address).unwrap();
+    let receiver = Pubkey::from_str(&recorded.receiver).unwrap();
+
+    // Invalid event data should give an error
+    assert_eq!(
+        decode_event(&[1, 2, 3]).unwrap_err(),
+        IndexerError::MissingDiscriminator
+    );
+    assert_eq!(
+        decode_event(&[1; 16]).unwrap_err(),
+        IndexerError::UnknownDiscriminator([1; 8])
+    );
+    assert!(matches!(
+        decode_event(&FeesCollected::DISCRIMINATOR).unwrap_err(),
+        IndexerError::InvalidEventData { .. }
+    ));
+
+    // Event data must be consumed completely
+    let fees_collected = FeesCollected {
+        instance: Pubkey::default(),
+        amount: 5000,
+        receiver,
+        signer: admin,
+        remaining_balance: 0,
+        timestamp: 1735689600,
+        slot: 1,
+    };
+    let mut data = fees_collected.data();
+    assert_eq!(
+        decode_event(&data).unwrap(),
+        KeyringEvent::FeesCollected(fees_collected)
+    );
+    data.push(0);
+    assert!(matches!(
+        decode_event(&data).unwrap_err(),
+        IndexerError::InvalidEventData { .. }
+    ));
+
+    // Data emitted by other programs in the same transaction must be ignored
+    let events = parse_logs(&program_id, &recorded.transactions[8].logs).unwrap();
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        KeyringEvent::CredentialsCreated(CredentialsCreated {
+            key,
+            policy_id,
+            trading_address: event_trading_address,
+            signer,
+            valid_until,
+            cost,
+            ..
+        }) => {
+            assert_eq!(key, &recorded.key);
+            assert_eq!(*policy_id, 1);
+            assert_eq!(event_trading_address, &trading_address);
+            assert_eq!(signer, &operator);
+            assert_eq!(*valid_until, 1735689600 + 3600);
+            assert_eq!(*cost, 5000);
+        }
+        event => panic!("Unexpected event {:?}", event),
+    }
+
+    // Events of the same program under a different id must not be decoded
+    assert!(
+        parse_logs(&Pubkey::new_unique(), &recorded.transactions[0].logs)
+            .unwrap()
+            .is_empty()
+    );
+
+    let mut state = KeyringState::default();
+    state
+        .replay(&program_id, recorded.transactions.iter())
+        .unwrap();
+
+    assert_eq!(
+        &state.chain_id.as_ref().unwrap().chain_id[..4],
+        recorded.chain_id.as_slice()
+    );
+    assert_eq!(state.config, ProgramConfig::default());
+    assert_eq!(state.role_members(&DEFAULT_ADMIN_ROLE), vec![admin]);
+    assert!(!state.has_role(&KEY_MANAGER_ROLE, &admin));
+    assert!(state.has_role(&OPERATOR_ROLE, &operator));
+    assert_eq!(state.role_expiry(&OPERATOR_ROLE, &operator), None);
+    assert!(state.has_role_at(&OPERATOR_ROLE, &operator, 4_000_000_000));
+
+    // Key registered in failed transaction must not be indexed
+    assert_eq!(state.keys.len(), 1);
+    assert_eq!(
+        state.keys.get(&recorded.key),
+        Some(&KeyRecord {
+            is_valid: false,
+            valid_from: 1735689600,
+            valid_to: 1735689600 + 86400,
+            policy_scope: PolicyScope::Any,
+            quota: KeyQuota::default(),
+            credentials_issued: 1,
+        })
+    );
+    assert!(state.active_keys().is_empty());
+
+    assert_eq!(
+        state.entity(1, &trading_address),
+        Some(&EntityRecord {
+            blacklisted: false,
+            exp: 1735689600 + 3600,
+            blacklisting: Blacklisting::default(),
+        })
+    );
+    assert_eq!(state.entity(2, &trading_address), None);
+    assert_eq!(state.entity(1, &receiver), None);
+    assert_eq!(state.fees_collected, 5000);
+    assert_eq!(state.last_slot, recorded.transactions.last().unwrap().slot);
+
+    // Events of the default instance do not belong to any other instance
+    let mut state = KeyringState::new(Pubkey::new_unique());
+    state
+        .replay(&program_id, recorded.transactions.iter())
+        .unwrap();
+    assert_eq!(state.chain_id, None);
+    assert!(state.keys.is_empty());
+    assert!(state.entities.is_empty());
+}
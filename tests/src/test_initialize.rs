@@ -1,11 +1,17 @@
-use crate::common::{generate_random_chain_id, init_program};
+use crate::common::{
+    assert_program_error, generate_random_chain_id, init_program, initialize_accounts,
+};
 use anchor_client::solana_client::rpc_client::RpcClient;
 use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::solana_sdk::signer::Signer;
 use anchor_client::{solana_sdk::commitment_config::CommitmentConfig, Client, Cluster};
+use keyring_network::common::error::KeyringError;
 use keyring_network::ID as program_id;
 use rand::rngs::OsRng;
+use std::thread::sleep;
+use std::time::Duration;
 
 #[test]
 fn test_initialize() {
@@ -23,3 +29,41 @@ fn test_initialize() {
     let chain_id = generate_random_chain_id(&mut rng);
     init_program(&program, &payer, chain_id);
 }
+
+#[test]
+fn test_initialize_requires_upgrade_authority() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let attacker = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&attacker.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client =
+        Client::new_with_options(Cluster::Localnet, &attacker, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    // We need to wait a bit for validator to start
+    sleep(Duration::from_secs(3));
+
+    // Nobody but the upgrade authority can initialize, so a deployment cannot
+    // be front-run before its authority initializes it. A fresh instance keeps
+    // this independent of the initialization of the default instance.
+    let mut rng = OsRng;
+    let instance = Pubkey::new_unique();
+    let result = program
+        .request()
+        .accounts(initialize_accounts(
+            &program_id,
+            &instance,
+            &attacker.pubkey(),
+        ))
+        .args(keyring_network::instruction::InitializeV2 {
+            instance,
+            chain_id: generate_random_chain_id(&mut rng),
+        })
+        .send()
+        .map(|_| ())
+        .map_err(Box::new);
+    assert_program_error(result, KeyringError::ErrNotUpgradeAuthority);
+}
@@ -0,0 +1,280 @@
+use crate::common::{
+    assert_program_error, generate_random_chain_id, get_entity_mapping, get_role_account,
+    get_timestamp, init_program, initialize_accounts, instance_address, manage_role,
+    sign_credential, submit_credential, submit_signed_credential,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, Cluster,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    EntityData, ProgramState, ToHash, DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE, KEY_MANAGER_ROLE,
+};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+
+const POLICY_ID: u64 = 1;
+
+#[test]
+fn test_instances() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let partner = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&partner.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (default_program_state, _, _) = init_program(&program, &payer, chain_id.clone());
+
+    // The default instance keeps the seeds from before instances existed
+    assert_eq!(
+        default_program_state,
+        Pubkey::find_program_address(
+            &[b"keyring_program".as_ref(), b"global_state".as_ref()],
+            &program.id(),
+        )
+        .0
+    );
+
+    let instance = Pubkey::new_unique();
+    let instance_chain_id = generate_random_chain_id(&mut rng);
+    let initialize = |chain_id: &Vec<u8>| {
+        program
+            .request()
+            .accounts(initialize_accounts(
+                &program.id(),
+                &instance,
+                &payer.pubkey(),
+            ))
+            .args(keyring_network::instruction::InitializeV2 {
+                instance,
+                chain_id: chain_id.clone(),
+            })
+            .send()
+    };
+    initialize(&instance_chain_id).expect("Upgrade authority must be able to create an instance");
+    initialize(&instance_chain_id).expect_err("Instance cannot be initialized twice");
+
+    let instance_program_state = instance_address(
+        &program_id,
+        &[b"keyring_program", b"global_state"],
+        &instance,
+    );
+    assert_ne!(instance_program_state, default_program_state);
+    let state: ProgramState = program.account(instance_program_state).unwrap();
+    assert_eq!(
+        &state.chain_id.chain_id[..instance_chain_id.len()],
+        instance_chain_id.as_slice()
+    );
+    let state: ProgramState = program.account(default_program_state).unwrap();
+    assert_eq!(
+        &state.chain_id.chain_id[..chain_id.len()],
+        chain_id.as_slice()
+    );
+
+    // Hand the instance over to the partner, who administers only that instance
+    manage_role(
+        &program,
+        instance,
+        &payer,
+        DEFAULT_ADMIN_ROLE,
+        DEFAULT_ADMIN_ROLE,
+        partner.pubkey(),
+        true,
+        None,
+    )
+    .expect("Instance admin must be able to grant the admin role");
+    manage_role(
+        &program,
+        instance,
+        &partner,
+        DEFAULT_ADMIN_ROLE,
+        KEY_MANAGER_ROLE,
+        partner.pubkey(),
+        true,
+        None,
+    )
+    .expect("Partner must be able to grant roles in its instance");
+    assert_program_error(
+        manage_role(
+            &program,
+            DEFAULT_INSTANCE,
+            &partner,
+            DEFAULT_ADMIN_ROLE,
+            KEY_MANAGER_ROLE,
+            partner.pubkey(),
+            true,
+            None,
+        ),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+
+    let timestamp = get_timestamp(&rpc);
+    let secret_key = SecretKey::random(&mut rng);
+    let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+    let register_key = |instance: Pubkey, signer: &Keypair| {
+        program
+            .request()
+            .accounts(keyring_network::accounts::RegisterKey {
+                key_registry: instance_address(
+                    &program_id,
+                    &[b"keyring_program", b"active_keys"],
+                    &instance,
+                ),
+                key_mapping: instance_address(
+                    &program_id,
+                    &[b"keyring_program", b"_key_mapping", key.to_hash().as_ref()],
+                    &instance,
+                ),
+                signer: signer.pubkey(),
+                key_manager_role: get_role_account(
+                    &program_id,
+                    &instance,
+                    &KEY_MANAGER_ROLE,
+                    &signer.pubkey(),
+                ),
+                system_program: System::id(),
+            })
+            .args(keyring_network::instruction::RegisterKeyV2 {
+                instance,
+                key: key.clone(),
+                valid_from: timestamp - 1,
+                valid_to: timestamp + 10000,
+            })
+            .signer(signer)
+            .send()
+            .map(|_| ())
+            .map_err(Box::new)
+    };
+    register_key(instance, &partner)
+        .expect("Partner must be able to register keys in its instance");
+
+    let trading_address = Pubkey::new_unique();
+    submit_credential(
+        &program,
+        DEFAULT_INSTANCE,
+        default_program_state,
+        &chain_id,
+        &secret_key,
+        &key,
+        POLICY_ID,
+        trading_address,
+        timestamp + 1000,
+    )
+    .expect_err("Key of another instance must not be accepted");
+    submit_credential(
+        &program,
+        instance,
+        instance_program_state,
+        &instance_chain_id,
+        &secret_key,
+        &key,
+        POLICY_ID,
+        trading_address,
+        timestamp + 1000,
+    )
+    .expect("Key must sign credentials for its instance");
+
+    let entity: EntityData = program
+        .account(get_entity_mapping(
+            &program_id,
+            &instance,
+            POLICY_ID,
+            &trading_address,
+        ))
+        .unwrap();
+    assert_eq!(entity.exp, timestamp + 1000);
+
+    // Credentials of an instance cannot be replayed on another instance with
+    // the same chain id and key
+    let other_instance = Pubkey::new_unique();
+    program
+        .request()
+        .accounts(initialize_accounts(
+            &program.id(),
+            &other_instance,
+            &payer.pubkey(),
+        ))
+        .args(keyring_network::instruction::InitializeV2 {
+            instance: other_instance,
+            chain_id: instance_chain_id.clone(),
+        })
+        .send()
+        .expect("Upgrade authority must be able to create an instance");
+    manage_role(
+        &program,
+        other_instance,
+        &payer,
+        DEFAULT_ADMIN_ROLE,
+        KEY_MANAGER_ROLE,
+        payer.pubkey(),
+        true,
+        None,
+    )
+    .expect("Instance admin must be able to grant roles");
+    register_key(other_instance, &payer).expect("Key manager must be able to register keys");
+    let other_program_state = instance_address(
+        &program_id,
+        &[b"keyring_program", b"global_state"],
+        &other_instance,
+    );
+    let trading_address = Pubkey::new_unique();
+    let signature = sign_credential(
+        instance,
+        &instance_chain_id,
+        &secret_key,
+        POLICY_ID,
+        trading_address,
+        timestamp + 1000,
+    );
+    assert_program_error(
+        submit_signed_credential(
+            &program,
+            other_instance,
+            other_program_state,
+            &key,
+            POLICY_ID,
+            trading_address,
+            timestamp + 1000,
+            signature.clone(),
+        ),
+        KeyringError::ErrSignatureMismatch,
+    );
+    submit_signed_credential(
+        &program,
+        instance,
+        instance_program_state,
+        &key,
+        POLICY_ID,
+        trading_address,
+        timestamp + 1000,
+        signature,
+    )
+    .expect("Credential must be accepted by the instance it was signed for");
+    assert!(program
+        .account::<EntityData>(get_entity_mapping(
+            &program_id,
+            &DEFAULT_INSTANCE,
+            POLICY_ID,
+            &trading_address
+        ))
+        .is_err());
+}
@@ -0,0 +1,313 @@
+use crate::common::{
+    create_credential, generate_random_chain_id, get_key_mapping, get_role_definition,
+    get_role_members, get_timestamp, init_program,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, Cluster,
+};
+use keyring_network::common::types::{
+    KeyEntry, PolicyScope, DEFAULT_INSTANCE, KEY_MANAGER_ROLE, MAX_SCOPED_POLICIES,
+};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+
+#[test]
+fn key_policy_scope() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    // Let's fund dummy payer
+    let dummy_payer = Keypair::new();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+    rpc.request_airdrop(&dummy_payer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (program_state_pubkey, _, default_admin_role_pubkey) =
+        init_program(&program, &payer, chain_id.clone());
+
+    let secret_key = SecretKey::random(&mut rng);
+    let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+    let key_mapping_pubkey = get_key_mapping(&program.id(), &key);
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_admin, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_dummy_payer, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            dummy_payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRole {
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
+            role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
+            role: KEY_MANAGER_ROLE,
+            user: payer.pubkey(),
+            has_role: true,
+            expires_at: None,
+        })
+        .send()
+        .expect("Current admin must be able to grant key manager role");
+
+    let update_scope = |signer: &Keypair, key_manager_role: Pubkey, policy_scope: PolicyScope| {
+        program
+            .request()
+            .accounts(keyring_network::accounts::UpdateKeyPolicyScope {
+                signer: signer.pubkey(),
+                key_manager_role,
+                key_mapping: key_mapping_pubkey,
+                system_program: System::id(),
+            })
+            .args(keyring_network::instruction::UpdateKeyPolicyScope {
+                instance: DEFAULT_INSTANCE,
+                key: key.clone(),
+                policy_scope,
+            })
+            .payer(signer)
+            .send()
+            .map_err(Box::new)
+    };
+
+    update_scope(
+        &payer,
+        key_manager_role_account_for_admin,
+        PolicyScope::List(vec![2]),
+    )
+    .expect_err("Unregistered key cannot be scoped");
+
+    let timestamp = get_timestamp(&rpc);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: key_mapping_pubkey,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect("Valid key registration must be successful");
+
+    // Freshly registered key may sign for any policy
+    let key_entry: KeyEntry = program.account(key_mapping_pubkey).unwrap();
+    assert_eq!(key_entry.policy_scope, PolicyScope::Any);
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        1,
+        timestamp + 1000,
+    )
+    .expect("Unscoped key must be accepted for any policy");
+
+    update_scope(
+        &dummy_payer,
+        key_manager_role_account_for_dummy_payer,
+        PolicyScope::List(vec![2]),
+    )
+    .expect_err("DummyPayer must not be allowed to scope key");
+
+    update_scope(
+        &payer,
+        key_manager_role_account_for_admin,
+        PolicyScope::Range { from: 5, to: 1 },
+    )
+    .expect_err("Empty range must be rejected");
+
+    update_scope(
+        &payer,
+        key_manager_role_account_for_admin,
+        PolicyScope::List(vec![]),
+    )
+    .expect_err("Empty list must be rejected");
+
+    update_scope(
+        &payer,
+        key_manager_role_account_for_admin,
+        PolicyScope::List(vec![1; MAX_SCOPED_POLICIES + 1]),
+    )
+    .expect_err("Too many policies must be rejected");
+
+    // Allow-list
+    update_scope(
+        &payer,
+        key_manager_role_account_for_admin,
+        PolicyScope::List(vec![2, 7]),
+    )
+    .expect("Key manager must be allowed to scope key");
+
+    let key_entry: KeyEntry = program.account(key_mapping_pubkey).unwrap();
+    assert_eq!(key_entry.policy_scope, PolicyScope::List(vec![2, 7]));
+
+    let timestamp = get_timestamp(&rpc);
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        1,
+        timestamp + 1000,
+    )
+    .expect_err("Policy outside of the allow-list must be rejected");
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        7,
+        timestamp + 1000,
+    )
+    .expect("Policy in the allow-list must be accepted");
+
+    // Range
+    update_scope(
+        &payer,
+        key_manager_role_account_for_admin,
+        PolicyScope::Range { from: 10, to: 20 },
+    )
+    .expect("Key manager must be allowed to scope key");
+
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        7,
+        timestamp + 1000,
+    )
+    .expect_err("Policy outside of the range must be rejected");
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        20,
+        timestamp + 1000,
+    )
+    .expect("Policy at the end of the range must be accepted");
+
+    // Rotated key inherits the scope
+    let new_secret_key = SecretKey::random(&mut rng);
+    let new_key =
+        libsecp256k1::PublicKey::from_secret_key(&new_secret_key).serialize()[1..].to_vec();
+    let new_key_mapping_pubkey = get_key_mapping(&program.id(), &new_key);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RotateKey {
+            key_registry,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            old_key_mapping: key_mapping_pubkey,
+            new_key_mapping: new_key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RotateKey {
+            instance: DEFAULT_INSTANCE,
+            old_key: key.clone(),
+            new_key: new_key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+            overlap: 0,
+        })
+        .send()
+        .expect("Key manager must be allowed to rotate key");
+
+    let key_entry: KeyEntry = program.account(new_key_mapping_pubkey).unwrap();
+    assert_eq!(
+        key_entry.policy_scope,
+        PolicyScope::Range { from: 10, to: 20 }
+    );
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &new_secret_key,
+        1,
+        timestamp + 1000,
+    )
+    .expect_err("Rotated key must keep the scope of the old key");
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &new_secret_key,
+        15,
+        timestamp + 1000,
+    )
+    .expect("Rotated key must be accepted within its scope");
+
+    // Registering the revoked old key again keeps its scope
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: key_mapping_pubkey,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect("Revoked key must be able to be registered again");
+    let key_entry: KeyEntry = program.account(key_mapping_pubkey).unwrap();
+    assert_eq!(
+        key_entry.policy_scope,
+        PolicyScope::Range { from: 10, to: 20 }
+    );
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        1,
+        timestamp + 1000,
+    )
+    .expect_err("Key registered again must keep its scope");
+}
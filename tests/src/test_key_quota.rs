@@ -0,0 +1,315 @@
+use crate::common::{
+    create_credential, generate_random_chain_id, get_key_mapping, get_role_definition,
+    get_role_members, get_timestamp, init_program,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, Cluster,
+};
+use keyring_network::common::types::{KeyEntry, KeyQuota, DEFAULT_INSTANCE, KEY_MANAGER_ROLE};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+
+#[test]
+fn key_quota() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    // Let's fund dummy payer
+    let dummy_payer = Keypair::new();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+    rpc.request_airdrop(&dummy_payer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (program_state_pubkey, _, default_admin_role_pubkey) =
+        init_program(&program, &payer, chain_id.clone());
+
+    let secret_key = SecretKey::random(&mut rng);
+    let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+    let key_mapping_pubkey = get_key_mapping(&program.id(), &key);
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_admin, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_dummy_payer, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            dummy_payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRole {
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
+            role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
+            role: KEY_MANAGER_ROLE,
+            user: payer.pubkey(),
+            has_role: true,
+            expires_at: None,
+        })
+        .send()
+        .expect("Current admin must be able to grant key manager role");
+
+    let update_quota = |signer: &Keypair, key_manager_role: Pubkey, quota: KeyQuota| {
+        program
+            .request()
+            .accounts(keyring_network::accounts::UpdateKeyQuota {
+                signer: signer.pubkey(),
+                key_manager_role,
+                key_mapping: key_mapping_pubkey,
+                system_program: System::id(),
+            })
+            .args(keyring_network::instruction::UpdateKeyQuota {
+                instance: DEFAULT_INSTANCE,
+                key: key.clone(),
+                quota,
+            })
+            .payer(signer)
+            .send()
+            .map_err(Box::new)
+    };
+
+    let timestamp = get_timestamp(&rpc);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: key_mapping_pubkey,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect("Valid key registration must be successful");
+
+    // Usage is counted even without a quota
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        1,
+        timestamp + 1000,
+    )
+    .expect("Key without quota must be accepted");
+    let key_entry: KeyEntry = program.account(key_mapping_pubkey).unwrap();
+    assert_eq!(key_entry.quota, KeyQuota::default());
+    assert_eq!(key_entry.usage.credentials_issued, 1);
+
+    update_quota(
+        &dummy_payer,
+        key_manager_role_account_for_dummy_payer,
+        KeyQuota {
+            max_credentials: 1,
+            window_duration: 0,
+            max_credentials_per_window: 0,
+        },
+    )
+    .expect_err("DummyPayer must not be allowed to set key quota");
+
+    update_quota(
+        &payer,
+        key_manager_role_account_for_admin,
+        KeyQuota {
+            max_credentials: 0,
+            window_duration: 3600,
+            max_credentials_per_window: 0,
+        },
+    )
+    .expect_err("Rate limit window without a limit must be rejected");
+
+    // Rate limit of two credentials per hour
+    update_quota(
+        &payer,
+        key_manager_role_account_for_admin,
+        KeyQuota {
+            max_credentials: 0,
+            window_duration: 3600,
+            max_credentials_per_window: 2,
+        },
+    )
+    .expect("Key manager must be allowed to set key quota");
+
+    // Avoid crossing a window boundary in the middle of the test
+    let mut timestamp = get_timestamp(&rpc);
+    while timestamp % 3600 > 3600 - 30 {
+        std::thread::sleep(std::time::Duration::from_secs(5));
+        timestamp = get_timestamp(&rpc);
+    }
+
+    for policy_id in [2, 3] {
+        create_credential(
+            &program,
+            program_state_pubkey,
+            &chain_id,
+            &secret_key,
+            policy_id,
+            timestamp + 1000,
+        )
+        .expect("Credential within the rate limit must be accepted");
+    }
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        4,
+        timestamp + 1000,
+    )
+    .expect_err("Credential over the rate limit must be rejected");
+
+    let key_entry: KeyEntry = program.account(key_mapping_pubkey).unwrap();
+    assert_eq!(key_entry.usage.credentials_issued, 3);
+    assert_eq!(key_entry.usage.window_start, timestamp - timestamp % 3600);
+    assert_eq!(key_entry.usage.window_credentials_issued, 2);
+
+    // Lifetime quota already used up by the credentials issued so far
+    update_quota(
+        &payer,
+        key_manager_role_account_for_admin,
+        KeyQuota {
+            max_credentials: 3,
+            window_duration: 0,
+            max_credentials_per_window: 0,
+        },
+    )
+    .expect("Key manager must be allowed to set key quota");
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        5,
+        timestamp + 1000,
+    )
+    .expect_err("Credential over the lifetime quota must be rejected");
+
+    update_quota(
+        &payer,
+        key_manager_role_account_for_admin,
+        KeyQuota {
+            max_credentials: 4,
+            window_duration: 0,
+            max_credentials_per_window: 0,
+        },
+    )
+    .expect("Key manager must be allowed to raise key quota");
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        5,
+        timestamp + 1000,
+    )
+    .expect("Credential within the lifetime quota must be accepted");
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        6,
+        timestamp + 1000,
+    )
+    .expect_err("Credential over the lifetime quota must be rejected");
+
+    // Rotated key inherits the quota but starts with fresh counters
+    let new_secret_key = SecretKey::random(&mut rng);
+    let new_key =
+        libsecp256k1::PublicKey::from_secret_key(&new_secret_key).serialize()[1..].to_vec();
+    let new_key_mapping_pubkey = get_key_mapping(&program.id(), &new_key);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RotateKey {
+            key_registry,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            old_key_mapping: key_mapping_pubkey,
+            new_key_mapping: new_key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RotateKey {
+            instance: DEFAULT_INSTANCE,
+            old_key: key.clone(),
+            new_key: new_key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+            overlap: 0,
+        })
+        .send()
+        .expect("Key manager must be allowed to rotate key");
+
+    let key_entry: KeyEntry = program.account(new_key_mapping_pubkey).unwrap();
+    assert_eq!(key_entry.quota.max_credentials, 4);
+    assert_eq!(key_entry.usage.credentials_issued, 0);
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &new_secret_key,
+        6,
+        timestamp + 1000,
+    )
+    .expect("Rotated key must start with a fresh quota");
+
+    // Registering the revoked old key again keeps its quota, like a rotation
+    // its counters start over
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: key_mapping_pubkey,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect("Revoked key must be able to be registered again");
+    let key_entry: KeyEntry = program.account(key_mapping_pubkey).unwrap();
+    assert_eq!(key_entry.quota.max_credentials, 4);
+    assert_eq!(key_entry.usage.credentials_issued, 0);
+}
@@ -0,0 +1,387 @@
+use crate::common::{
+    assert_program_error, generate_random_chain_id, get_entity_mapping, get_key_mapping,
+    get_policy_config, get_program_data, get_role_account, get_role_members, get_timestamp,
+    initialize_accounts, set_upgrade_authority, sign_credential, CREDENTIAL_BACKDOOR,
+    CREDENTIAL_COST,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    KeyEntry, RoleMembers, BLACKLIST_MANAGER_ROLE, DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE,
+    KEY_MANAGER_ROLE, OPERATOR_ROLE,
+};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+use std::thread::sleep;
+use std::time::Duration;
+
+// Grants `role` to the payer with the original `manage_roles`, updating the
+// member index of the role if `index` is set
+fn legacy_grant_role(
+    program: &Program<&Keypair>,
+    payer: &Keypair,
+    role: [u8; 32],
+    index: bool,
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRoleLegacy {
+            default_admin_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &DEFAULT_ADMIN_ROLE,
+                &payer.pubkey(),
+            ),
+            role: get_role_account(&program_id, &DEFAULT_INSTANCE, &role, &payer.pubkey()),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+            role_members: index.then(|| get_role_members(&program_id, &DEFAULT_INSTANCE, &role)),
+        })
+        .args(keyring_network::instruction::ManageRoles {
+            role,
+            user: payer.pubkey(),
+            has_role: true,
+        })
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+// Creates a credential with the original `create_credential`, which reads the
+// key mapping without writing it
+fn legacy_create_credential(
+    program: &Program<&Keypair>,
+    payer: &Keypair,
+    chain_id: &[u8],
+    secret_key: &SecretKey,
+    policy_id: u64,
+    trading_address: Pubkey,
+    valid_until: u64,
+) -> Result<(), Box<ClientError>> {
+    let key = libsecp256k1::PublicKey::from_secret_key(secret_key).serialize()[1..].to_vec();
+    let signature = sign_credential(
+        DEFAULT_INSTANCE,
+        chain_id,
+        secret_key,
+        policy_id,
+        trading_address,
+        valid_until,
+    );
+    program
+        .request()
+        .accounts(keyring_network::accounts::CreateCredentialLegacy {
+            program_state: initialize_accounts(&program_id, &DEFAULT_INSTANCE, &payer.pubkey())
+                .program_state,
+            signer: payer.pubkey(),
+            key_mapping: get_key_mapping(&program_id, &key),
+            entity_mapping: get_entity_mapping(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                policy_id,
+                &trading_address,
+            ),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::CreateCredential {
+            key,
+            policy_id,
+            trading_address,
+            signature,
+            valid_until,
+            cost: CREDENTIAL_COST,
+            backdoor: CREDENTIAL_BACKDOOR.to_vec(),
+        })
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn legacy_check_credential(
+    program: &Program<&Keypair>,
+    payer: &Keypair,
+    policy_id: u64,
+    trading_address: Pubkey,
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::CheckCredentialLegacy {
+            signer: payer.pubkey(),
+            entity_mapping: get_entity_mapping(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                policy_id,
+                &trading_address,
+            ),
+        })
+        .args(keyring_network::instruction::CheckCredential {
+            policy_id,
+            trading_address,
+        })
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn set_policy_threshold(
+    program: &Program<&Keypair>,
+    payer: &Keypair,
+    policy_id: u64,
+    threshold: u8,
+) {
+    let accounts = initialize_accounts(&program_id, &DEFAULT_INSTANCE, &payer.pubkey());
+    program
+        .request()
+        .accounts(keyring_network::accounts::SetPolicyThreshold {
+            signer: payer.pubkey(),
+            default_admin_role: accounts.default_admin_role,
+            policy_config: get_policy_config(&program_id, policy_id),
+            program_state: accounts.program_state,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::SetPolicyThreshold {
+            instance: DEFAULT_INSTANCE,
+            policy_id,
+            threshold,
+        })
+        .send()
+        .expect("Admin must be able to set policy threshold");
+}
+
+#[test]
+fn test_legacy_instructions() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    // We need to wait a bit for validator to start
+    sleep(Duration::from_secs(3));
+    set_upgrade_authority(&program, &payer.pubkey());
+
+    // The original `initialize` needs the program data account appended to
+    // check the upgrade authority
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let accounts = initialize_accounts(&program_id, &DEFAULT_INSTANCE, &payer.pubkey());
+    let initialize = |program_data: Option<Pubkey>| {
+        program
+            .request()
+            .accounts(keyring_network::accounts::InitializeLegacy {
+                program_state: accounts.program_state,
+                key_registry: accounts.key_registry,
+                default_admin_role: accounts.default_admin_role,
+                signer: payer.pubkey(),
+                system_program: System::id(),
+                program_data,
+                default_admin_role_members: Some(accounts.default_admin_role_members),
+            })
+            .args(keyring_network::instruction::Initialize {
+                chain_id: chain_id.clone(),
+            })
+            .send()
+            .map(|_| ())
+            .map_err(Box::new)
+    };
+    assert_program_error(initialize(None), KeyringError::ErrNotUpgradeAuthority);
+    initialize(Some(get_program_data(&program_id)))
+        .expect("Upgrade authority must be able to initialize with the original instruction");
+    let admins: RoleMembers = program
+        .account(accounts.default_admin_role_members)
+        .unwrap();
+    assert_eq!(admins.members, vec![payer.pubkey()]);
+
+    // Roles granted without the member index are honored but not indexed
+    legacy_grant_role(&program, &payer, KEY_MANAGER_ROLE, true)
+        .expect("Admin must be able to grant roles with the original instruction");
+    legacy_grant_role(&program, &payer, BLACKLIST_MANAGER_ROLE, false)
+        .expect("Member index must be optional");
+    legacy_grant_role(&program, &payer, OPERATOR_ROLE, false)
+        .expect("Member index must be optional");
+    let key_managers: RoleMembers = program
+        .account(get_role_members(
+            &program_id,
+            &DEFAULT_INSTANCE,
+            &KEY_MANAGER_ROLE,
+        ))
+        .unwrap();
+    assert_eq!(key_managers.members, vec![payer.pubkey()]);
+
+    let secret_key = SecretKey::random(&mut rng);
+    let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+    let key_accounts = |key_mapping| keyring_network::accounts::RegisterKeyLegacy {
+        key_registry: accounts.key_registry,
+        signer: payer.pubkey(),
+        key_manager_role: get_role_account(
+            &program_id,
+            &DEFAULT_INSTANCE,
+            &KEY_MANAGER_ROLE,
+            &payer.pubkey(),
+        ),
+        key_mapping,
+        system_program: System::id(),
+    };
+    let timestamp = get_timestamp(&anchor_rpc_client);
+    program
+        .request()
+        .accounts(key_accounts(get_key_mapping(&program_id, &key)))
+        .args(keyring_network::instruction::RegisterKey {
+            key: key.clone(),
+            valid_from: timestamp,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect("Key manager must be able to register keys with the original instruction");
+
+    let policy_id = 1;
+    let trading_address = Pubkey::new_unique();
+    let valid_until = timestamp + 1000;
+    legacy_create_credential(
+        &program,
+        &payer,
+        &chain_id,
+        &secret_key,
+        policy_id,
+        trading_address,
+        valid_until,
+    )
+    .expect("Credentials must be creatable with the original instruction");
+    legacy_check_credential(&program, &payer, policy_id, trading_address)
+        .expect("Credential must be valid");
+    // The key mapping is read-only, so usage of keys without a quota is not
+    // counted
+    let key_entry: KeyEntry = program.account(get_key_mapping(&program_id, &key)).unwrap();
+    assert_eq!(key_entry.usage.credentials_issued, 0);
+
+    let blacklist_manager_role = get_role_account(
+        &program_id,
+        &DEFAULT_INSTANCE,
+        &BLACKLIST_MANAGER_ROLE,
+        &payer.pubkey(),
+    );
+    let entity_mapping =
+        get_entity_mapping(&program_id, &DEFAULT_INSTANCE, policy_id, &trading_address);
+    program
+        .request()
+        .accounts(keyring_network::accounts::BlacklistEntityLegacy {
+            signer: payer.pubkey(),
+            blacklist_manager_role,
+            entity_mapping,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::BlacklistEntity {
+            policy_id,
+            trading_address,
+        })
+        .send()
+        .expect("Blacklist manager must be able to blacklist with the original instruction");
+    assert_program_error(
+        legacy_check_credential(&program, &payer, policy_id, trading_address),
+        KeyringError::ErrInCheckingCredentials,
+    );
+    program
+        .request()
+        .accounts(keyring_network::accounts::UnblacklistEntityLegacy {
+            signer: payer.pubkey(),
+            blacklist_manager_role,
+            entity_mapping,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UnblacklistEntity {
+            policy_id,
+            trading_address,
+        })
+        .send()
+        .expect("Blacklist manager must be able to unblacklist with the original instruction");
+    legacy_check_credential(&program, &payer, policy_id, trading_address)
+        .expect("Credential must be valid again");
+
+    let receiver = Pubkey::new_unique();
+    program
+        .request()
+        .accounts(keyring_network::accounts::CollectFeesLegacy {
+            receiver_account: receiver,
+            signer: payer.pubkey(),
+            operator_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &OPERATOR_ROLE,
+                &payer.pubkey(),
+            ),
+            program_state: accounts.program_state,
+        })
+        .args(keyring_network::instruction::CollectFees {})
+        .send()
+        .expect("Operator must be able to collect fees with the original instruction");
+    assert_eq!(
+        anchor_rpc_client.get_balance(&receiver).unwrap(),
+        CREDENTIAL_COST
+    );
+
+    // The original instruction cannot see policy thresholds, so it is refused
+    // while any policy has one
+    set_policy_threshold(&program, &payer, 7, 2);
+    assert_program_error(
+        legacy_create_credential(
+            &program,
+            &payer,
+            &chain_id,
+            &secret_key,
+            policy_id,
+            Pubkey::new_unique(),
+            valid_until,
+        ),
+        KeyringError::ErrLegacyInstructionUnsupported,
+    );
+    set_policy_threshold(&program, &payer, 7, 1);
+    legacy_create_credential(
+        &program,
+        &payer,
+        &chain_id,
+        &secret_key,
+        policy_id,
+        Pubkey::new_unique(),
+        valid_until,
+    )
+    .expect("Original instruction must be allowed once no policy has a threshold");
+
+    let revoke_accounts = key_accounts(get_key_mapping(&program_id, &key));
+    program
+        .request()
+        .accounts(keyring_network::accounts::RevokeKeyLegacy {
+            key_registry: revoke_accounts.key_registry,
+            signer: revoke_accounts.signer,
+            key_manager_role: revoke_accounts.key_manager_role,
+            key_mapping: revoke_accounts.key_mapping,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RevokeKey { key: key.clone() })
+        .send()
+        .expect("Key manager must be able to revoke keys with the original instruction");
+    assert_program_error(
+        legacy_create_credential(
+            &program,
+            &payer,
+            &chain_id,
+            &secret_key,
+            policy_id,
+            Pubkey::new_unique(),
+            valid_until,
+        ),
+        KeyringError::ErrKeyRevoked,
+    );
+}
@@ -1,4 +1,7 @@
-use crate::common::{generate_random_chain_id, init_program};
+use crate::common::{
+    generate_random_chain_id, get_events, get_role_definition, get_role_members, get_timestamp,
+    init_program,
+};
 use anchor_client::anchor_lang::prelude::System;
 use anchor_client::anchor_lang::Id;
 use anchor_client::solana_client::rpc_client::RpcClient;
@@ -7,9 +10,12 @@ use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::solana_sdk::signer::Signer;
 use anchor_client::{solana_sdk::commitment_config::CommitmentConfig, Client, Cluster};
+use keyring_indexer::events::KeyringEvent;
 use keyring_network::common::types::{
-    Role, BLACKLIST_MANAGER_ROLE, DEFAULT_ADMIN_ROLE, KEY_MANAGER_ROLE, OPERATOR_ROLE,
+    Role, BLACKLIST_MANAGER_ROLE, DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE, KEY_MANAGER_ROLE,
+    OPERATOR_ROLE,
 };
+use keyring_network::events::RoleManaged;
 use keyring_network::ID as program_id;
 use rand::rngs::OsRng;
 
@@ -71,35 +77,75 @@ fn test_manage_roles() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_account_for_new_admin,
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &DEFAULT_ADMIN_ROLE,
+            ),
+            admin_role: default_admin_role_account_for_new_admin,
             role: default_admin_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &DEFAULT_ADMIN_ROLE),
             signer: new_admin.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: DEFAULT_ADMIN_ROLE,
             user: new_admin.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .payer(&new_admin)
         .send()
         .expect_err("Non admin must not be able to manage roles");
 
-    program
+    let timestamp = get_timestamp(&rpc) as i64;
+    let signature = program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &DEFAULT_ADMIN_ROLE,
+            ),
+            admin_role: default_admin_role_pubkey,
             role: default_admin_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &DEFAULT_ADMIN_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: DEFAULT_ADMIN_ROLE,
             user: new_admin.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant admin role");
+    let (slot, events) = get_events(&rpc, &signature);
+    match &events[..] {
+        [KeyringEvent::RoleManaged(event)] => {
+            assert_eq!(
+                event,
+                &RoleManaged {
+                    instance: DEFAULT_INSTANCE,
+                    role: DEFAULT_ADMIN_ROLE,
+                    user: new_admin.pubkey(),
+                    signer: payer.pubkey(),
+                    previous_has_role: false,
+                    has_role: true,
+                    previous_expires_at: None,
+                    expires_at: None,
+                    members: 2,
+                    timestamp: event.timestamp,
+                    slot,
+                }
+            );
+            assert!(event.timestamp >= timestamp);
+        }
+        events => panic!("Unexpected events {:?}", events),
+    }
 
     let role_account_data: Role = program
         .account(default_admin_role_account_for_new_admin.clone())
@@ -109,15 +155,19 @@ fn test_manage_roles() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
             role: key_manager_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: KEY_MANAGER_ROLE,
             user: new_admin.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant key manager role");
@@ -130,15 +180,23 @@ fn test_manage_roles() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &BLACKLIST_MANAGER_ROLE,
+            ),
+            admin_role: default_admin_role_pubkey,
             role: blacklist_manager_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &BLACKLIST_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: BLACKLIST_MANAGER_ROLE,
             user: new_admin.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant blacklist manager role");
@@ -151,15 +209,19 @@ fn test_manage_roles() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &OPERATOR_ROLE),
+            admin_role: default_admin_role_pubkey,
             role: operator_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &OPERATOR_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: OPERATOR_ROLE,
             user: new_admin.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant operator role");
@@ -169,21 +231,53 @@ fn test_manage_roles() {
         .expect("Operator role account must exist after granting role");
     assert_eq!(role_account_data.has_role, true);
 
-    program
+    let timestamp = get_timestamp(&rpc) as i64;
+    let signature = program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &DEFAULT_ADMIN_ROLE,
+            ),
+            admin_role: default_admin_role_pubkey,
             role: default_admin_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &DEFAULT_ADMIN_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: DEFAULT_ADMIN_ROLE,
             user: new_admin.pubkey(),
             has_role: false,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to revoke admin role");
+    let (slot, events) = get_events(&rpc, &signature);
+    match &events[..] {
+        [KeyringEvent::RoleManaged(event)] => {
+            assert_eq!(
+                event,
+                &RoleManaged {
+                    instance: DEFAULT_INSTANCE,
+                    role: DEFAULT_ADMIN_ROLE,
+                    user: new_admin.pubkey(),
+                    signer: payer.pubkey(),
+                    previous_has_role: true,
+                    has_role: false,
+                    previous_expires_at: None,
+                    expires_at: None,
+                    members: 1,
+                    timestamp: event.timestamp,
+                    slot,
+                }
+            );
+            assert!(event.timestamp >= timestamp);
+        }
+        events => panic!("Unexpected events {:?}", events),
+    }
 
     let role_account_data: Role = program
         .account(default_admin_role_account_for_new_admin.clone())
@@ -193,15 +287,19 @@ fn test_manage_roles() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
             role: key_manager_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: KEY_MANAGER_ROLE,
             user: new_admin.pubkey(),
             has_role: false,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to revoke key manager role");
@@ -214,15 +312,23 @@ fn test_manage_roles() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &BLACKLIST_MANAGER_ROLE,
+            ),
+            admin_role: default_admin_role_pubkey,
             role: blacklist_manager_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &BLACKLIST_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: BLACKLIST_MANAGER_ROLE,
             user: new_admin.pubkey(),
             has_role: false,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to revoke blacklist manager role");
@@ -235,15 +341,19 @@ fn test_manage_roles() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &OPERATOR_ROLE),
+            admin_role: default_admin_role_pubkey,
             role: operator_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &OPERATOR_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: OPERATOR_ROLE,
             user: new_admin.pubkey(),
             has_role: false,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to revoke operator role");
@@ -258,15 +368,19 @@ fn test_manage_roles() {
     program2
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_account_for_new_admin,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_account_for_new_admin,
             role: key_manager_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
             signer: new_admin.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: KEY_MANAGER_ROLE,
             user: new_admin.pubkey(),
             has_role: false,
+            expires_at: None,
         })
         .send()
         .expect("Key manager must be able to revoke key manager role");
@@ -279,15 +393,23 @@ fn test_manage_roles() {
     program2
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_account_for_new_admin,
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &BLACKLIST_MANAGER_ROLE,
+            ),
+            admin_role: default_admin_role_account_for_new_admin,
             role: blacklist_manager_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &BLACKLIST_MANAGER_ROLE),
             signer: new_admin.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: BLACKLIST_MANAGER_ROLE,
             user: new_admin.pubkey(),
             has_role: false,
+            expires_at: None,
         })
         .send()
         .expect("Blacklist manager must be able to revoke blacklist manager role");
@@ -300,15 +422,19 @@ fn test_manage_roles() {
     program2
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_account_for_new_admin,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &OPERATOR_ROLE),
+            admin_role: default_admin_role_account_for_new_admin,
             role: operator_role_account_for_new_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &OPERATOR_ROLE),
             signer: new_admin.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: OPERATOR_ROLE,
             user: new_admin.pubkey(),
             has_role: false,
+            expires_at: None,
         })
         .send()
         .expect("Operator must be able to revoke operator role");
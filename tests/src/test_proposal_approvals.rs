@@ -0,0 +1,323 @@
+use crate::common::{
+    assert_program_error, generate_random_chain_id, get_role_account, get_role_definition,
+    get_role_members, grant_role, init_program, manage_role,
+};
+use anchor_client::anchor_lang::prelude::{AccountMeta, System};
+use anchor_client::anchor_lang::{Id, InstructionData, ToAccountMetas};
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    ProgramConfig, Proposal, ProposalAccount, Role, APPROVER_ROLE, DEFAULT_ADMIN_ROLE,
+    DEFAULT_INSTANCE, KEY_MANAGER_ROLE, PROPOSER_ROLE,
+};
+use keyring_network::ID as program_id;
+use rand::rngs::OsRng;
+
+const APPROVAL_THRESHOLD: u8 = 2;
+
+fn program_state() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"global_state".as_ref()],
+        &program_id,
+    )
+    .0
+}
+
+fn proposal_account(proposal_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"proposal".as_ref(),
+            &proposal_id.to_le_bytes(),
+        ],
+        &program_id,
+    )
+    .0
+}
+
+fn manage_role_accounts(signer: &Pubkey, role: [u8; 32], user: &Pubkey) -> Vec<AccountMeta> {
+    keyring_network::accounts::ManageRole {
+        role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &role),
+        admin_role: get_role_account(&program_id, &DEFAULT_INSTANCE, &DEFAULT_ADMIN_ROLE, signer),
+        role: get_role_account(&program_id, &DEFAULT_INSTANCE, &role, user),
+        role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &role),
+        signer: *signer,
+        system_program: System::id(),
+    }
+    .to_account_metas(None)
+}
+
+fn approve_proposal(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    proposal_id: u64,
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::ApproveProposal {
+            proposal: proposal_account(proposal_id),
+            program_state: program_state(),
+            approver_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &APPROVER_ROLE,
+                &signer.pubkey(),
+            ),
+            signer: signer.pubkey(),
+        })
+        .args(keyring_network::instruction::ApproveProposal {
+            instance: DEFAULT_INSTANCE,
+            proposal_id,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn execute_proposal(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    proposal_id: u64,
+    proposer: Pubkey,
+    accounts: &[ProposalAccount],
+    approvals: &[Pubkey],
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::ExecuteProposal {
+            proposal: proposal_account(proposal_id),
+            proposer,
+            program_state: program_state(),
+            signer: signer.pubkey(),
+            keyring_program: program_id,
+        })
+        .accounts(
+            accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: false,
+                    is_writable: account.is_writable,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .accounts(
+            approvals
+                .iter()
+                .map(|approver| {
+                    AccountMeta::new_readonly(
+                        get_role_account(&program_id, &DEFAULT_INSTANCE, &APPROVER_ROLE, approver),
+                        false,
+                    )
+                })
+                .collect::<Vec<_>>(),
+        )
+        .args(keyring_network::instruction::ExecuteProposal {
+            instance: DEFAULT_INSTANCE,
+            proposal_id,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+#[test]
+fn test_proposal_approvals() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let approvers: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    for approver in &approvers {
+        anchor_rpc_client
+            .request_airdrop(&approver.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .unwrap();
+    }
+    let (timelock, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"timelock".as_ref()],
+        &program_id,
+    );
+    anchor_rpc_client
+        .request_airdrop(&timelock, 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    init_program(&program, &payer, chain_id.clone());
+
+    // Approvals without a timelock delay
+    program
+        .request()
+        .accounts(keyring_network::accounts::UpdateConfig {
+            program_state: program_state(),
+            default_admin_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &DEFAULT_ADMIN_ROLE,
+                &payer.pubkey(),
+            ),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UpdateConfig {
+            instance: DEFAULT_INSTANCE,
+            chain_id,
+            config: ProgramConfig {
+                approval_threshold: APPROVAL_THRESHOLD,
+                ..Default::default()
+            },
+        })
+        .signer(&payer)
+        .send()
+        .expect("Admin must be able to set the approval threshold");
+
+    grant_role(&program, &payer, DEFAULT_ADMIN_ROLE, timelock)
+        .expect("Admin must be able to grant the admin role to the timelock authority");
+    grant_role(&program, &payer, PROPOSER_ROLE, payer.pubkey())
+        .expect("Admin must be able to grant the proposer role");
+    for approver in &approvers[..2] {
+        grant_role(&program, &payer, APPROVER_ROLE, approver.pubkey())
+            .expect("Admin must be able to grant the approver role");
+    }
+
+    // Grant the key manager role to the user through the timelock authority
+    let user = Pubkey::new_unique();
+    let accounts: Vec<ProposalAccount> = manage_role_accounts(&timelock, KEY_MANAGER_ROLE, &user)
+        .into_iter()
+        .map(|account| ProposalAccount {
+            pubkey: account.pubkey,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    program
+        .request()
+        .accounts(keyring_network::accounts::ScheduleProposal {
+            proposal: proposal_account(1),
+            program_state: program_state(),
+            proposer_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &PROPOSER_ROLE,
+                &payer.pubkey(),
+            ),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ScheduleProposal {
+            instance: DEFAULT_INSTANCE,
+            proposal_id: 1,
+            accounts: accounts.clone(),
+            data: keyring_network::instruction::ManageRolesV2 {
+                instance: DEFAULT_INSTANCE,
+                role: KEY_MANAGER_ROLE,
+                user,
+                has_role: true,
+                expires_at: None,
+            }
+            .data(),
+        })
+        .signer(&payer)
+        .send()
+        .expect("Proposer must be able to schedule a proposal");
+
+    assert_program_error(
+        execute_proposal(&program, &payer, 1, payer.pubkey(), &accounts, &[]),
+        KeyringError::ErrProposalNotApproved,
+    );
+    assert_program_error(
+        approve_proposal(&program, &approvers[2], 1),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+
+    approve_proposal(&program, &approvers[0], 1).expect("Approver must be able to approve");
+    assert_program_error(
+        approve_proposal(&program, &approvers[0], 1),
+        KeyringError::ErrProposalAlreadyApproved,
+    );
+    assert_program_error(
+        execute_proposal(
+            &program,
+            &payer,
+            1,
+            payer.pubkey(),
+            &accounts,
+            &[approvers[0].pubkey()],
+        ),
+        KeyringError::ErrProposalNotApproved,
+    );
+
+    approve_proposal(&program, &approvers[1], 1).expect("Approver must be able to approve");
+    let proposal: Proposal = program.account(proposal_account(1)).unwrap();
+    assert_eq!(
+        proposal.approvals,
+        vec![approvers[0].pubkey(), approvers[1].pubkey()]
+    );
+
+    let approvals = [approvers[0].pubkey(), approvers[1].pubkey()];
+    assert_program_error(
+        execute_proposal(
+            &program,
+            &payer,
+            1,
+            payer.pubkey(),
+            &accounts,
+            &approvals[..1],
+        ),
+        KeyringError::ErrProposalNotApproved,
+    );
+
+    // Approvals of approvers whose role was revoked are not counted
+    manage_role(
+        &program,
+        DEFAULT_INSTANCE,
+        &payer,
+        DEFAULT_ADMIN_ROLE,
+        APPROVER_ROLE,
+        approvers[1].pubkey(),
+        false,
+        None,
+    )
+    .expect("Admin must be able to revoke the approver role");
+    assert_program_error(
+        execute_proposal(&program, &payer, 1, payer.pubkey(), &accounts, &approvals),
+        KeyringError::ErrProposalNotApproved,
+    );
+    grant_role(&program, &payer, APPROVER_ROLE, approvers[2].pubkey())
+        .expect("Admin must be able to grant the approver role");
+    approve_proposal(&program, &approvers[2], 1).expect("Approver must be able to approve");
+
+    execute_proposal(
+        &program,
+        &approvers[2],
+        1,
+        payer.pubkey(),
+        &accounts,
+        &[approvals[0], approvals[1], approvers[2].pubkey()],
+    )
+    .expect("Anyone must be able to execute an approved proposal");
+    let role: Role = program
+        .account(get_role_account(
+            &program_id,
+            &DEFAULT_INSTANCE,
+            &KEY_MANAGER_ROLE,
+            &user,
+        ))
+        .unwrap();
+    assert!(role.has_role);
+    assert!(program.account::<Proposal>(proposal_account(1)).is_err());
+}
@@ -1,4 +1,7 @@
-use crate::common::{generate_random_chain_id, get_timestamp, init_program};
+use crate::common::{
+    generate_random_chain_id, get_events, get_role_definition, get_role_members, get_timestamp,
+    init_program,
+};
 use anchor_client::anchor_lang::prelude::System;
 use anchor_client::anchor_lang::Id;
 use anchor_client::solana_client::rpc_client::RpcClient;
@@ -9,7 +12,9 @@ use anchor_client::{
     solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
     Client, Cluster,
 };
-use keyring_network::common::types::{KeyRegistry, ToHash, KEY_MANAGER_ROLE};
+use keyring_indexer::events::KeyringEvent;
+use keyring_network::common::types::{KeyRegistry, ToHash, DEFAULT_INSTANCE, KEY_MANAGER_ROLE};
+use keyring_network::events::KeyRegistered;
 use keyring_network::ID as program_id;
 use rand::rngs::OsRng;
 
@@ -68,15 +73,19 @@ fn register_key() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
             role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: KEY_MANAGER_ROLE,
             user: payer.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant key manager role");
@@ -91,7 +100,8 @@ fn register_key() {
             key_manager_role: key_manager_role_account_for_dummy_payer,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RegisterKey {
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             valid_from: timestamp - 1,
             valid_to: timestamp + 20,
@@ -110,7 +120,8 @@ fn register_key() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RegisterKey {
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             valid_from: timestamp + 20,
             valid_to: timestamp + 20,
@@ -128,7 +139,8 @@ fn register_key() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RegisterKey {
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             valid_from: timestamp - 10,
             valid_to: timestamp - 1,
@@ -156,7 +168,8 @@ fn register_key() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RegisterKey {
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
             key: invalid_key,
             valid_from: timestamp - 1,
             valid_to: timestamp + 20,
@@ -165,7 +178,7 @@ fn register_key() {
         .expect_err("Invalid key must be rejected");
 
     let timestamp = get_timestamp(&rpc);
-    program
+    let signature = program
         .request()
         .accounts(keyring_network::accounts::RegisterKey {
             key_registry: key_registry.clone(),
@@ -174,13 +187,34 @@ fn register_key() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RegisterKey {
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             valid_from: timestamp - 1,
             valid_to: timestamp + 20,
         })
         .send()
         .expect("Valid key registration must be successful");
+    let (slot, events) = get_events(&rpc, &signature);
+    match &events[..] {
+        [KeyringEvent::KeyRegistered(event)] => {
+            assert_eq!(
+                event,
+                &KeyRegistered {
+                    instance: DEFAULT_INSTANCE,
+                    key: key.clone(),
+                    signer: payer.pubkey(),
+                    valid_from: timestamp - 1,
+                    valid_to: timestamp + 20,
+                    active_keys: 1,
+                    timestamp: event.timestamp,
+                    slot,
+                }
+            );
+            assert!(event.timestamp >= timestamp as i64);
+        }
+        events => panic!("Unexpected events {:?}", events),
+    }
 
     let timestamp = get_timestamp(&rpc);
     program
@@ -192,7 +226,8 @@ fn register_key() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RegisterKey {
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             valid_from: timestamp - 1,
             valid_to: timestamp + 20,
@@ -0,0 +1,209 @@
+use crate::common::{
+    assert_program_error, convert_secp_pubkey_to_address, create_credential,
+    create_credential_with_key, generate_random_chain_id, get_key_mapping, get_role_definition,
+    get_role_members, get_timestamp, init_program,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::secp256k1_recover::Secp256k1Pubkey;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, Cluster,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{KeyEntry, KeyRegistry, DEFAULT_INSTANCE, KEY_MANAGER_ROLE};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+
+fn public_key_of(secret_key: &SecretKey) -> Vec<u8> {
+    libsecp256k1::PublicKey::from_secret_key(secret_key).serialize()[1..].to_vec()
+}
+
+fn address_of(secret_key: &SecretKey) -> Vec<u8> {
+    convert_secp_pubkey_to_address(&Secp256k1Pubkey::new(&public_key_of(secret_key)))
+}
+
+#[test]
+fn register_key_address() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (program_state_pubkey, _, default_admin_role_pubkey) =
+        init_program(&program, &payer, chain_id.clone());
+
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_admin, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRole {
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
+            role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
+            role: KEY_MANAGER_ROLE,
+            user: payer.pubkey(),
+            has_role: true,
+            expires_at: None,
+        })
+        .send()
+        .expect("Current admin must be able to grant key manager role");
+
+    let register_key = |key: Vec<u8>, valid_from: u64, valid_to: u64| {
+        program
+            .request()
+            .accounts(keyring_network::accounts::RegisterKey {
+                key_registry,
+                key_mapping: get_key_mapping(&program.id(), &key),
+                signer: payer.pubkey(),
+                key_manager_role: key_manager_role_account_for_admin,
+                system_program: System::id(),
+            })
+            .args(keyring_network::instruction::RegisterKeyV2 {
+                instance: DEFAULT_INSTANCE,
+                key,
+                valid_from,
+                valid_to,
+            })
+            .send()
+            .map(|_| ())
+            .map_err(Box::new)
+    };
+
+    let timestamp = get_timestamp(&rpc);
+    let secret_key = SecretKey::random(&mut rng);
+    let address = address_of(&secret_key);
+
+    register_key(vec![1; 21], timestamp - 1, timestamp + 10000)
+        .expect_err("Key which is neither a public key nor an address must be rejected");
+
+    register_key(address.clone(), timestamp - 1, timestamp + 10000)
+        .expect("Key registration by address must be successful");
+
+    let key_registry_data: KeyRegistry = program.account(key_registry).unwrap();
+    assert!(key_registry_data.active_keys.contains(&address));
+    let key_entry: KeyEntry = program
+        .account(get_key_mapping(&program.id(), &address))
+        .unwrap();
+    assert!(key_entry.is_valid);
+
+    // Signer is matched by the address of the recovered public key
+    create_credential_with_key(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        address.clone(),
+        1,
+        timestamp + 1000,
+    )
+    .expect("Credential signed by the owner of the address must be accepted");
+
+    create_credential_with_key(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &SecretKey::random(&mut rng),
+        address.clone(),
+        1,
+        timestamp + 1000,
+    )
+    .expect_err("Credential signed by another key must be rejected");
+
+    // The public key form has a key mapping of its own which is not registered
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        1,
+        timestamp + 1000,
+    )
+    .expect_err("Public key of an address registered key must not be accepted");
+    assert_program_error(
+        register_key(public_key_of(&secret_key), timestamp - 1, timestamp + 10000),
+        KeyringError::ErrKeyAlreadyRegistered,
+    );
+
+    // Address keys can be rotated like public keys
+    let new_secret_key = SecretKey::random(&mut rng);
+    let new_address = address_of(&new_secret_key);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RotateKey {
+            key_registry,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            old_key_mapping: get_key_mapping(&program.id(), &address),
+            new_key_mapping: get_key_mapping(&program.id(), &new_address),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RotateKey {
+            instance: DEFAULT_INSTANCE,
+            old_key: address.clone(),
+            new_key: new_address.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+            overlap: 0,
+        })
+        .send()
+        .expect("Key manager must be allowed to rotate key");
+
+    create_credential_with_key(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_key,
+        address,
+        2,
+        timestamp + 1000,
+    )
+    .expect_err("Rotated out address must be rejected");
+    create_credential_with_key(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &new_secret_key,
+        new_address,
+        2,
+        timestamp + 1000,
+    )
+    .expect("Rotated in address must be accepted");
+
+    // Only one form of a key can be registered at a time
+    register_key(public_key_of(&secret_key), timestamp - 1, timestamp + 10000)
+        .expect("Public key of a rotated out address must be able to be registered");
+    assert_program_error(
+        register_key(address_of(&secret_key), timestamp - 1, timestamp + 10000),
+        KeyringError::ErrKeyAlreadyRegistered,
+    );
+}
@@ -1,4 +1,6 @@
-use crate::common::{generate_random_chain_id, get_timestamp, init_program};
+use crate::common::{
+    generate_random_chain_id, get_role_definition, get_role_members, get_timestamp, init_program,
+};
 use anchor_client::anchor_lang::prelude::System;
 use anchor_client::anchor_lang::Id;
 use anchor_client::solana_client::rpc_client::RpcClient;
@@ -10,7 +12,7 @@ use anchor_client::{
     solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
     Client, Cluster,
 };
-use keyring_network::common::types::{KeyRegistry, ToHash, KEY_MANAGER_ROLE};
+use keyring_network::common::types::{KeyRegistry, ToHash, DEFAULT_INSTANCE, KEY_MANAGER_ROLE};
 use keyring_network::ID as program_id;
 use rand::rngs::OsRng;
 
@@ -69,15 +71,19 @@ fn revoke_key() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
             role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: KEY_MANAGER_ROLE,
             user: payer.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant key manager role");
@@ -92,7 +98,8 @@ fn revoke_key() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RegisterKey {
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
             key: key.clone(),
             valid_from: timestamp - 1,
             valid_to: timestamp + 20,
@@ -116,7 +123,10 @@ fn revoke_key() {
             key_manager_role: key_manager_role_account_for_dummy_payer,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RevokeKey { key: key.clone() })
+        .args(keyring_network::instruction::RevokeKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+        })
         .payer(&dummy_payer)
         .send()
         .expect_err("DummyPayer must not be allowed to revoke new key");
@@ -141,7 +151,10 @@ fn revoke_key() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RevokeKey { key: invalid_key })
+        .args(keyring_network::instruction::RevokeKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: invalid_key,
+        })
         .send()
         .expect_err("Invalid key cannot be revoked");
 
@@ -154,7 +167,10 @@ fn revoke_key() {
             key_manager_role: key_manager_role_account_for_admin,
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::RevokeKey { key: key.clone() })
+        .args(keyring_network::instruction::RevokeKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+        })
         .send()
         .expect("Key manager must be allowed to revoke key");
 
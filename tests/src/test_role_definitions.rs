@@ -0,0 +1,266 @@
+use crate::common::{
+    assert_program_error, generate_random_chain_id, get_entity_mapping, get_role_account,
+    get_role_definition, grant_role, init_program, manage_role,
+};
+use anchor_client::anchor_lang::prelude::{AccountMeta, System};
+use anchor_client::anchor_lang::solana_program::keccak;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    policy_blacklist_manager_role, BlacklistEntry, BlacklistReason, EntityData, Role,
+    RoleDefinition, BLACKLIST_MANAGER_ROLE, DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE,
+};
+use keyring_network::ID as program_id;
+use rand::rngs::OsRng;
+
+fn define_role(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    role: [u8; 32],
+    name: &str,
+    admin_role: [u8; 32],
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::DefineRole {
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &role),
+            default_admin_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &DEFAULT_ADMIN_ROLE,
+                &signer.pubkey(),
+            ),
+            signer: signer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::DefineRole {
+            instance: DEFAULT_INSTANCE,
+            role,
+            name: name.to_string(),
+            admin_role,
+            description_hash: keccak::hash(name.as_bytes()).to_bytes(),
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn batch_blacklist_entities(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    manager_role: [u8; 32],
+    entries: Vec<BlacklistEntry>,
+) -> Result<(), Box<ClientError>> {
+    let entity_mappings: Vec<AccountMeta> = entries
+        .iter()
+        .map(|entry| {
+            let entity_mapping = get_entity_mapping(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                entry.policy_id,
+                &entry.trading_address,
+            );
+            AccountMeta::new(entity_mapping, false)
+        })
+        .collect();
+    program
+        .request()
+        .accounts(keyring_network::accounts::BatchBlacklistEntities {
+            signer: signer.pubkey(),
+            blacklist_manager_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &manager_role,
+                &signer.pubkey(),
+            ),
+            system_program: System::id(),
+        })
+        .accounts(entity_mappings)
+        .args(keyring_network::instruction::BatchBlacklistEntities {
+            instance: DEFAULT_INSTANCE,
+            entries,
+            reason: BlacklistReason::Sanctions,
+            until: None,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+#[test]
+fn test_role_definitions() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let non_admin = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&non_admin.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let user = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    init_program(&program, &payer, chain_id);
+
+    // Blacklist manager of a single policy, administered by the blacklist
+    // managers
+    let name = "POLICY_1_BLACKLIST_MANAGER_ROLE";
+    let policy_role = policy_blacklist_manager_role(1);
+
+    assert_program_error(
+        manage_role(
+            &program,
+            DEFAULT_INSTANCE,
+            &payer,
+            DEFAULT_ADMIN_ROLE,
+            policy_role,
+            user.pubkey(),
+            true,
+            None,
+        ),
+        KeyringError::ErrRoleNotDefined,
+    );
+    assert_program_error(
+        define_role(
+            &program,
+            &non_admin,
+            policy_role,
+            name,
+            BLACKLIST_MANAGER_ROLE,
+        ),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+    assert_program_error(
+        define_role(&program, &payer, policy_role, "", BLACKLIST_MANAGER_ROLE),
+        KeyringError::ErrInvalidRoleName,
+    );
+    assert_program_error(
+        define_role(
+            &program,
+            &payer,
+            policy_role,
+            &"A".repeat(33),
+            BLACKLIST_MANAGER_ROLE,
+        ),
+        KeyringError::ErrInvalidRoleName,
+    );
+    assert_program_error(
+        define_role(
+            &program,
+            &payer,
+            DEFAULT_ADMIN_ROLE,
+            "DEFAULT_ADMIN_ROLE",
+            BLACKLIST_MANAGER_ROLE,
+        ),
+        KeyringError::ErrInvalidRoleDefinition,
+    );
+
+    define_role(&program, &payer, policy_role, name, BLACKLIST_MANAGER_ROLE)
+        .expect("Admin must be able to define a role");
+    let definition: RoleDefinition = program
+        .account(get_role_definition(
+            &program_id,
+            &DEFAULT_INSTANCE,
+            &policy_role,
+        ))
+        .unwrap();
+    assert_eq!(
+        definition,
+        RoleDefinition {
+            name: name.to_string(),
+            admin_role: BLACKLIST_MANAGER_ROLE,
+            description_hash: keccak::hash(name.as_bytes()).to_bytes(),
+        }
+    );
+
+    // Only members of the admin role of the definition may grant the role
+    assert_program_error(
+        manage_role(
+            &program,
+            DEFAULT_INSTANCE,
+            &payer,
+            DEFAULT_ADMIN_ROLE,
+            policy_role,
+            user.pubkey(),
+            true,
+            None,
+        ),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+    grant_role(&program, &payer, BLACKLIST_MANAGER_ROLE, payer.pubkey())
+        .expect("Built-in roles do not need a definition");
+    manage_role(
+        &program,
+        DEFAULT_INSTANCE,
+        &payer,
+        BLACKLIST_MANAGER_ROLE,
+        policy_role,
+        user.pubkey(),
+        true,
+        None,
+    )
+    .expect("Blacklist manager must be able to grant the defined role");
+    let role: Role = program
+        .account(get_role_account(
+            &program_id,
+            &DEFAULT_INSTANCE,
+            &policy_role,
+            &user.pubkey(),
+        ))
+        .unwrap();
+    assert!(role.has_role);
+
+    // The policy role lets its members manage the blacklist of that policy only
+    let entry = |policy_id| BlacklistEntry {
+        policy_id,
+        trading_address: Pubkey::new_unique(),
+    };
+    let blacklisted = entry(1);
+    batch_blacklist_entities(&program, &user, policy_role, vec![blacklisted.clone()])
+        .expect("Policy blacklist manager must be able to blacklist entities of its policy");
+    let entity_data: EntityData = program
+        .account(get_entity_mapping(
+            &program_id,
+            &DEFAULT_INSTANCE,
+            1,
+            &blacklisted.trading_address,
+        ))
+        .unwrap();
+    assert!(entity_data.blacklisted);
+    assert_program_error(
+        batch_blacklist_entities(&program, &user, policy_role, vec![entry(2)]),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+    assert_program_error(
+        batch_blacklist_entities(&program, &user, policy_role, vec![entry(1), entry(2)]),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+    assert_program_error(
+        batch_blacklist_entities(
+            &program,
+            &user,
+            policy_blacklist_manager_role(2),
+            vec![entry(2)],
+        ),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+}
@@ -0,0 +1,199 @@
+use crate::common::{
+    assert_program_error, generate_random_chain_id, get_key_mapping, get_role_account,
+    get_timestamp, init_program, manage_role,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    Role, DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE, KEY_MANAGER_ROLE,
+};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+use std::thread::sleep;
+use std::time::Duration;
+
+fn register_key(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    timestamp: u64,
+) -> Result<(), Box<ClientError>> {
+    let secret_key = SecretKey::random(&mut OsRng);
+    let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: get_key_mapping(&program.id(), &key),
+            signer: signer.pubkey(),
+            key_manager_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &KEY_MANAGER_ROLE,
+                &signer.pubkey(),
+            ),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key,
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn wait_until(rpc: &RpcClient, timestamp: i64) {
+    while (get_timestamp(rpc) as i64) < timestamp {
+        sleep(Duration::from_secs(1));
+    }
+}
+
+#[test]
+fn test_role_expiry() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let contractor = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&contractor.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    init_program(&program, &payer, chain_id);
+
+    let now = get_timestamp(&rpc) as i64;
+    assert_program_error(
+        manage_role(
+            &program,
+            DEFAULT_INSTANCE,
+            &payer,
+            DEFAULT_ADMIN_ROLE,
+            KEY_MANAGER_ROLE,
+            contractor.pubkey(),
+            true,
+            Some(now),
+        ),
+        KeyringError::ErrInvalidRoleExpiry,
+    );
+
+    // Temporary key manager
+    let expires_at = now + 5;
+    manage_role(
+        &program,
+        DEFAULT_INSTANCE,
+        &payer,
+        DEFAULT_ADMIN_ROLE,
+        KEY_MANAGER_ROLE,
+        contractor.pubkey(),
+        true,
+        Some(expires_at),
+    )
+    .expect("Admin must be able to grant a temporary role");
+    let role: Role = program
+        .account(get_role_account(
+            &program_id,
+            &DEFAULT_INSTANCE,
+            &KEY_MANAGER_ROLE,
+            &contractor.pubkey(),
+        ))
+        .unwrap();
+    assert!(role.has_role);
+    assert_eq!(role.expires_at, Some(expires_at));
+    register_key(&program, &contractor, get_timestamp(&rpc))
+        .expect("Key manager must be able to register keys before the grant expires");
+
+    wait_until(&rpc, expires_at);
+    assert_program_error(
+        register_key(&program, &contractor, get_timestamp(&rpc)),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+
+    // Granting again without expiry makes the grant permanent
+    manage_role(
+        &program,
+        DEFAULT_INSTANCE,
+        &payer,
+        DEFAULT_ADMIN_ROLE,
+        KEY_MANAGER_ROLE,
+        contractor.pubkey(),
+        true,
+        None,
+    )
+    .expect("Admin must be able to renew a role");
+    let role: Role = program
+        .account(get_role_account(
+            &program_id,
+            &DEFAULT_INSTANCE,
+            &KEY_MANAGER_ROLE,
+            &contractor.pubkey(),
+        ))
+        .unwrap();
+    assert_eq!(role.expires_at, None);
+    register_key(&program, &contractor, get_timestamp(&rpc))
+        .expect("Key manager must be able to register keys after renewal");
+
+    // Temporary admin
+    let expires_at = get_timestamp(&rpc) as i64 + 5;
+    manage_role(
+        &program,
+        DEFAULT_INSTANCE,
+        &payer,
+        DEFAULT_ADMIN_ROLE,
+        DEFAULT_ADMIN_ROLE,
+        contractor.pubkey(),
+        true,
+        Some(expires_at),
+    )
+    .expect("Admin must be able to grant a temporary admin role");
+    manage_role(
+        &program,
+        DEFAULT_INSTANCE,
+        &contractor,
+        DEFAULT_ADMIN_ROLE,
+        KEY_MANAGER_ROLE,
+        payer.pubkey(),
+        true,
+        None,
+    )
+    .expect("Temporary admin must be able to grant roles before the grant expires");
+
+    wait_until(&rpc, expires_at);
+    assert_program_error(
+        manage_role(
+            &program,
+            DEFAULT_INSTANCE,
+            &contractor,
+            DEFAULT_ADMIN_ROLE,
+            KEY_MANAGER_ROLE,
+            contractor.pubkey(),
+            true,
+            None,
+        ),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+}
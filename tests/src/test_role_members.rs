@@ -0,0 +1,293 @@
+use crate::common::{
+    assert_program_error, generate_random_chain_id, get_role_account, get_role_definition,
+    get_role_members, get_timestamp, init_program, manage_role,
+};
+use anchor_client::anchor_lang::prelude::{AccountMeta, System};
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    RoleMembers, DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE, KEY_MANAGER_ROLE, MAX_ROLE_MEMBERS,
+};
+use keyring_network::ID as program_id;
+use rand::rngs::OsRng;
+use std::thread::sleep;
+use std::time::Duration;
+
+fn members(program: &Program<&Keypair>, role: [u8; 32]) -> Vec<Pubkey> {
+    let role_members: RoleMembers = program
+        .account(get_role_members(&program_id, &DEFAULT_INSTANCE, &role))
+        .unwrap();
+    role_members.members
+}
+
+// Default admin revoking itself, naming the other admins which remain
+fn renounce_admin(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    other_admins: &[Pubkey],
+) -> Result<(), Box<ClientError>> {
+    update_own_admin(program, signer, false, None, other_admins)
+}
+
+// Default admin changing its own grant, naming the other admins which remain
+fn update_own_admin(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    has_role: bool,
+    expires_at: Option<i64>,
+    other_admins: &[Pubkey],
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRole {
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &DEFAULT_ADMIN_ROLE,
+            ),
+            admin_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &DEFAULT_ADMIN_ROLE,
+                &signer.pubkey(),
+            ),
+            role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &DEFAULT_ADMIN_ROLE,
+                &signer.pubkey(),
+            ),
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &DEFAULT_ADMIN_ROLE),
+            signer: signer.pubkey(),
+            system_program: System::id(),
+        })
+        .accounts(
+            other_admins
+                .iter()
+                .map(|admin| {
+                    AccountMeta::new_readonly(
+                        get_role_account(
+                            &program_id,
+                            &DEFAULT_INSTANCE,
+                            &DEFAULT_ADMIN_ROLE,
+                            admin,
+                        ),
+                        false,
+                    )
+                })
+                .collect::<Vec<_>>(),
+        )
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
+            role: DEFAULT_ADMIN_ROLE,
+            user: signer.pubkey(),
+            has_role,
+            expires_at,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+#[test]
+fn test_role_members() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let new_admin = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&new_admin.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    init_program(&program, &payer, chain_id);
+    assert_eq!(members(&program, DEFAULT_ADMIN_ROLE), vec![payer.pubkey()]);
+
+    let first = Pubkey::new_unique();
+    let second = Pubkey::new_unique();
+    for user in [first, second, first] {
+        manage_role(
+            &program,
+            DEFAULT_INSTANCE,
+            &payer,
+            DEFAULT_ADMIN_ROLE,
+            KEY_MANAGER_ROLE,
+            user,
+            true,
+            None,
+        )
+        .expect("Admin must be able to grant role");
+    }
+    assert_eq!(members(&program, KEY_MANAGER_ROLE), vec![first, second]);
+    manage_role(
+        &program,
+        DEFAULT_INSTANCE,
+        &payer,
+        DEFAULT_ADMIN_ROLE,
+        KEY_MANAGER_ROLE,
+        first,
+        false,
+        None,
+    )
+    .expect("Admin must be able to revoke role");
+    assert_eq!(members(&program, KEY_MANAGER_ROLE), vec![second]);
+
+    // Only admins may revoke roles of others
+    assert_program_error(
+        manage_role(
+            &program,
+            DEFAULT_INSTANCE,
+            &new_admin,
+            DEFAULT_ADMIN_ROLE,
+            KEY_MANAGER_ROLE,
+            second,
+            false,
+            None,
+        ),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+    assert_eq!(members(&program, KEY_MANAGER_ROLE), vec![second]);
+
+    // A role can only be granted to `MAX_ROLE_MEMBERS` users at once
+    let grant_key_manager = |user: Pubkey, has_role: bool| {
+        manage_role(
+            &program,
+            DEFAULT_INSTANCE,
+            &payer,
+            DEFAULT_ADMIN_ROLE,
+            KEY_MANAGER_ROLE,
+            user,
+            has_role,
+            None,
+        )
+    };
+    let others: Vec<Pubkey> = (1..MAX_ROLE_MEMBERS)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    for user in &others {
+        grant_key_manager(*user, true).expect("Admin must be able to grant role");
+    }
+    assert_eq!(
+        members(&program, KEY_MANAGER_ROLE).len(),
+        MAX_ROLE_MEMBERS as usize
+    );
+    assert_program_error(
+        grant_key_manager(Pubkey::new_unique(), true),
+        KeyringError::ErrBreachedMaxRoleMemberLimit,
+    );
+    grant_key_manager(second, true).expect("Members must be able to be granted again");
+    for user in &others {
+        grant_key_manager(*user, false).expect("Admin must be able to revoke role");
+    }
+    assert_eq!(members(&program, KEY_MANAGER_ROLE), vec![second]);
+
+    assert_program_error(
+        manage_role(
+            &program,
+            DEFAULT_INSTANCE,
+            &payer,
+            DEFAULT_ADMIN_ROLE,
+            DEFAULT_ADMIN_ROLE,
+            payer.pubkey(),
+            false,
+            None,
+        ),
+        KeyringError::ErrLastAdmin,
+    );
+
+    // Admins whose grant expires do not count
+    let expires_at = get_timestamp(&anchor_rpc_client) as i64 + 6;
+    manage_role(
+        &program,
+        DEFAULT_INSTANCE,
+        &payer,
+        DEFAULT_ADMIN_ROLE,
+        DEFAULT_ADMIN_ROLE,
+        new_admin.pubkey(),
+        true,
+        Some(expires_at),
+    )
+    .expect("Admin must be able to grant an expiring admin role");
+    assert_program_error(
+        update_own_admin(
+            &program,
+            &payer,
+            true,
+            Some(expires_at),
+            &[new_admin.pubkey()],
+        ),
+        KeyringError::ErrLastAdmin,
+    );
+    while get_timestamp(&anchor_rpc_client) as i64 <= expires_at {
+        sleep(Duration::from_secs(1));
+    }
+    assert_program_error(
+        renounce_admin(&program, &payer, &[new_admin.pubkey()]),
+        KeyringError::ErrLastAdmin,
+    );
+    let expires_at = get_timestamp(&anchor_rpc_client) as i64 + 60;
+    assert_program_error(
+        update_own_admin(&program, &payer, true, Some(expires_at), &[]),
+        KeyringError::ErrLastAdmin,
+    );
+
+    // Hand over to the new admin
+    manage_role(
+        &program,
+        DEFAULT_INSTANCE,
+        &payer,
+        DEFAULT_ADMIN_ROLE,
+        DEFAULT_ADMIN_ROLE,
+        new_admin.pubkey(),
+        true,
+        None,
+    )
+    .expect("Admin must be able to grant admin role");
+    assert_program_error(
+        renounce_admin(&program, &payer, &[]),
+        KeyringError::ErrLastAdmin,
+    );
+    update_own_admin(
+        &program,
+        &payer,
+        true,
+        Some(expires_at),
+        &[new_admin.pubkey()],
+    )
+    .expect("Admin must be able to let its grant expire while another admin remains");
+    renounce_admin(&program, &payer, &[new_admin.pubkey()])
+        .expect("Admin must be able to revoke itself while another admin remains");
+    assert_eq!(
+        members(&program, DEFAULT_ADMIN_ROLE),
+        vec![new_admin.pubkey()]
+    );
+    assert_program_error(
+        manage_role(
+            &program,
+            DEFAULT_INSTANCE,
+            &new_admin,
+            DEFAULT_ADMIN_ROLE,
+            DEFAULT_ADMIN_ROLE,
+            new_admin.pubkey(),
+            false,
+            None,
+        ),
+        KeyringError::ErrLastAdmin,
+    );
+}
@@ -0,0 +1,307 @@
+use crate::common::{
+    generate_random_chain_id, get_role_definition, get_role_members, get_timestamp, init_program,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, Cluster,
+};
+use keyring_network::common::types::{
+    KeyEntry, KeyRegistry, ToHash, DEFAULT_INSTANCE, KEY_MANAGER_ROLE,
+};
+use keyring_network::ID as program_id;
+use rand::rngs::OsRng;
+use std::thread::sleep;
+use std::time::Duration;
+
+// Seconds for which a rotated key stays valid
+const OVERLAP: u64 = 3;
+
+fn generate_key(program: &Pubkey) -> (Vec<u8>, Pubkey) {
+    let mut os_rng = OsRng;
+    let secret_key = libsecp256k1::SecretKey::random(&mut os_rng);
+    let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+    let key = public_key.serialize()[1..].to_vec();
+    let key_hash = key.to_hash();
+    let key_mapping_seeds = [
+        b"keyring_program".as_ref(),
+        b"_key_mapping".as_ref(),
+        key_hash.as_ref(),
+    ];
+    let (key_mapping_pubkey, _) = Pubkey::find_program_address(&key_mapping_seeds, program);
+    (key, key_mapping_pubkey)
+}
+
+#[test]
+fn rotate_key() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    // Let's fund dummy payer
+    let dummy_payer = Keypair::new();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+    rpc.request_airdrop(&dummy_payer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (_, _, default_admin_role_pubkey) = init_program(&program, &payer, chain_id);
+
+    let (first_key, first_key_mapping_pubkey) = generate_key(&program.id());
+    let (second_key, second_key_mapping_pubkey) = generate_key(&program.id());
+    let (third_key, third_key_mapping_pubkey) = generate_key(&program.id());
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_admin, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_dummy_payer, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            dummy_payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRole {
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
+            role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
+            role: KEY_MANAGER_ROLE,
+            user: payer.pubkey(),
+            has_role: true,
+            expires_at: None,
+        })
+        .send()
+        .expect("Current admin must be able to grant key manager role");
+
+    let timestamp = get_timestamp(&rpc);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: first_key_mapping_pubkey,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: first_key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 1000,
+        })
+        .send()
+        .expect("Valid key registration must be successful");
+
+    // Non key manager must not be able to rotate keys
+    program
+        .request()
+        .accounts(keyring_network::accounts::RotateKey {
+            key_registry,
+            signer: dummy_payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_dummy_payer,
+            old_key_mapping: first_key_mapping_pubkey,
+            new_key_mapping: second_key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RotateKey {
+            instance: DEFAULT_INSTANCE,
+            old_key: first_key.clone(),
+            new_key: second_key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 1000,
+            overlap: 0,
+        })
+        .payer(&dummy_payer)
+        .send()
+        .expect_err("DummyPayer must not be allowed to rotate key");
+
+    // Key cannot be rotated to itself
+    program
+        .request()
+        .accounts(keyring_network::accounts::RotateKey {
+            key_registry,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            old_key_mapping: first_key_mapping_pubkey,
+            new_key_mapping: first_key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RotateKey {
+            instance: DEFAULT_INSTANCE,
+            old_key: first_key.clone(),
+            new_key: first_key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 1000,
+            overlap: 0,
+        })
+        .send()
+        .expect_err("Key cannot be rotated to itself");
+
+    // Invalid parameters of new key must fail the whole rotation
+    program
+        .request()
+        .accounts(keyring_network::accounts::RotateKey {
+            key_registry,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            old_key_mapping: first_key_mapping_pubkey,
+            new_key_mapping: second_key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RotateKey {
+            instance: DEFAULT_INSTANCE,
+            old_key: first_key.clone(),
+            new_key: second_key.clone(),
+            valid_from: timestamp + 1000,
+            valid_to: timestamp - 1,
+            overlap: 0,
+        })
+        .send()
+        .expect_err("Invalid key registration params must not be accepted");
+
+    let first_key_entry: KeyEntry = program.account(first_key_mapping_pubkey).unwrap();
+    assert!(first_key_entry.is_valid);
+
+    // Rotation without overlap revokes old key immediately
+    program
+        .request()
+        .accounts(keyring_network::accounts::RotateKey {
+            key_registry,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            old_key_mapping: first_key_mapping_pubkey,
+            new_key_mapping: second_key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RotateKey {
+            instance: DEFAULT_INSTANCE,
+            old_key: first_key.clone(),
+            new_key: second_key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 1000,
+            overlap: 0,
+        })
+        .send()
+        .expect("Key manager must be allowed to rotate key");
+
+    let first_key_entry: KeyEntry = program.account(first_key_mapping_pubkey).unwrap();
+    assert!(!first_key_entry.is_valid);
+    let second_key_entry: KeyEntry = program.account(second_key_mapping_pubkey).unwrap();
+    assert!(second_key_entry.is_valid);
+    assert_eq!(second_key_entry.valid_from, timestamp - 1);
+    assert_eq!(second_key_entry.valid_to, timestamp + 1000);
+    let key_registry_account: KeyRegistry = program.account(key_registry).unwrap();
+    assert_eq!(key_registry_account.active_keys, vec![second_key.clone()]);
+
+    // Revoked key cannot be rotated
+    program
+        .request()
+        .accounts(keyring_network::accounts::RotateKey {
+            key_registry,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            old_key_mapping: first_key_mapping_pubkey,
+            new_key_mapping: third_key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RotateKey {
+            instance: DEFAULT_INSTANCE,
+            old_key: first_key.clone(),
+            new_key: third_key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 1000,
+            overlap: 0,
+        })
+        .send()
+        .expect_err("Revoked key cannot be rotated");
+
+    // Rotation with overlap keeps old key valid for the overlap period
+    let timestamp = get_timestamp(&rpc);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RotateKey {
+            key_registry,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            old_key_mapping: second_key_mapping_pubkey,
+            new_key_mapping: third_key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RotateKey {
+            instance: DEFAULT_INSTANCE,
+            old_key: second_key.clone(),
+            new_key: third_key.clone(),
+            valid_from: timestamp,
+            valid_to: timestamp + 2000,
+            overlap: OVERLAP,
+        })
+        .send()
+        .expect("Key manager must be allowed to rotate key with overlap");
+
+    let second_key_entry: KeyEntry = program.account(second_key_mapping_pubkey).unwrap();
+    assert!(second_key_entry.is_valid);
+    assert!(second_key_entry.valid_to >= timestamp + OVERLAP);
+    assert!(second_key_entry.valid_to < timestamp + 1000);
+    let third_key_entry: KeyEntry = program.account(third_key_mapping_pubkey).unwrap();
+    assert!(third_key_entry.is_valid);
+    let key_registry_account: KeyRegistry = program.account(key_registry).unwrap();
+    assert_eq!(
+        key_registry_account.active_keys,
+        vec![second_key.clone(), third_key.clone()]
+    );
+
+    // The old key keeps its slot in the active keys after the overlap until it
+    // is revoked
+    while get_timestamp(&rpc) <= second_key_entry.valid_to {
+        sleep(Duration::from_secs(1));
+    }
+    let key_registry_account: KeyRegistry = program.account(key_registry).unwrap();
+    assert_eq!(key_registry_account.active_keys.len(), 2);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RevokeKey {
+            key_registry,
+            key_mapping: second_key_mapping_pubkey,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RevokeKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: second_key,
+        })
+        .send()
+        .expect("Key manager must be allowed to revoke the old key after the overlap");
+    let second_key_entry: KeyEntry = program.account(second_key_mapping_pubkey).unwrap();
+    assert!(!second_key_entry.is_valid);
+    let key_registry_account: KeyRegistry = program.account(key_registry).unwrap();
+    assert_eq!(key_registry_account.active_keys, vec![third_key]);
+}
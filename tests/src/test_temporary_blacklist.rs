@@ -0,0 +1,249 @@
+use crate::common::{
+    assert_program_error, generate_random_chain_id, get_entity_mapping, get_key_mapping,
+    get_role_account, get_timestamp, grant_role, init_program, submit_credential,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    BlacklistReason, Blacklisting, EntityData, BLACKLIST_MANAGER_ROLE, CURRENT_VERSION,
+    DEFAULT_INSTANCE, KEY_MANAGER_ROLE,
+};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+use std::thread::sleep;
+use std::time::Duration;
+
+const POLICY_ID: u64 = 1;
+const SUSPENSION: u64 = 5;
+
+fn entity_mapping(trading_address: &Pubkey) -> Pubkey {
+    get_entity_mapping(&program_id, &DEFAULT_INSTANCE, POLICY_ID, trading_address)
+}
+
+fn blacklist_entity(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    trading_address: Pubkey,
+    reason: BlacklistReason,
+    until: Option<u64>,
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::BlacklistEntity {
+            signer: signer.pubkey(),
+            blacklist_manager_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &BLACKLIST_MANAGER_ROLE,
+                &signer.pubkey(),
+            ),
+            entity_mapping: entity_mapping(&trading_address),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::BlacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
+            policy_id: POLICY_ID,
+            trading_address,
+            reason,
+            until,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn check_credential(
+    program: &Program<&Keypair>,
+    trading_address: Pubkey,
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::CheckCredential {
+            signer: program.payer(),
+            entity_mapping: entity_mapping(&trading_address),
+        })
+        .args(keyring_network::instruction::CheckCredentialV2 {
+            instance: DEFAULT_INSTANCE,
+            policy_id: POLICY_ID,
+            trading_address,
+        })
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+#[test]
+fn test_temporary_blacklist() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (program_state, _, _) = init_program(&program, &payer, chain_id.clone());
+
+    for role in [KEY_MANAGER_ROLE, BLACKLIST_MANAGER_ROLE] {
+        grant_role(&program, &payer, role, payer.pubkey())
+            .expect("Current admin must be able to grant role");
+    }
+
+    let secret_key = SecretKey::random(&mut rng);
+    let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+    let timestamp = get_timestamp(&anchor_rpc_client);
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program_id,
+    );
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: get_key_mapping(&program_id, &key),
+            signer: payer.pubkey(),
+            key_manager_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &KEY_MANAGER_ROLE,
+                &payer.pubkey(),
+            ),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect("Valid key registration must be successful");
+
+    let trading_address = Pubkey::new_unique();
+    assert_program_error(
+        blacklist_entity(
+            &program,
+            &payer,
+            trading_address,
+            BlacklistReason::FraudReview,
+            Some(timestamp - 1),
+        ),
+        KeyringError::ErrInvalidBlacklistExpiry,
+    );
+
+    blacklist_entity(
+        &program,
+        &payer,
+        trading_address,
+        BlacklistReason::Sanctions,
+        None,
+    )
+    .expect("Blacklist manager should be able to blacklist entity");
+    let entity_data: EntityData = program.account(entity_mapping(&trading_address)).unwrap();
+    assert_eq!(
+        entity_data.blacklisting,
+        Blacklisting {
+            reason: BlacklistReason::Sanctions,
+            until: None,
+        }
+    );
+
+    // Blacklisting again replaces the terms of the blacklisting
+    let until = get_timestamp(&anchor_rpc_client) + SUSPENSION;
+    blacklist_entity(
+        &program,
+        &payer,
+        trading_address,
+        BlacklistReason::FraudReview,
+        Some(until),
+    )
+    .expect("Blacklist manager should be able to suspend entity");
+    let entity_data: EntityData = program.account(entity_mapping(&trading_address)).unwrap();
+    assert_eq!(
+        entity_data,
+        EntityData {
+            version: CURRENT_VERSION,
+            blacklisted: true,
+            exp: 0,
+            blacklisting: Blacklisting {
+                reason: BlacklistReason::FraudReview,
+                until: Some(until),
+            },
+        }
+    );
+
+    let valid_until = until + 10000;
+    assert_program_error(
+        submit_credential(
+            &program,
+            DEFAULT_INSTANCE,
+            program_state,
+            &chain_id,
+            &secret_key,
+            &key,
+            POLICY_ID,
+            trading_address,
+            valid_until,
+        ),
+        KeyringError::ErrEntityBlacklisted,
+    );
+
+    // The suspension is lifted once it expires
+    while get_timestamp(&anchor_rpc_client) <= until {
+        sleep(Duration::from_secs(1));
+    }
+    submit_credential(
+        &program,
+        DEFAULT_INSTANCE,
+        program_state,
+        &chain_id,
+        &secret_key,
+        &key,
+        POLICY_ID,
+        trading_address,
+        valid_until,
+    )
+    .expect("Credentials must be created once the suspension expired");
+    let entity_data: EntityData = program.account(entity_mapping(&trading_address)).unwrap();
+    assert_eq!(
+        entity_data,
+        EntityData {
+            version: CURRENT_VERSION,
+            blacklisted: false,
+            exp: valid_until,
+            blacklisting: Blacklisting::default(),
+        }
+    );
+    check_credential(&program, trading_address)
+        .expect("Credentials must be valid once the suspension expired");
+
+    // Suspended entities do not pass credential checks
+    let until = get_timestamp(&anchor_rpc_client) + 1000;
+    blacklist_entity(
+        &program,
+        &payer,
+        trading_address,
+        BlacklistReason::CourtOrder,
+        Some(until),
+    )
+    .expect("Blacklist manager should be able to suspend entity");
+    assert_program_error(
+        check_credential(&program, trading_address),
+        KeyringError::ErrInCheckingCredentials,
+    );
+}
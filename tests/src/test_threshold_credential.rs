@@ -0,0 +1,422 @@
+use crate::common::{
+    convert_pubkey_to_address, convert_secp_pubkey_to_address, create_credential,
+    generate_random_chain_id, get_key_mapping, get_policy_config, get_role_definition,
+    get_role_members, get_timestamp, init_program, sign_payload,
+};
+use anchor_client::anchor_lang::prelude::{AccountMeta, System};
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::secp256k1_recover::Secp256k1Pubkey;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program,
+};
+use keyring_network::common::types::{
+    AttestationFormat, Blacklisting, ChainId, EntityData, KeyEntry, PolicyConfig, CURRENT_VERSION,
+    DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE, KEY_MANAGER_ROLE, MAX_ATTESTATION_KEYS,
+};
+use keyring_network::common::verify_auth_message::create_signature_payload;
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+
+struct Attestation {
+    key: Vec<u8>,
+    signature: Vec<u8>,
+    key_mapping: Pubkey,
+}
+
+fn attest(program: &Pubkey, secret_key: &SecretKey, payload: &[u8]) -> Attestation {
+    let key = libsecp256k1::PublicKey::from_secret_key(secret_key).serialize()[1..].to_vec();
+    Attestation {
+        key_mapping: get_key_mapping(program, &key),
+        signature: sign_payload(secret_key, payload),
+        key,
+    }
+}
+
+fn create_threshold_credential(
+    program: &Program<&Keypair>,
+    policy_id: u64,
+    trading_address: Pubkey,
+    attestations: &[Attestation],
+    valid_until: u64,
+    cost: u64,
+    backdoor: Vec<u8>,
+) -> Result<(), Box<ClientError>> {
+    let (program_state, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"global_state".as_ref()],
+        &program.id(),
+    );
+    let (entity_mapping, _) = Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"_entity_mapping".as_ref(),
+            &policy_id.to_le_bytes(),
+            &trading_address.to_bytes(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::CreateThresholdCredential {
+            program_state,
+            signer: program.payer(),
+            policy_config: get_policy_config(&program.id(), policy_id),
+            entity_mapping,
+            system_program: System::id(),
+        })
+        .accounts(
+            attestations
+                .iter()
+                .map(|attestation| AccountMeta::new(attestation.key_mapping, false))
+                .collect::<Vec<_>>(),
+        )
+        .args(keyring_network::instruction::CreateThresholdCredential {
+            instance: DEFAULT_INSTANCE,
+            policy_id,
+            trading_address,
+            keys: attestations
+                .iter()
+                .map(|attestation| attestation.key.clone())
+                .collect(),
+            signatures: attestations
+                .iter()
+                .map(|attestation| attestation.signature.clone())
+                .collect(),
+            valid_until,
+            cost,
+            backdoor,
+            format: AttestationFormat::Packed,
+        })
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+#[test]
+fn threshold_credential() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    // Let's fund dummy payer
+    let dummy_payer = Keypair::new();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+    rpc.request_airdrop(&dummy_payer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (program_state_pubkey, _, default_admin_role_pubkey) =
+        init_program(&program, &payer, chain_id.clone());
+
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_admin, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+    let (default_admin_role_account_for_dummy_payer, _) = Pubkey::find_program_address(
+        &[
+            DEFAULT_ADMIN_ROLE.as_ref(),
+            dummy_payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRole {
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
+            role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
+            role: KEY_MANAGER_ROLE,
+            user: payer.pubkey(),
+            has_role: true,
+            expires_at: None,
+        })
+        .send()
+        .expect("Current admin must be able to grant key manager role");
+
+    let timestamp = get_timestamp(&rpc);
+    let secret_keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random(&mut rng)).collect();
+    for secret_key in &secret_keys {
+        let key = libsecp256k1::PublicKey::from_secret_key(secret_key).serialize()[1..].to_vec();
+        program
+            .request()
+            .accounts(keyring_network::accounts::RegisterKey {
+                key_registry,
+                key_mapping: get_key_mapping(&program.id(), &key),
+                signer: payer.pubkey(),
+                key_manager_role: key_manager_role_account_for_admin,
+                system_program: System::id(),
+            })
+            .args(keyring_network::instruction::RegisterKeyV2 {
+                instance: DEFAULT_INSTANCE,
+                key,
+                valid_from: timestamp - 1,
+                valid_to: timestamp + 10000,
+            })
+            .send()
+            .expect("Valid key registration must be successful");
+    }
+
+    let policy_id: u64 = 7;
+    let policy_config_pubkey = get_policy_config(&program.id(), policy_id);
+    let set_threshold = |signer: &Keypair, default_admin_role: Pubkey, threshold: u8| {
+        program
+            .request()
+            .accounts(keyring_network::accounts::SetPolicyThreshold {
+                signer: signer.pubkey(),
+                default_admin_role,
+                policy_config: policy_config_pubkey,
+                program_state: program_state_pubkey,
+                system_program: System::id(),
+            })
+            .args(keyring_network::instruction::SetPolicyThreshold {
+                instance: DEFAULT_INSTANCE,
+                policy_id,
+                threshold,
+            })
+            .payer(signer)
+            .send()
+            .map_err(Box::new)
+    };
+
+    set_threshold(&dummy_payer, default_admin_role_account_for_dummy_payer, 2)
+        .expect_err("DummyPayer must not be allowed to set policy threshold");
+    set_threshold(&payer, default_admin_role_pubkey, 0)
+        .expect_err("Zero threshold must be rejected");
+    set_threshold(&payer, default_admin_role_pubkey, MAX_ATTESTATION_KEYS + 1)
+        .expect_err("Threshold above the attestation limit must be rejected");
+
+    // Single signature is enough until a threshold is set
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_keys[0],
+        policy_id,
+        timestamp + 1000,
+    )
+    .expect("Single signature must be accepted without a threshold");
+
+    set_threshold(&payer, default_admin_role_pubkey, 2)
+        .expect("Admin must be allowed to set policy threshold");
+    let policy_config: PolicyConfig = program.account(policy_config_pubkey).unwrap();
+    assert_eq!(
+        policy_config,
+        PolicyConfig {
+            version: CURRENT_VERSION,
+            threshold: 2,
+        }
+    );
+
+    create_credential(
+        &program,
+        program_state_pubkey,
+        &chain_id,
+        &secret_keys[0],
+        policy_id,
+        timestamp + 1000,
+    )
+    .expect_err("Single signature must be rejected for a threshold policy");
+
+    let timestamp = get_timestamp(&rpc);
+    let trading_address = Pubkey::new_unique();
+    let valid_until = timestamp + 1000;
+    let cost = LAMPORTS_PER_SOL;
+    let backdoor = vec![2; 20];
+    let payload = create_signature_payload(
+        convert_pubkey_to_address(&trading_address),
+        policy_id,
+        ChainId::new(chain_id.clone()).unwrap(),
+        valid_until,
+        cost,
+        backdoor.clone(),
+    )
+    .unwrap();
+    let attestations: Vec<Attestation> = secret_keys
+        .iter()
+        .map(|secret_key| attest(&program.id(), secret_key, payload.as_ref()))
+        .collect();
+    let create = |attestations: &[Attestation]| {
+        create_threshold_credential(
+            &program,
+            policy_id,
+            trading_address,
+            attestations,
+            valid_until,
+            cost,
+            backdoor.clone(),
+        )
+    };
+
+    create(&attestations[..1]).expect_err("Attestations below the threshold must be rejected");
+
+    let duplicate = [
+        attest(&program.id(), &secret_keys[0], payload.as_ref()),
+        attest(&program.id(), &secret_keys[0], payload.as_ref()),
+    ];
+    create(&duplicate).expect_err("Duplicate keys must not count towards the threshold");
+
+    // Same key registered a second time by its address
+    let address = convert_secp_pubkey_to_address(&Secp256k1Pubkey::new(&attestations[0].key));
+    let address_key_mapping = get_key_mapping(&program.id(), &address);
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: address_key_mapping,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: address.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect("Valid key registration must be successful");
+    let duplicate_address = [
+        attest(&program.id(), &secret_keys[0], payload.as_ref()),
+        Attestation {
+            key: address,
+            key_mapping: address_key_mapping,
+            ..attest(&program.id(), &secret_keys[0], payload.as_ref())
+        },
+    ];
+    create(&duplicate_address)
+        .expect_err("Public key and address of one key must not count towards the threshold");
+
+    let swapped_accounts = [
+        Attestation {
+            key_mapping: attestations[1].key_mapping,
+            ..attest(&program.id(), &secret_keys[0], payload.as_ref())
+        },
+        Attestation {
+            key_mapping: attestations[0].key_mapping,
+            ..attest(&program.id(), &secret_keys[1], payload.as_ref())
+        },
+    ];
+    create(&swapped_accounts).expect_err("Key mappings must match the attesting keys");
+
+    let forged = [
+        attest(&program.id(), &secret_keys[0], payload.as_ref()),
+        Attestation {
+            signature: attestations[2].signature.clone(),
+            ..attest(&program.id(), &secret_keys[1], payload.as_ref())
+        },
+    ];
+    create(&forged).expect_err("Signature of another key must be rejected");
+
+    create(&attestations[..2]).expect("Attestations meeting the threshold must be accepted");
+
+    let entity_data: EntityData = program
+        .account(
+            Pubkey::find_program_address(
+                &[
+                    b"keyring_program".as_ref(),
+                    b"_entity_mapping".as_ref(),
+                    &policy_id.to_le_bytes(),
+                    &trading_address.to_bytes(),
+                ],
+                &program.id(),
+            )
+            .0,
+        )
+        .unwrap();
+    assert_eq!(
+        entity_data,
+        EntityData {
+            version: CURRENT_VERSION,
+            blacklisted: false,
+            exp: valid_until,
+            blacklisting: Blacklisting::default(),
+        }
+    );
+
+    // Every attesting key is counted against its quota
+    let key_entry: KeyEntry = program.account(attestations[0].key_mapping).unwrap();
+    assert_eq!(key_entry.usage.credentials_issued, 2);
+    let key_entry: KeyEntry = program.account(attestations[1].key_mapping).unwrap();
+    assert_eq!(key_entry.usage.credentials_issued, 1);
+    let key_entry: KeyEntry = program.account(attestations[2].key_mapping).unwrap();
+    assert_eq!(key_entry.usage.credentials_issued, 0);
+
+    // Revoked key cannot attest
+    program
+        .request()
+        .accounts(keyring_network::accounts::RevokeKey {
+            key_registry,
+            key_mapping: attestations[2].key_mapping,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RevokeKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: attestations[2].key.clone(),
+        })
+        .send()
+        .expect("Key manager must be allowed to revoke key");
+
+    let trading_address = Pubkey::new_unique();
+    let payload = create_signature_payload(
+        convert_pubkey_to_address(&trading_address),
+        policy_id,
+        ChainId::new(chain_id.clone()).unwrap(),
+        valid_until,
+        cost,
+        backdoor.clone(),
+    )
+    .unwrap();
+    let attestations: Vec<Attestation> = secret_keys
+        .iter()
+        .map(|secret_key| attest(&program.id(), secret_key, payload.as_ref()))
+        .collect();
+    create_threshold_credential(
+        &program,
+        policy_id,
+        trading_address,
+        &attestations[1..],
+        valid_until,
+        cost,
+        backdoor.clone(),
+    )
+    .expect_err("Revoked key must not attest");
+    create_threshold_credential(
+        &program,
+        policy_id,
+        trading_address,
+        &attestations[..2],
+        valid_until,
+        cost,
+        backdoor,
+    )
+    .expect("Attestations of two active keys must be accepted");
+}
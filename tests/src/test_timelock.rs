@@ -0,0 +1,328 @@
+use crate::common::{
+    assert_program_error, generate_random_chain_id, get_key_mapping, get_role_account,
+    get_timestamp, grant_role, init_program,
+};
+use anchor_client::anchor_lang::prelude::{AccountMeta, System};
+use anchor_client::anchor_lang::{Id, InstructionData, ToAccountMetas};
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    KeyEntry, ProgramConfig, ProgramState, Proposal, ProposalAccount, CANCELLER_ROLE,
+    DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE, KEY_MANAGER_ROLE, PROPOSER_ROLE,
+};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+use std::thread::sleep;
+use std::time::Duration;
+
+const TIMELOCK_DELAY: u64 = 5;
+
+fn proposal_account(proposal_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"keyring_program".as_ref(),
+            b"proposal".as_ref(),
+            &proposal_id.to_le_bytes(),
+        ],
+        &program_id,
+    )
+    .0
+}
+
+// Accounts and data of `register_key` signed by `signer`
+fn register_key_instruction(signer: &Pubkey, key: &[u8]) -> (Vec<ProposalAccount>, Vec<u8>) {
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program_id,
+    );
+    let accounts = keyring_network::accounts::RegisterKey {
+        key_registry,
+        key_mapping: get_key_mapping(&program_id, key),
+        signer: *signer,
+        key_manager_role: get_role_account(
+            &program_id,
+            &DEFAULT_INSTANCE,
+            &KEY_MANAGER_ROLE,
+            signer,
+        ),
+        system_program: System::id(),
+    }
+    .to_account_metas(None)
+    .into_iter()
+    .map(|account| ProposalAccount {
+        pubkey: account.pubkey,
+        is_signer: account.is_signer,
+        is_writable: account.is_writable,
+    })
+    .collect();
+    let data = keyring_network::instruction::RegisterKeyV2 {
+        instance: DEFAULT_INSTANCE,
+        key: key.to_vec(),
+        valid_from: 0,
+        valid_to: u32::MAX as u64,
+    }
+    .data();
+    (accounts, data)
+}
+
+fn schedule_proposal(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    proposal_id: u64,
+    (accounts, data): (Vec<ProposalAccount>, Vec<u8>),
+) -> Result<(), Box<ClientError>> {
+    let (program_state, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"global_state".as_ref()],
+        &program_id,
+    );
+    program
+        .request()
+        .accounts(keyring_network::accounts::ScheduleProposal {
+            proposal: proposal_account(proposal_id),
+            program_state,
+            proposer_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &PROPOSER_ROLE,
+                &signer.pubkey(),
+            ),
+            signer: signer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ScheduleProposal {
+            instance: DEFAULT_INSTANCE,
+            proposal_id,
+            accounts,
+            data,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn cancel_proposal(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    proposal_id: u64,
+    proposer: Pubkey,
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::CancelProposal {
+            proposal: proposal_account(proposal_id),
+            proposer,
+            canceller_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &CANCELLER_ROLE,
+                &signer.pubkey(),
+            ),
+            signer: signer.pubkey(),
+        })
+        .args(keyring_network::instruction::CancelProposal {
+            instance: DEFAULT_INSTANCE,
+            proposal_id,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn execute_proposal(
+    program: &Program<&Keypair>,
+    signer: &Keypair,
+    proposal_id: u64,
+    proposer: Pubkey,
+    accounts: &[ProposalAccount],
+) -> Result<(), Box<ClientError>> {
+    let (program_state, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"global_state".as_ref()],
+        &program_id,
+    );
+    program
+        .request()
+        .accounts(keyring_network::accounts::ExecuteProposal {
+            proposal: proposal_account(proposal_id),
+            proposer,
+            program_state,
+            signer: signer.pubkey(),
+            keyring_program: program_id,
+        })
+        .accounts(
+            accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: false,
+                    is_writable: account.is_writable,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .args(keyring_network::instruction::ExecuteProposal {
+            instance: DEFAULT_INSTANCE,
+            proposal_id,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn new_key() -> Vec<u8> {
+    let secret_key = SecretKey::random(&mut OsRng);
+    libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec()
+}
+
+#[test]
+fn test_timelock() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let canceller = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&canceller.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let executor = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&executor.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let (timelock, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"timelock".as_ref()],
+        &program_id,
+    );
+    // The timelock authority pays for the accounts created by proposals
+    anchor_rpc_client
+        .request_airdrop(&timelock, 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    init_program(&program, &payer, chain_id.clone());
+
+    let (program_state, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"global_state".as_ref()],
+        &program_id,
+    );
+    program
+        .request()
+        .accounts(keyring_network::accounts::UpdateConfig {
+            program_state,
+            default_admin_role: get_role_account(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &DEFAULT_ADMIN_ROLE,
+                &payer.pubkey(),
+            ),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UpdateConfig {
+            instance: DEFAULT_INSTANCE,
+            chain_id,
+            config: ProgramConfig {
+                timelock_delay: TIMELOCK_DELAY,
+                ..Default::default()
+            },
+        })
+        .signer(&payer)
+        .send()
+        .expect("Admin must be able to set the timelock delay");
+    let state: ProgramState = program.account(program_state).unwrap();
+    assert_eq!(state.config.timelock_delay, TIMELOCK_DELAY);
+
+    grant_role(&program, &payer, KEY_MANAGER_ROLE, timelock)
+        .expect("Admin must be able to grant roles to the timelock authority");
+    grant_role(&program, &payer, PROPOSER_ROLE, payer.pubkey())
+        .expect("Admin must be able to grant the proposer role");
+    grant_role(&program, &payer, CANCELLER_ROLE, canceller.pubkey())
+        .expect("Admin must be able to grant the canceller role");
+
+    let key = new_key();
+    assert_program_error(
+        schedule_proposal(
+            &program,
+            &canceller,
+            1,
+            register_key_instruction(&timelock, &key),
+        ),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+    // Nobody but the timelock authority can be required to sign
+    assert_program_error(
+        schedule_proposal(
+            &program,
+            &payer,
+            1,
+            register_key_instruction(&payer.pubkey(), &key),
+        ),
+        KeyringError::ErrInvalidProposal,
+    );
+
+    let now = get_timestamp(&anchor_rpc_client) as i64;
+    let (accounts, data) = register_key_instruction(&timelock, &key);
+    schedule_proposal(&program, &payer, 1, (accounts.clone(), data.clone()))
+        .expect("Proposer must be able to schedule a proposal");
+    let proposal: Proposal = program.account(proposal_account(1)).unwrap();
+    assert_eq!(proposal.proposer, payer.pubkey());
+    assert_eq!(proposal.accounts, accounts);
+    assert_eq!(proposal.data, data);
+    assert!(proposal.execute_after >= now + TIMELOCK_DELAY as i64);
+
+    assert_program_error(
+        execute_proposal(&program, &executor, 1, payer.pubkey(), &accounts),
+        KeyringError::ErrProposalNotReady,
+    );
+
+    // Cancelled proposals cannot be executed
+    let cancelled_key = new_key();
+    let (cancelled_accounts, cancelled_data) = register_key_instruction(&timelock, &cancelled_key);
+    schedule_proposal(
+        &program,
+        &payer,
+        2,
+        (cancelled_accounts.clone(), cancelled_data),
+    )
+    .expect("Proposer must be able to schedule a proposal");
+    assert_program_error(
+        cancel_proposal(&program, &executor, 2, payer.pubkey()),
+        KeyringError::ErrCallerDoesNotHaveRole,
+    );
+    cancel_proposal(&program, &canceller, 2, payer.pubkey())
+        .expect("Canceller must be able to cancel a proposal");
+    assert!(program.account::<Proposal>(proposal_account(2)).is_err());
+
+    while (get_timestamp(&anchor_rpc_client) as i64) < proposal.execute_after {
+        sleep(Duration::from_secs(1));
+    }
+    assert!(execute_proposal(&program, &executor, 2, payer.pubkey(), &cancelled_accounts).is_err());
+    assert_program_error(
+        execute_proposal(&program, &executor, 1, payer.pubkey(), &accounts[1..]),
+        KeyringError::ErrProposalAccountMismatch,
+    );
+
+    // Anyone can execute the proposal once the delay has passed
+    execute_proposal(&program, &executor, 1, payer.pubkey(), &accounts)
+        .expect("Anyone must be able to execute a proposal after the delay");
+    let key_entry: KeyEntry = program.account(get_key_mapping(&program_id, &key)).unwrap();
+    assert!(key_entry.is_valid);
+    assert!(program.account::<Proposal>(proposal_account(1)).is_err());
+    assert!(program
+        .account::<KeyEntry>(get_key_mapping(&program_id, &cancelled_key))
+        .is_err());
+}
@@ -1,4 +1,6 @@
-use crate::common::{generate_random_chain_id, init_program};
+use crate::common::{
+    generate_random_chain_id, get_role_definition, get_role_members, init_program,
+};
 use anchor_client::anchor_lang::prelude::System;
 use anchor_client::anchor_lang::Id;
 use anchor_client::solana_client::rpc_client::RpcClient;
@@ -9,7 +11,10 @@ use anchor_client::{
     solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
     Client, Cluster,
 };
-use keyring_network::common::types::{EntityData, BLACKLIST_MANAGER_ROLE, CURRENT_VERSION};
+use keyring_network::common::types::{
+    BlacklistReason, Blacklisting, EntityData, BLACKLIST_MANAGER_ROLE, CURRENT_VERSION,
+    DEFAULT_INSTANCE,
+};
 use keyring_network::ID as program_id;
 use rand::rngs::OsRng;
 
@@ -33,8 +38,7 @@ fn test_unblacklist_entity() {
 
     let mut rng = OsRng::default();
     let chain_id = generate_random_chain_id(&mut rng);
-    let (_, _, default_admin_role_pubkey) =
-        init_program(&program, &payer, chain_id);
+    let (_, _, default_admin_role_pubkey) = init_program(&program, &payer, chain_id);
 
     let policy_id: u64 = 1;
     let trading_address = Pubkey::new_unique();
@@ -64,15 +68,23 @@ fn test_unblacklist_entity() {
     program
         .request()
         .accounts(keyring_network::accounts::ManageRole {
-            default_admin_role: default_admin_role_pubkey,
+            role_definition: get_role_definition(
+                &program_id,
+                &DEFAULT_INSTANCE,
+                &BLACKLIST_MANAGER_ROLE,
+            ),
+            admin_role: default_admin_role_pubkey,
             role: blacklist_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &BLACKLIST_MANAGER_ROLE),
             signer: payer.pubkey(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::ManageRoles {
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
             role: BLACKLIST_MANAGER_ROLE,
             user: payer.pubkey(),
             has_role: true,
+            expires_at: None,
         })
         .send()
         .expect("Current admin must be able to grant blacklist manager role");
@@ -86,7 +98,8 @@ fn test_unblacklist_entity() {
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::UnblacklistEntity {
+        .args(keyring_network::instruction::UnblacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
         })
@@ -103,7 +116,8 @@ fn test_unblacklist_entity() {
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::UnblacklistEntity {
+        .args(keyring_network::instruction::UnblacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
         })
@@ -117,6 +131,7 @@ fn test_unblacklist_entity() {
             version: CURRENT_VERSION,
             blacklisted: false,
             exp: 0,
+            blacklisting: Blacklisting::default(),
         }
     );
 
@@ -128,9 +143,12 @@ fn test_unblacklist_entity() {
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::BlacklistEntity {
+        .args(keyring_network::instruction::BlacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
+            reason: BlacklistReason::Unspecified,
+            until: None,
         })
         .send()
         .expect("Blacklist manager should be able to blacklist entity");
@@ -142,6 +160,7 @@ fn test_unblacklist_entity() {
             version: CURRENT_VERSION,
             blacklisted: true,
             exp: 0,
+            blacklisting: Blacklisting::default(),
         }
     );
 
@@ -154,7 +173,8 @@ fn test_unblacklist_entity() {
             entity_mapping: entity_mapping_pubkey.clone(),
             system_program: System::id(),
         })
-        .args(keyring_network::instruction::UnblacklistEntity {
+        .args(keyring_network::instruction::UnblacklistEntityV2 {
+            instance: DEFAULT_INSTANCE,
             policy_id,
             trading_address,
         })
@@ -168,6 +188,7 @@ fn test_unblacklist_entity() {
             version: CURRENT_VERSION,
             blacklisted: false,
             exp: 0,
+            blacklisting: Blacklisting::default(),
         }
     );
 }
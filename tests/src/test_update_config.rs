@@ -0,0 +1,289 @@
+use crate::common::{
+    assert_program_error, create_credential_with_key, generate_random_chain_id, get_key_mapping,
+    get_role_definition, get_role_members, get_timestamp, init_program,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, ClientError, Cluster, Program,
+};
+use keyring_network::common::error::KeyringError;
+use keyring_network::common::types::{
+    ProgramConfig, ProgramState, CHAIN_ID_MIN_SIZE, DEFAULT_ADMIN_ROLE, DEFAULT_INSTANCE,
+    KEY_MANAGER_ROLE, OPERATOR_ROLE, PAUSE_CREDENTIALS, PAUSE_FEE_COLLECTION,
+};
+use keyring_network::ID as program_id;
+use libsecp256k1::SecretKey;
+use rand::rngs::OsRng;
+
+fn update_config(
+    program: &Program<&Keypair>,
+    program_state: Pubkey,
+    signer: &Keypair,
+    chain_id: Vec<u8>,
+    config: ProgramConfig,
+) -> Result<(), Box<ClientError>> {
+    let (default_admin_role, _) = Pubkey::find_program_address(
+        &[
+            DEFAULT_ADMIN_ROLE.as_ref(),
+            signer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+    program
+        .request()
+        .accounts(keyring_network::accounts::UpdateConfig {
+            program_state,
+            default_admin_role,
+            signer: signer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UpdateConfig {
+            instance: DEFAULT_INSTANCE,
+            chain_id,
+            config,
+        })
+        .signer(signer)
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+fn collect_fees(
+    program: &Program<&Keypair>,
+    program_state: Pubkey,
+    operator_role: Pubkey,
+    receiver: Pubkey,
+) -> Result<(), Box<ClientError>> {
+    program
+        .request()
+        .accounts(keyring_network::accounts::CollectFees {
+            receiver_account: receiver,
+            signer: program.payer(),
+            operator_role,
+            program_state,
+        })
+        .args(keyring_network::instruction::CollectFeesV2 {
+            instance: DEFAULT_INSTANCE,
+        })
+        .send()
+        .map(|_| ())
+        .map_err(Box::new)
+}
+
+#[test]
+fn test_update_config() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let non_admin = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&non_admin.pubkey(), LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (program_state_pubkey, program_state, default_admin_role_pubkey) =
+        init_program(&program, &payer, chain_id.clone());
+    assert_eq!(program_state.config, ProgramConfig::default());
+
+    let role_account = |role: [u8; 32]| {
+        Pubkey::find_program_address(
+            &[role.as_ref(), payer.pubkey().to_bytes().as_ref()],
+            &program.id(),
+        )
+        .0
+    };
+    for role in [KEY_MANAGER_ROLE, OPERATOR_ROLE] {
+        program
+            .request()
+            .accounts(keyring_network::accounts::ManageRole {
+                role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &role),
+                admin_role: default_admin_role_pubkey,
+                role: role_account(role),
+                role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &role),
+                signer: payer.pubkey(),
+                system_program: System::id(),
+            })
+            .args(keyring_network::instruction::ManageRolesV2 {
+                instance: DEFAULT_INSTANCE,
+                role,
+                user: payer.pubkey(),
+                has_role: true,
+                expires_at: None,
+            })
+            .send()
+            .expect("Current admin must be able to grant role");
+    }
+
+    let timestamp = get_timestamp(&rpc);
+    let secret_key = SecretKey::random(&mut rng);
+    let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: get_key_mapping(&program.id(), &key),
+            signer: payer.pubkey(),
+            key_manager_role: role_account(KEY_MANAGER_ROLE),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect("Valid key registration must be successful");
+
+    update_config(
+        &program,
+        program_state_pubkey,
+        &non_admin,
+        chain_id.clone(),
+        ProgramConfig::default(),
+    )
+    .expect_err("Only admin can update the config");
+    assert_program_error(
+        update_config(
+            &program,
+            program_state_pubkey,
+            &payer,
+            vec![1; CHAIN_ID_MIN_SIZE - 1],
+            ProgramConfig::default(),
+        ),
+        KeyringError::ErrInvalidChainId,
+    );
+
+    let new_chain_id = generate_random_chain_id(&mut rng);
+    let fee_vault = Pubkey::new_unique();
+    let config = ProgramConfig {
+        fee_vault: Some(fee_vault),
+        pause_flags: PAUSE_CREDENTIALS,
+        max_credential_validity: 1000,
+        min_credential_cost: LAMPORTS_PER_SOL,
+        timelock_delay: 0,
+        approval_threshold: 0,
+    };
+    update_config(
+        &program,
+        program_state_pubkey,
+        &payer,
+        new_chain_id.clone(),
+        config.clone(),
+    )
+    .expect("Admin must be able to update the config");
+
+    let program_state: ProgramState = program.account(program_state_pubkey).unwrap();
+    assert_eq!(
+        &program_state.chain_id.chain_id[..4],
+        new_chain_id.as_slice()
+    );
+    assert_eq!(program_state.config, config);
+
+    let create = |chain_id: &[u8], valid_until| {
+        create_credential_with_key(
+            &program,
+            program_state_pubkey,
+            chain_id,
+            &secret_key,
+            key.clone(),
+            1,
+            valid_until,
+        )
+    };
+
+    let timestamp = get_timestamp(&rpc);
+    assert_program_error(
+        create(&new_chain_id, timestamp + 100),
+        KeyringError::ErrProgramPaused,
+    );
+
+    let config = ProgramConfig {
+        pause_flags: 0,
+        ..config
+    };
+    update_config(
+        &program,
+        program_state_pubkey,
+        &payer,
+        new_chain_id.clone(),
+        config.clone(),
+    )
+    .expect("Admin must be able to unpause");
+
+    assert_program_error(
+        create(&new_chain_id, timestamp + 5000),
+        KeyringError::ErrCredentialValidityTooLong,
+    );
+    assert_program_error(
+        create(&chain_id, timestamp + 100),
+        KeyringError::ErrSignatureMismatch,
+    );
+    create(&new_chain_id, timestamp + 100)
+        .expect("Credential signed for the new chain id must be accepted");
+
+    update_config(
+        &program,
+        program_state_pubkey,
+        &payer,
+        new_chain_id.clone(),
+        ProgramConfig {
+            min_credential_cost: 2 * LAMPORTS_PER_SOL,
+            ..config.clone()
+        },
+    )
+    .expect("Admin must be able to raise the minimum cost");
+    assert_program_error(
+        create(&new_chain_id, timestamp + 100),
+        KeyringError::ErrCostBelowMinimum,
+    );
+
+    // Fees can only go to the fee vault
+    let operator_role = role_account(OPERATOR_ROLE);
+    assert_program_error(
+        collect_fees(
+            &program,
+            program_state_pubkey,
+            operator_role,
+            Pubkey::new_unique(),
+        ),
+        KeyringError::ErrInvalidFeeVault,
+    );
+    collect_fees(&program, program_state_pubkey, operator_role, fee_vault)
+        .expect("Fees must be collected to the fee vault");
+    assert_eq!(rpc.get_balance(&fee_vault).unwrap(), LAMPORTS_PER_SOL);
+
+    update_config(
+        &program,
+        program_state_pubkey,
+        &payer,
+        new_chain_id,
+        ProgramConfig {
+            pause_flags: PAUSE_FEE_COLLECTION,
+            ..config
+        },
+    )
+    .expect("Admin must be able to pause fee collection");
+    assert_program_error(
+        collect_fees(&program, program_state_pubkey, operator_role, fee_vault),
+        KeyringError::ErrProgramPaused,
+    );
+}
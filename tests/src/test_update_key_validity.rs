@@ -0,0 +1,260 @@
+use crate::common::{
+    generate_random_chain_id, get_role_definition, get_role_members, get_timestamp, init_program,
+};
+use anchor_client::anchor_lang::prelude::System;
+use anchor_client::anchor_lang::Id;
+use anchor_client::solana_client::rpc_client::RpcClient;
+use anchor_client::solana_sdk::native_token::LAMPORTS_PER_SOL;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    Client, Cluster,
+};
+use keyring_network::common::types::{
+    KeyEntry, ToHash, CURRENT_VERSION, DEFAULT_INSTANCE, KEY_MANAGER_ROLE,
+};
+use keyring_network::ID as program_id;
+use rand::rngs::OsRng;
+
+#[test]
+fn update_key_validity() {
+    let anchor_rpc_client = RpcClient::new(Cluster::Localnet.url());
+
+    let payer = Keypair::new();
+    anchor_rpc_client
+        .request_airdrop(&payer.pubkey(), 10000 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let client = Client::new_with_options(Cluster::Localnet, &payer, CommitmentConfig::confirmed());
+    let program = client.program(program_id).unwrap();
+
+    // Let's fund dummy payer
+    let dummy_payer = Keypair::new();
+    let rpc = RpcClient::new(Cluster::Localnet.url());
+    rpc.request_airdrop(&dummy_payer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+
+    let mut rng = OsRng;
+    let chain_id = generate_random_chain_id(&mut rng);
+    let (_, _, default_admin_role_pubkey) = init_program(&program, &payer, chain_id);
+
+    let secret_key = libsecp256k1::SecretKey::random(&mut rng);
+    let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+    let key = public_key.serialize()[1..].to_vec();
+    let key_hash = key.to_hash();
+    let key_mapping_seeds = [
+        b"keyring_program".as_ref(),
+        b"_key_mapping".as_ref(),
+        key_hash.as_ref(),
+    ];
+    let (key_mapping_pubkey, _) = Pubkey::find_program_address(&key_mapping_seeds, &program.id());
+    let (key_registry, _) = Pubkey::find_program_address(
+        &[b"keyring_program".as_ref(), b"active_keys".as_ref()],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_admin, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+    let (key_manager_role_account_for_dummy_payer, _) = Pubkey::find_program_address(
+        &[
+            KEY_MANAGER_ROLE.as_ref(),
+            dummy_payer.pubkey().to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::ManageRole {
+            role_definition: get_role_definition(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            admin_role: default_admin_role_pubkey,
+            role: key_manager_role_account_for_admin,
+            role_members: get_role_members(&program_id, &DEFAULT_INSTANCE, &KEY_MANAGER_ROLE),
+            signer: payer.pubkey(),
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::ManageRolesV2 {
+            instance: DEFAULT_INSTANCE,
+            role: KEY_MANAGER_ROLE,
+            user: payer.pubkey(),
+            has_role: true,
+            expires_at: None,
+        })
+        .send()
+        .expect("Current admin must be able to grant key manager role");
+
+    // Unregistered key cannot be updated
+    let timestamp = get_timestamp(&rpc);
+    program
+        .request()
+        .accounts(keyring_network::accounts::UpdateKeyValidity {
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            key_mapping: key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UpdateKeyValidity {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp,
+            valid_to: timestamp + 100,
+        })
+        .send()
+        .expect_err("Unregistered key cannot be updated");
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::RegisterKey {
+            key_registry,
+            key_mapping: key_mapping_pubkey,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RegisterKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 20,
+        })
+        .send()
+        .expect("Valid key registration must be successful");
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::UpdateKeyValidity {
+            signer: dummy_payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_dummy_payer,
+            key_mapping: key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UpdateKeyValidity {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp,
+            valid_to: timestamp + 100,
+        })
+        .payer(&dummy_payer)
+        .send()
+        .expect_err("DummyPayer must not be allowed to update key validity");
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::UpdateKeyValidity {
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            key_mapping: key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UpdateKeyValidity {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp - 100,
+            valid_to: timestamp - 10,
+        })
+        .send()
+        .expect_err("valid_to cannot be set in the past");
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::UpdateKeyValidity {
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            key_mapping: key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UpdateKeyValidity {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp + 100,
+            valid_to: timestamp + 100,
+        })
+        .send()
+        .expect_err("valid_to must be greater than valid_from");
+
+    // Extending validity of a live key
+    program
+        .request()
+        .accounts(keyring_network::accounts::UpdateKeyValidity {
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            key_mapping: key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UpdateKeyValidity {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp - 1,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect("Key manager must be allowed to extend key validity");
+
+    let key_entry: KeyEntry = program.account(key_mapping_pubkey).unwrap();
+    assert!(key_entry.is_valid);
+    assert_eq!(key_entry.version, CURRENT_VERSION);
+    assert_eq!(key_entry.valid_from, timestamp - 1);
+    assert_eq!(key_entry.valid_to, timestamp + 10000);
+
+    // Shortening validity of a live key
+    let timestamp = get_timestamp(&rpc);
+    program
+        .request()
+        .accounts(keyring_network::accounts::UpdateKeyValidity {
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            key_mapping: key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UpdateKeyValidity {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp,
+            valid_to: timestamp + 50,
+        })
+        .send()
+        .expect("Key manager must be allowed to shorten key validity");
+
+    let key_entry: KeyEntry = program.account(key_mapping_pubkey).unwrap();
+    assert_eq!(key_entry.valid_from, timestamp);
+    assert_eq!(key_entry.valid_to, timestamp + 50);
+
+    // Revoked key cannot be extended
+    program
+        .request()
+        .accounts(keyring_network::accounts::RevokeKey {
+            key_registry,
+            key_mapping: key_mapping_pubkey,
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::RevokeKeyV2 {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+        })
+        .send()
+        .expect("Key manager must be allowed to revoke key");
+
+    program
+        .request()
+        .accounts(keyring_network::accounts::UpdateKeyValidity {
+            signer: payer.pubkey(),
+            key_manager_role: key_manager_role_account_for_admin,
+            key_mapping: key_mapping_pubkey,
+            system_program: System::id(),
+        })
+        .args(keyring_network::instruction::UpdateKeyValidity {
+            instance: DEFAULT_INSTANCE,
+            key: key.clone(),
+            valid_from: timestamp,
+            valid_to: timestamp + 10000,
+        })
+        .send()
+        .expect_err("Revoked key cannot be extended");
+}
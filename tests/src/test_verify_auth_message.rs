@@ -1,8 +1,19 @@
-use crate::common::convert_secp_pubkey_to_address;
-use anchor_client::solana_sdk::secp256k1_recover::secp256k1_recover;
+use crate::common::{convert_secp_pubkey_to_address, sign_payload};
+use anchor_client::solana_sdk::keccak;
+use anchor_client::solana_sdk::keccak::Hash;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::secp256k1_recover::{secp256k1_recover, Secp256k1Pubkey};
 use keyring_network::common::error::KeyringError;
-use keyring_network::common::types::ChainId;
-use keyring_network::common::verify_auth_message::{create_signature_payload, split_signature};
+use keyring_network::common::typed_data::{
+    domain_separator, encode_uint, hash_credential, hash_typed_data,
+};
+use keyring_network::common::types::{AttestationFormat, ChainId, DEFAULT_INSTANCE};
+use keyring_network::common::verify_auth_message::{
+    create_attestation_payload, create_extended_signature_payload, create_signature_payload,
+    pack_auth_message, pack_extended_auth_message, signer_address, split_signature,
+    truncate_trading_address, verify_signature,
+};
+use libsecp256k1::SecretKey;
 use serde::{Deserialize, Serialize};
 use std::fs;
 
@@ -30,6 +41,24 @@ pub struct Secp256k1Vectors {
     pub vectors: Vec<Secp256k1Vector>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureEncodingVector {
+    pub description: String,
+    #[serde(with = "hex::serde")]
+    pub message_hash: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub key: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub signature: Vec<u8>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SignatureEncodingVectors {
+    pub vectors: Vec<SignatureEncodingVector>,
+}
+
 #[test]
 pub fn test_verify_auth_message() {
     let secp256k1_vectors = fs::read("./vectors/secp256k1_vector.json").unwrap();
@@ -86,3 +115,336 @@ pub fn test_verify_auth_message() {
         }
     }
 }
+
+#[test]
+pub fn test_signature_encodings() {
+    let vectors = fs::read("./vectors/signature_encodings.json").unwrap();
+    let vectors: SignatureEncodingVectors = serde_json::from_slice(&vectors).unwrap();
+
+    for vector in vectors.vectors {
+        match vector.error.as_deref() {
+            None => {
+                let (signature, recovery_id) = split_signature(vector.signature)
+                    .unwrap_or_else(|err| panic!("{}: {:?}", vector.description, err));
+                let recovered_pubkey =
+                    secp256k1_recover(&vector.message_hash, recovery_id, &signature).unwrap();
+                assert_eq!(
+                    recovered_pubkey.to_bytes().to_vec(),
+                    vector.key,
+                    "{}",
+                    vector.description
+                );
+            }
+            Some(error) => {
+                let expected = match error {
+                    "ErrInvalidSignatureLength" => KeyringError::ErrInvalidSignatureLength,
+                    "ErrInvalidRecoveryID" => KeyringError::ErrInvalidRecoveryID,
+                    "ErrMalleableSignature" => KeyringError::ErrMalleableSignature,
+                    unknown => panic!("Unknown error {}", unknown),
+                };
+                assert_eq!(
+                    split_signature(vector.signature).unwrap_err(),
+                    expected.into(),
+                    "{}",
+                    vector.description
+                );
+            }
+        }
+    }
+}
+
+#[test]
+pub fn test_verify_signature_by_address() {
+    let vectors = fs::read("./vectors/signature_encodings.json").unwrap();
+    let vectors: SignatureEncodingVectors = serde_json::from_slice(&vectors).unwrap();
+
+    for vector in vectors
+        .vectors
+        .into_iter()
+        .filter(|vector| vector.error.is_none())
+    {
+        let message_hash = Hash::new(&vector.message_hash);
+        let address = signer_address(&vector.key).unwrap();
+        assert_eq!(
+            address.to_vec(),
+            convert_secp_pubkey_to_address(&Secp256k1Pubkey::new(&vector.key))
+        );
+
+        // Key registered either as a public key or as its address
+        assert!(
+            verify_signature(&vector.key, &message_hash, vector.signature.clone()).unwrap(),
+            "{}",
+            vector.description
+        );
+        assert!(
+            verify_signature(&address, &message_hash, vector.signature.clone()).unwrap(),
+            "{}",
+            vector.description
+        );
+
+        let mut other_address = address;
+        other_address[0] ^= 1;
+        assert!(
+            !verify_signature(&other_address, &message_hash, vector.signature.clone()).unwrap()
+        );
+
+        assert_eq!(
+            verify_signature(&vector.key[..21], &message_hash, vector.signature).unwrap_err(),
+            KeyringError::ErrInvalidPubkeyLength.into()
+        );
+    }
+}
+
+#[test]
+pub fn test_typed_data_hash() {
+    // Example from the EIP-712 specification
+    let domain_separator = keccak::hashv(&[
+        keccak::hash(
+            b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)",
+        )
+        .as_ref(),
+        keccak::hash(b"Ether Mail").as_ref(),
+        keccak::hash(b"1").as_ref(),
+        &encode_uint(1),
+        &encode_address("cccccccccccccccccccccccccccccccccccccccc"),
+    ]);
+    assert_eq!(
+        hex::encode(domain_separator),
+        "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+    );
+
+    let person_type = b"Person(string name,address wallet)";
+    let hash_person = |name: &[u8], wallet: &str| {
+        keccak::hashv(&[
+            keccak::hash(person_type).as_ref(),
+            keccak::hash(name).as_ref(),
+            &encode_address(wallet),
+        ])
+    };
+    let mail = keccak::hashv(&[
+        keccak::hash(
+            b"Mail(Person from,Person to,string contents)Person(string name,address wallet)",
+        )
+        .as_ref(),
+        hash_person(b"Cow", "cd2a3d9f938e13cd947ec05abc7fe734df8dd826").as_ref(),
+        hash_person(b"Bob", "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb").as_ref(),
+        keccak::hash(b"Hello, Bob!").as_ref(),
+    ]);
+    assert_eq!(
+        hex::encode(mail),
+        "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+    );
+
+    assert_eq!(
+        hex::encode(hash_typed_data(&domain_separator, &mail)),
+        "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+    );
+}
+
+#[test]
+pub fn test_attestation_formats() {
+    let secret_key = SecretKey::parse(&[7u8; 32]).unwrap();
+    let key = libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..].to_vec();
+    let trading_address = Pubkey::new_from_array([9u8; 32]);
+    let chain_id = ChainId::new(vec![0, 0, 0, 1]).unwrap();
+    let payload = |format, instance: &Pubkey| {
+        create_attestation_payload(
+            format,
+            instance,
+            &trading_address,
+            2u64.pow(24),
+            chain_id.clone(),
+            u32::MAX as u64 + 1,
+            1_000_000,
+            vec![2; 20],
+        )
+    };
+
+    // Typed data has no range limits of the packed format
+    assert_eq!(
+        payload(AttestationFormat::Packed, &DEFAULT_INSTANCE).unwrap_err(),
+        KeyringError::ErrAuthMessageParameterOutOfRange.into()
+    );
+    let typed_data_hash = payload(AttestationFormat::Eip712, &DEFAULT_INSTANCE).unwrap();
+    assert_eq!(
+        typed_data_hash,
+        hash_typed_data(
+            &domain_separator(&chain_id, &keyring_network::ID, &DEFAULT_INSTANCE),
+            &hash_credential(
+                &trading_address,
+                2u64.pow(24),
+                u32::MAX as u64 + 1,
+                1_000_000,
+                &[2; 20]
+            ),
+        )
+    );
+
+    let signature = sign_payload(&secret_key, typed_data_hash.as_ref());
+    assert!(verify_signature(&key, &typed_data_hash, signature.clone()).unwrap());
+
+    // Signature over the typed data does not verify for another domain
+    let other_domain = hash_typed_data(
+        &domain_separator(
+            &chain_id,
+            &Pubkey::new_from_array([1u8; 32]),
+            &DEFAULT_INSTANCE,
+        ),
+        &hash_credential(
+            &trading_address,
+            2u64.pow(24),
+            u32::MAX as u64 + 1,
+            1_000_000,
+            &[2; 20],
+        ),
+    );
+    assert!(!verify_signature(&key, &other_domain, signature.clone()).unwrap());
+
+    // Nor for another instance, or a chain id sharing the leading bytes
+    let instance = Pubkey::new_from_array([3u8; 32]);
+    let other_instance = payload(AttestationFormat::Eip712, &instance).unwrap();
+    assert!(!verify_signature(&key, &other_instance, signature.clone()).unwrap());
+    let other_chain_id = hash_typed_data(
+        &domain_separator(
+            &ChainId::new(vec![0, 0, 0, 1, 5]).unwrap(),
+            &keyring_network::ID,
+            &DEFAULT_INSTANCE,
+        ),
+        &hash_credential(
+            &trading_address,
+            2u64.pow(24),
+            u32::MAX as u64 + 1,
+            1_000_000,
+            &[2; 20],
+        ),
+    );
+    assert!(!verify_signature(&key, &other_chain_id, signature).unwrap());
+}
+
+#[test]
+pub fn test_packed_attestation_instances() {
+    let trading_address = Pubkey::new_from_array([9u8; 32]);
+    let chain_id = ChainId::new(vec![0, 0, 0, 1]).unwrap();
+    let payload = |format, instance: &Pubkey| {
+        create_attestation_payload(
+            format,
+            instance,
+            &trading_address,
+            1,
+            chain_id.clone(),
+            1000,
+            1_000_000,
+            vec![2; 20],
+        )
+        .unwrap()
+    };
+
+    // The default instance keeps the original payload
+    assert_eq!(
+        payload(AttestationFormat::Packed, &DEFAULT_INSTANCE),
+        create_signature_payload(
+            truncate_trading_address(&trading_address),
+            1,
+            chain_id.clone(),
+            1000,
+            1_000_000,
+            vec![2; 20],
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        payload(AttestationFormat::PackedExtended, &DEFAULT_INSTANCE),
+        create_extended_signature_payload(
+            truncate_trading_address(&trading_address),
+            1,
+            chain_id.clone(),
+            1000,
+            1_000_000,
+            vec![2; 20],
+        )
+    );
+
+    // Other instances sign different payloads
+    let instance = Pubkey::new_from_array([3u8; 32]);
+    let other_instance = Pubkey::new_from_array([4u8; 32]);
+    for format in [AttestationFormat::Packed, AttestationFormat::PackedExtended] {
+        let instance_payload = payload(format, &instance);
+        assert_ne!(instance_payload, payload(format, &DEFAULT_INSTANCE));
+        assert_ne!(instance_payload, payload(format, &other_instance));
+    }
+}
+
+fn encode_address(address: &str) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&hex::decode(address).unwrap());
+    word
+}
+
+#[test]
+pub fn test_extended_auth_message() {
+    let trading_address = vec![0xaa; 20];
+    let chain_id = ChainId::new(vec![1, 2, 3, 4]).unwrap();
+    let backdoor = vec![0xbb; 3];
+
+    // Cost is packed as uint160 in both layouts
+    let packed = pack_auth_message(
+        trading_address.clone(),
+        0x010203,
+        chain_id.clone(),
+        0x04050607,
+        0x08090a0b0c0d0e0f,
+        backdoor.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        hex::encode(packed),
+        [
+            "aa".repeat(20).as_str(),
+            "00",
+            "010203",
+            "01020304",
+            "04050607",
+            "000000000000000000000000",
+            "08090a0b0c0d0e0f",
+            "bbbbbb",
+        ]
+        .concat()
+    );
+
+    let packed = pack_extended_auth_message(
+        trading_address.clone(),
+        u64::MAX - 1,
+        chain_id.clone(),
+        u64::MAX,
+        0x08090a0b0c0d0e0f,
+        backdoor.clone(),
+    );
+    assert_eq!(
+        hex::encode(packed),
+        [
+            "aa".repeat(20).as_str(),
+            "01",
+            "fffffffffffffffe",
+            "01020304",
+            "ffffffffffffffff",
+            "000000000000000000000000",
+            "08090a0b0c0d0e0f",
+            "bbbbbb",
+        ]
+        .concat()
+    );
+
+    // Same parameters do not produce the same payload in both layouts
+    assert_ne!(
+        create_signature_payload(
+            trading_address.clone(),
+            1,
+            chain_id.clone(),
+            1,
+            1,
+            backdoor.clone()
+        )
+        .unwrap(),
+        create_extended_signature_payload(trading_address, 1, chain_id, 1, 1, backdoor)
+    );
+}